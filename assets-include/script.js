@@ -97,6 +97,31 @@ let openArticles = new Buckets();
 
 let resolutionPath = [];
 
+/**
+ * Path of the website root, as in /team/notes. Empty when the website is hosted at the root.
+ */
+function basePath() {
+    let meta = document.querySelector("meta[name=base-path]");
+    return meta === null ? "" : meta.content;
+}
+
+/**
+ * User interface strings of the locale of the website.
+ */
+let localeStrings = null;
+
+/**
+ * Get a user interface string from the locale, or the key if it is not defined.
+ */
+function t(key) {
+    if (localeStrings === null) {
+        let script = document.getElementById("locale");
+        localeStrings = script === null ? {} : JSON.parse(script.textContent);
+    }
+    let string = localeStrings[key];
+    return string === undefined ? key : string;
+}
+
 /**
  * Read the article types, progress types and articles from data.
  */
@@ -254,11 +279,13 @@ function init() {
     // Article button tooltips.
     document.addEventListener("mouseover", event => {
         if (event.target.matches(".article > header > menu > .close-button")) {
-            createTooltip(event.target, document.createTextNode("Minimize article"));
+            createTooltip(event.target, document.createTextNode(t("MinimizeArticle")));
+        } else if (event.target.matches(".article > header > menu > .graph-button")) {
+            createTooltip(event.target, document.createTextNode(t("ViewNeighbourhood")));
         } else if (event.target.matches(".article > header > menu > a")) {
-            createTooltip(event.target, document.createTextNode("Go to class page"));
+            createTooltip(event.target, document.createTextNode(t("GoToClassPage")));
         } else if (event.target.matches(".article > header > menu > .class-button")) {
-            createTooltip(event.target, document.createTextNode("View articles in class"));
+            createTooltip(event.target, document.createTextNode(t("ViewClassArticles")));
         }
     });
     // Article parameter tooltips.
@@ -297,6 +324,8 @@ function init() {
     });
     document.addEventListener("mouseover", handleLinkGroupTooltip); // Deprecated
     document.addEventListener("click", handleArticleLinkFollow); // Click on article link.
+    document.addEventListener("click", handleReferenceFollow); // Click on inline reference.
+    document.addEventListener("mouseover", handleReferenceTooltip); // Hover inline reference.
     /// Article link hover.
     document.addEventListener("mouseover", event => {
         let target = event.target;
@@ -368,8 +397,82 @@ async function handleArticleLinkFollow(event) {
     target.parentElement.parentElement.after(article);
 }
 
+/**
+ * Find the inline reference an event target is part of, or null.
+ */
+function findReference(target) {
+    while (target !== null && !target.matches(".ref")) {
+        target = target.parentElement;
+    }
+    return target;
+}
+
+/**
+ * Handle click on inline reference. Opens the referenced article in the
+ * details tab. Follows the link to the class page if there is no details tab.
+ */
+async function handleReferenceFollow(event) {
+    let target = findReference(event.target);
+    if (target === null) return;
+    let articles = document.getElementById("articles");
+    if (articles === null) return;
+    event.preventDefault();
+    let classKey = target.getAttribute("data-class");
+    let articleKey = target.getAttribute("data-article");
+    if (prerenders.has(articleKey)) {
+        openPrerenderedArticle(articleKey);
+        return;
+    }
+    let loadedClass = await loadClass(classKey);
+    let article = generateArticle(loadedClass.type, classKey, articleKey, loadedClass.articles[articleKey]);
+    articles.appendChild(article);
+}
+
+/**
+ * Tooltip for inline reference. Shows the full name of the referenced article.
+ */
+async function handleReferenceTooltip(event) {
+    let target = findReference(event.target);
+    if (target === null) return;
+    let articleKey = target.getAttribute("data-article");
+    let prerender = prerenders.get(articleKey);
+    let div = document.createElement("div");
+    div.classList.add("serif");
+    if (prerender !== undefined) {
+        let name = prerender.querySelector("article > header > hgroup > h1");
+        if (name === null) return;
+        for (let c of name.childNodes) {
+            div.appendChild(c.cloneNode(true));
+        }
+    } else {
+        let loadedClass = await loadClass(target.getAttribute("data-class"));
+        let articleData = loadedClass.articles[articleKey];
+        if (articleData === undefined) return;
+        div.innerHTML = generateNameHtml(articleData.names[0]);
+    }
+    if (!target.matches(":hover")) return; // The mouse left while loading.
+    createTooltip(target, div);
+}
+
+/**
+ * Generate the HTML of a name in class data.
+ */
+function generateNameHtml(nameData) {
+    let html = "";
+    for (let element of nameData) {
+        if (!Array.isArray(element)) {
+            html += element;
+        } else if (element[1] === "") {
+            html += "<strong>" + element[0] + "</strong>";
+        } else {
+            html += "<b data-class=\"" + element[1] + "\">" + element[0] + "</b>";
+        }
+    }
+    return html;
+}
+
 async function readModel() {
-    let modelFile = await fetch("/model.json");
+    let modelFile = await fetch(basePath() + "/model.json");
     if (modelFile.status !== 200) {
         console.log("Failed to load model file.");
         return;
@@ -558,7 +661,7 @@ async function loadClass(key) {
         if (loading !== undefined) {
             return await loading;
         } else {
-            let classPath = "/classes/" + key + ".json";
+            let classPath = basePath() + "/classes/" + key + ".json";
             let future = fetch(classPath).then(file => {
                 if (!file.ok) {
                     return Promise.reject("Failed loading class " + key);
@@ -591,6 +694,10 @@ function generateArticle(typeKey, classKey, articleKey, articleData) {
     // header > hgroup
     let hgroup = document.createElement("hgroup");
     header.appendChild(hgroup);
+    let classData = loadedClasses.get(classKey);
+    if (classData !== undefined && classData.symbol !== undefined) {
+        hgroup.insertAdjacentHTML("beforeend", classData.symbol);
+    }
     {
         let h1 = document.createElement("h1");
         h1.innerHTML = getArticleFullNameHtml(articleData.names[0]);
@@ -613,8 +720,12 @@ function generateArticle(typeKey, classKey, articleKey, articleData) {
         let classButton = document.createElement("button");
         classButton.classList.add("class-button");
         menu.appendChild(classButton);
+        let graphButton = document.createElement("a");
+        graphButton.classList.add("graph-button");
+        graphButton.href = basePath() + "/graph.html?article=" + encodeURIComponent(articleKey);
+        menu.appendChild(graphButton);
         let classLink = document.createElement("a");
-        classLink.href = "/classes/" + classKey + ".html";
+        classLink.href = basePath() + "/classes/" + classKey + ".html";
         classLink.target = "_blank";
         menu.appendChild(classLink);
         let closeButton = document.createElement("button");
@@ -632,6 +743,10 @@ function generateArticle(typeKey, classKey, articleKey, articleData) {
         // content.appendChild(section);
         // section.textContent = articleData.content;
     }
+    // Backlinks
+    if (classData !== undefined && classData.backlinks !== undefined) {
+        article.insertAdjacentHTML("beforeend", classData.backlinks);
+    }
     // Links
     let links = document.createElement("footer");
     links.classList.add("links", "unloaded", "collapsed");
@@ -804,15 +919,18 @@ function populateClassDialog(dialog, c) {
     for (const entry of Object.entries(articles)) {
         let key = entry[0];
         let article = entry[1];
-        let dialogEntry = generateClassDialogEntry(key, article);
+        let dialogEntry = generateClassDialogEntry(key, article, c.symbol);
         list.appendChild(dialogEntry);
     }
     dialog.classList.remove("loading");
 }
 
-function generateClassDialogEntry(key, article) {
+function generateClassDialogEntry(key, article, symbol) {
     let li = document.createElement("li");
     li.setAttribute("data-article", key);
+    if (symbol !== undefined) {
+        li.insertAdjacentHTML("beforeend", symbol);
+    }
     let keySpan = document.createElement("span");
     keySpan.classList.add("key");
     keySpan.textContent = key;
@@ -956,7 +1074,7 @@ function fillProgressLog(key) {
             timestr += timestamp.getMinutes().toString().padStart(2, "0");
             timestr += "  ·  ";
             timestr += ((Date.now() - timestamp.getTime()) / (1000 * 60 * 60)).toFixed(1);
-            timestr += "h ago";
+            timestr += t("HoursAgo");
             log.insertAdjacentHTML("afterbegin", `
             <div class="progress-log-entry">
               <div class="progress-log-icon level${level}"></div>${timestr}<div class="close-button">C</div>
@@ -967,20 +1085,7 @@ function fillProgressLog(key) {
 }
 
 function monthstr(n) {
-    switch (n) {
-        case 0: return "Jan";
-        case 1: return "Feb";
-        case 2: return "Mar";
-        case 3: return "Apr";
-        case 4: return "Mai";
-        case 5: return "Jun";
-        case 6: return "Jul";
-        case 7: return "Aug";
-        case 8: return "Sep";
-        case 9: return "Okt";
-        case 10: return "Nov";
-        case 11: return "Des";
-    }
+    return t("Month" + (n + 1));
 }
 
 function setProgress(key, levelstr, timestamp) {
@@ -1217,6 +1322,361 @@ function setupToolbar() {
     });
 }
 
+/// Search
+
+let searchItems = null;
+let searchShards = new Map();
+
+/**
+ * Split text into lowercase terms. Must match `terms` in web/search.rs.
+ */
+function searchTerms(text) {
+    return text.split(/[^\p{L}\p{N}]+/u).filter(term => [...term].length >= 2).map(term => term.toLowerCase());
+}
+
+/**
+ * Name of the index shard of a term. Must match `shard_name` in web/search.rs.
+ */
+function searchShardName(term) {
+    let first = term[0];
+    return /[a-z0-9]/.test(first) ? first : "other";
+}
+
+async function loadSearchFile(name) {
+    let file = await fetch(basePath() + "/search/" + name + ".json");
+    if (!file.ok) return null;
+    return await file.json();
+}
+
+async function loadSearchShard(name) {
+    let shard = searchShards.get(name);
+    if (shard === undefined) {
+        shard = loadSearchFile(name).then(json => json === null ? {} : json);
+        searchShards.set(name, shard);
+    }
+    return await shard;
+}
+
+/**
+ * Find the items matching all terms of a query, ordered by score. The last
+ * term also matches as a prefix.
+ */
+async function search(query) {
+    let terms = searchTerms(query);
+    if (terms.length === 0) return [];
+    if (searchItems === null) {
+        searchItems = await loadSearchFile("items");
+        if (searchItems === null) return [];
+    }
+    let scores = null;
+    for (let i = 0; i < terms.length; i++) {
+        let term = terms[i];
+        let shard = await loadSearchShard(searchShardName(term));
+        let termScores = new Map();
+        for (let [indexTerm, postings] of Object.entries(shard)) {
+            if (indexTerm !== term && !(i === terms.length - 1 && indexTerm.startsWith(term))) continue;
+            for (let j = 0; j < postings.length; j += 2) {
+                termScores.set(postings[j], (termScores.get(postings[j]) ?? 0) + postings[j + 1]);
+            }
+        }
+        if (scores === null) {
+            scores = termScores;
+        } else {
+            for (let [item, score] of scores) {
+                if (termScores.has(item)) {
+                    scores.set(item, score + termScores.get(item));
+                } else {
+                    scores.delete(item);
+                }
+            }
+        }
+    }
+    return [...scores.entries()].sort((a, b) => b[1] - a[1]).map(([item]) => searchItems[item]);
+}
+
+function setupSearch() {
+    let box = document.getElementById("search-box");
+    let results = document.getElementById("search-results");
+    if (box === null || results === null) return;
+    box.addEventListener("input", async event => {
+        let query = box.value;
+        let found = await search(query);
+        if (box.value !== query) return; // A newer query is in progress.
+        results.replaceChildren();
+        for (let [title, href, type] of found.slice(0, 20)) {
+            let li = document.createElement("li");
+            li.classList.add(type + "-result");
+            let a = document.createElement("a");
+            a.href = basePath() + href;
+            a.textContent = title;
+            li.appendChild(a);
+            results.appendChild(li);
+        }
+    });
+    box.addEventListener("keydown", event => {
+        if (event.key === "Enter") {
+            let first = results.querySelector("a");
+            if (first !== null) window.location.href = first.href;
+        } else if (event.key === "Escape") {
+            box.value = "";
+            results.replaceChildren();
+        }
+    });
+}
+
+document.addEventListener("DOMContentLoaded", event => {
+    setupSearch();
+});
+
+/// Theme
+
+/**
+ * Colour theme of the page: the choice stored by the theme button, else the Theme
+ * setting of the project, else the colour scheme preferred by the system.
+ */
+function currentTheme() {
+    let stored = localStorage.getItem("theme");
+    if (stored === "light" || stored === "dark") return stored;
+    let meta = document.querySelector("meta[name=theme]");
+    let setting = meta === null ? "auto" : meta.content;
+    if (setting === "light" || setting === "dark") return setting;
+    return matchMedia("(prefers-color-scheme: light)").matches ? "light" : "dark";
+}
+
+function applyTheme() {
+    document.documentElement.setAttribute("data-theme", currentTheme());
+}
+
+/**
+ * Switch between the light and dark theme, and remember the choice.
+ */
+function toggleTheme() {
+    localStorage.setItem("theme", currentTheme() === "light" ? "dark" : "light");
+    applyTheme();
+}
+
+// Apply the theme before the page is shown, so that it does not flash.
+applyTheme();
+matchMedia("(prefers-color-scheme: light)").addEventListener("change", applyTheme);
+
+document.addEventListener("DOMContentLoaded", event => {
+    let button = document.getElementById("theme-button");
+    if (button !== null) {
+        button.addEventListener("click", toggleTheme);
+    }
+});
+
+/// Graph
+
+const SVG_NAMESPACE = "http://www.w3.org/2000/svg";
+
+/**
+ * Lay out a graph with a force simulation: nodes repel each other, edges pull
+ * their nodes together and a weak gravity keeps the graph centred. Returns a
+ * map from node key to position.
+ */
+function layoutGraph(nodes, edges) {
+    let distance = 80;
+    let steps = 300;
+    let positions = new Map();
+    // Start on a spiral, so that the layout is the same every time.
+    nodes.forEach((node, i) => {
+        let radius = distance * Math.sqrt(i);
+        let angle = i * 2.399963;
+        positions.set(node.key, { x: radius * Math.cos(angle), y: radius * Math.sin(angle) });
+    });
+    for (let step = 0; step < steps; step++) {
+        let forces = new Map(nodes.map(node => [node.key, { x: 0, y: 0 }]));
+        for (let i = 0; i < nodes.length; i++) {
+            let a = positions.get(nodes[i].key);
+            let forceA = forces.get(nodes[i].key);
+            for (let j = i + 1; j < nodes.length; j++) {
+                let b = positions.get(nodes[j].key);
+                let forceB = forces.get(nodes[j].key);
+                let dx = a.x - b.x;
+                let dy = a.y - b.y;
+                let d = Math.max(Math.sqrt(dx * dx + dy * dy), 0.01);
+                let f = distance * distance / d;
+                forceA.x += dx / d * f;
+                forceA.y += dy / d * f;
+                forceB.x -= dx / d * f;
+                forceB.y -= dy / d * f;
+            }
+        }
+        for (let edge of edges) {
+            let a = positions.get(edge.source);
+            let b = positions.get(edge.target);
+            let dx = a.x - b.x;
+            let dy = a.y - b.y;
+            let d = Math.max(Math.sqrt(dx * dx + dy * dy), 0.01);
+            let f = d * d / distance;
+            forces.get(edge.source).x -= dx / d * f;
+            forces.get(edge.source).y -= dy / d * f;
+            forces.get(edge.target).x += dx / d * f;
+            forces.get(edge.target).y += dy / d * f;
+        }
+        // Move each node along its force, at most as far as the temperature.
+        let temperature = distance * (1 - step / steps);
+        for (let node of nodes) {
+            let position = positions.get(node.key);
+            let force = forces.get(node.key);
+            force.x -= position.x * 0.1;
+            force.y -= position.y * 0.1;
+            let f = Math.sqrt(force.x * force.x + force.y * force.y);
+            if (f > 0) {
+                let move = Math.min(f, temperature);
+                position.x += force.x / f * move;
+                position.y += force.y / f * move;
+            }
+        }
+    }
+    return positions;
+}
+
+/**
+ * Draw a laid out graph in an SVG element.
+ */
+function drawGraph(svg, nodes, edges, positions, centre) {
+    let defs = document.createElementNS(SVG_NAMESPACE, "defs");
+    defs.innerHTML = `<marker id="graph-arrow" viewBox="0 0 10 10" refX="20" refY="5" markerWidth="6" markerHeight="6" orient="auto-start-reverse"><path d="M 0 0 L 10 5 L 0 10 z"></path></marker>`;
+    svg.appendChild(defs);
+    for (let edge of edges) {
+        let source = positions.get(edge.source);
+        let target = positions.get(edge.target);
+        let line = document.createElementNS(SVG_NAMESPACE, "line");
+        line.classList.add("graph-edge", edge.kind);
+        line.setAttribute("x1", source.x);
+        line.setAttribute("y1", source.y);
+        line.setAttribute("x2", target.x);
+        line.setAttribute("y2", target.y);
+        line.setAttribute("marker-end", "url(#graph-arrow)");
+        svg.appendChild(line);
+    }
+    for (let node of nodes) {
+        let position = positions.get(node.key);
+        let g = document.createElementNS(SVG_NAMESPACE, "g");
+        g.classList.add("graph-node");
+        if (node.key === centre) g.classList.add("centre");
+        g.setAttribute("data-class", node.key);
+        g.setAttribute("transform", "translate(" + position.x + " " + position.y + ")");
+        let circle = document.createElementNS(SVG_NAMESPACE, "circle");
+        circle.setAttribute("r", 8);
+        if (node.colour !== null) circle.style.fill = node.colour;
+        g.appendChild(circle);
+        let text = document.createElementNS(SVG_NAMESPACE, "text");
+        text.setAttribute("y", 22);
+        text.textContent = node.label;
+        g.appendChild(text);
+        let title = document.createElementNS(SVG_NAMESPACE, "title");
+        title.textContent = node.key;
+        g.appendChild(title);
+        svg.appendChild(g);
+    }
+    // Fit the view box to the graph.
+    let xs = nodes.map(node => positions.get(node.key).x);
+    let ys = nodes.map(node => positions.get(node.key).y);
+    let margin = 60;
+    let left = Math.min(...xs) - margin;
+    let top = Math.min(...ys) - margin;
+    svg.setAttribute("viewBox", [left, top, Math.max(...xs) + margin - left, Math.max(...ys) + margin - top].join(" "));
+}
+
+/**
+ * Zoom the graph with the mouse wheel and pan it by dragging.
+ */
+function setupGraphNavigation(svg) {
+    let drag = null;
+    svg.addEventListener("wheel", event => {
+        event.preventDefault();
+        let [x, y, width, height] = svg.getAttribute("viewBox").split(" ").map(Number);
+        let box = svg.getBoundingClientRect();
+        let scale = event.deltaY > 0 ? 1.1 : 1 / 1.1;
+        // Keep the point under the cursor in place.
+        let px = x + (event.clientX - box.left) / box.width * width;
+        let py = y + (event.clientY - box.top) / box.height * height;
+        svg.setAttribute("viewBox", [px - (px - x) * scale, py - (py - y) * scale, width * scale, height * scale].join(" "));
+    }, { passive: false });
+    svg.addEventListener("pointerdown", event => {
+        if (event.target.matches(".graph-node, .graph-node *")) return;
+        drag = { x: event.clientX, y: event.clientY };
+    });
+    svg.addEventListener("pointermove", event => {
+        if (drag === null) return;
+        let [x, y, width, height] = svg.getAttribute("viewBox").split(" ").map(Number);
+        let box = svg.getBoundingClientRect();
+        let scale = Math.max(width / box.width, height / box.height);
+        x -= (event.clientX - drag.x) * scale;
+        y -= (event.clientY - drag.y) * scale;
+        drag = { x: event.clientX, y: event.clientY };
+        svg.setAttribute("viewBox", [x, y, width, height].join(" "));
+    });
+    svg.addEventListener("pointerup", event => {
+        drag = null;
+    });
+    svg.addEventListener("pointerleave", event => {
+        drag = null;
+    });
+}
+
+/**
+ * Set up the graph page. With ?article=key, only the class of the article and
+ * the classes next to it are shown.
+ */
+function setupGraph() {
+    let svg = document.getElementById("graph");
+    if (svg === null) return;
+    let graph = JSON.parse(document.getElementById("graph-data").textContent);
+    let nodes = graph.nodes;
+    let edges = graph.edges;
+    let centre = null;
+    let article = new URLSearchParams(location.search).get("article");
+    if (article !== null && graph.articles[article] !== undefined) {
+        centre = graph.articles[article];
+        let neighbours = new Set([centre]);
+        for (let edge of edges) {
+            if (edge.source === centre) neighbours.add(edge.target);
+            if (edge.target === centre) neighbours.add(edge.source);
+        }
+        nodes = nodes.filter(node => neighbours.has(node.key));
+        edges = edges.filter(edge => neighbours.has(edge.source) && neighbours.has(edge.target));
+        let label = nodes.find(node => node.key === centre).label;
+        document.getElementById("graph-heading").textContent = t("Neighbourhood") + " " + label;
+    }
+    if (nodes.length === 0) return;
+    drawGraph(svg, nodes, edges, layoutGraph(nodes, edges), centre);
+    setupGraphNavigation(svg);
+    // Click node - Open class dialog.
+    svg.addEventListener("click", event => {
+        let element = event.target;
+        if (!element.matches(".graph-node, .graph-node *")) return;
+        while (!element.matches(".graph-node")) {
+            element = element.parentElement;
+        }
+        openClassDialog(element.getAttribute("data-class"), event.clientX, event.clientY);
+    });
+    // Click outside class dialog - delete dialog.
+    document.addEventListener("click", event => {
+        if (event.target.matches("#class-dialog, #class-dialog *, .graph-node, .graph-node *")) return;
+        let dialog = document.getElementById("class-dialog");
+        if (dialog !== null) document.body.removeChild(dialog);
+        activeClassDialog = null;
+    });
+    // Click class dialog entry - Go to the article on the class page.
+    document.addEventListener("click", event => {
+        let element = event.target;
+        if (!element.matches("#class-dialog > ul > li, #class-dialog > ul > li *")) return;
+        while (!element.matches("#class-dialog > ul > li")) {
+            element = element.parentElement;
+        }
+        let articleKey = element.getAttribute("data-article");
+        window.location.href = basePath() + "/classes/" + activeClass + ".html#article." + articleKey;
+    });
+}
+
+document.addEventListener("DOMContentLoaded", event => {
+    setupGraph();
+});
+
 /// Tooltips
 
 /**
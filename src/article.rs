@@ -5,7 +5,10 @@ use std::rc::{Rc, Weak};
 use crate::relation::Relation;
 use crate::markup::Markup;
 use crate::name::Name;
+use crate::table::Table;
+use crate::figure::Figure;
 use crate::types::ArticleMeta;
+use crate::document::Document;
 
 pub struct Articles {
     pub class_map: HashMap<Rc<str>, Rc<RefCell<Class>>>,
@@ -48,6 +51,24 @@ pub struct Class {
     /// Relations relevant to this class.
     pub(crate) relations: HashSet<Relation>,
     pub style: Option<Rc<str>>,
+    /// Documents and articles that refer to this class.
+    pub(crate) backlinks: Backlinks,
+}
+
+/// Documents that include a class in their panels or reference it inline, and
+/// articles that use it as a parameter or reference it inline.
+#[derive(Default)]
+pub struct Backlinks {
+    pub(crate) documents: Vec<Weak<Document>>,
+    pub(crate) articles: Vec<Rc<str>>,
+}
+
+impl Backlinks {
+
+    pub fn is_empty(&self) -> bool {
+        self.documents.is_empty() && self.articles.is_empty()
+    }
+
 }
 
 pub type Parameters = Box<[Rc<str>]>; // TODO: Weak<RefCell<Class>> instead of Rc<str>
@@ -86,6 +107,7 @@ impl Class {
             articles: vec![],
             relations: HashSet::new(),
             style: None,
+            backlinks: Backlinks::default(),
         }
     }
 
@@ -146,6 +168,8 @@ pub struct Article {
 pub enum ArticleElement {
     Heading { level: u8, markup: Markup },
     Markup(Markup),
+    Table(Table),
+    Figure(Figure),
     /// Line that indicates separation between two instances of an article in the document.
     LocalSeparator,
 }
@@ -0,0 +1,165 @@
+use std::collections::HashMap;
+use crate::markup::escape_html;
+
+/// Entries read from the BibTeX files of a project.
+///
+/// Entries are sorted by author, year and title. Numeric labels follow this order.
+pub struct Bibliography {
+    pub(crate) entries: Vec<BibEntry>,
+    pub(crate) style: CitationStyle,
+    key_map: HashMap<String, usize>,
+}
+
+/// How citations are labelled.
+#[derive(Copy, Clone, Eq, PartialEq)]
+pub enum CitationStyle {
+    /// Label citations with author and year, as in (Knuth, 1984).
+    AuthorYear,
+    /// Label citations with the number of the entry, as in \[3\].
+    Numeric,
+}
+
+/// A BibTeX entry.
+pub struct BibEntry {
+    pub(crate) key: String,
+    /// Entry type, as in book or article. Lowercase.
+    pub(crate) kind: String,
+    /// Fields with lowercase names and braces removed from the values.
+    pub(crate) fields: HashMap<String, String>,
+}
+
+impl Bibliography {
+
+    pub fn new(mut entries: Vec<BibEntry>, style: CitationStyle) -> Self {
+        entries.sort_by(|a, b| {
+            (a.author_names().join(" "), a.field("year"), a.field("title"))
+                .cmp(&(b.author_names().join(" "), b.field("year"), b.field("title")))
+        });
+        let mut key_map = HashMap::new();
+        for (i, entry) in entries.iter().enumerate() {
+            key_map.insert(entry.key.clone(), i);
+        }
+        Self { entries, style, key_map }
+    }
+
+    pub fn get(&self, key: &str) -> Option<&BibEntry> {
+        self.key_map.get(key).map(|i| &self.entries[*i])
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Number of an entry in numeric style, starting at 1.
+    pub fn number(&self, key: &str) -> Option<usize> {
+        self.key_map.get(key).map(|i| i + 1)
+    }
+
+    /// Label of a citation, with an optional locator such as a page.
+    pub fn citation_label(&self, key: &str, locator: Option<&str>) -> Option<String> {
+        let entry = self.get(key)?;
+        let locator = match locator {
+            Some(locator) => format!(", {}", locator),
+            None => String::new(),
+        };
+        let label = match self.style {
+            CitationStyle::AuthorYear => format!("({}, {}{})", escape_html(&entry.short_authors()), escape_html(entry.field("year")), locator),
+            CitationStyle::Numeric => format!("[{}{}]", self.number(key).unwrap(), locator),
+        };
+        Some(label)
+    }
+
+    /// Formatted reference of an entry in HTML, prefixed by its number in numeric style.
+    pub fn reference_html(&self, key: &str) -> Option<String> {
+        let entry = self.get(key)?;
+        let mut html = String::new();
+        if self.style == CitationStyle::Numeric {
+            html.push_str(&format!("[{}] ", self.number(key).unwrap()));
+        }
+        html.push_str(&entry.reference_html());
+        Some(html)
+    }
+
+}
+
+impl BibEntry {
+
+    /// Value of a field, or an empty string.
+    pub fn field(&self, name: &str) -> &str {
+        self.fields.get(name).map(|v| v.as_str()).unwrap_or("")
+    }
+
+    /// Last names of the authors, or of the editors if there are no authors.
+    pub fn author_names(&self) -> Vec<String> {
+        let authors = if self.fields.contains_key("author") { self.field("author") } else { self.field("editor") };
+        if authors.is_empty() {
+            return vec![];
+        }
+        authors.split(" and ").map(|author| {
+            let author = author.trim();
+            if let Some((last, _)) = author.split_once(',') {
+                last.trim().to_string()
+            } else {
+                author.rsplit(' ').next().unwrap().to_string()
+            }
+        }).collect()
+    }
+
+    /// Authors as written in an author-year label.
+    pub fn short_authors(&self) -> String {
+        let names = self.author_names();
+        match names.len() {
+            0 => self.key.clone(),
+            1 => names[0].clone(),
+            2 => format!("{} and {}", names[0], names[1]),
+            _ => format!("{} et al.", names[0]),
+        }
+    }
+
+    /// Reference in HTML: authors, year, title and where it was published.
+    pub fn reference_html(&self) -> String {
+        let mut html = String::new();
+        let names = self.author_names();
+        if !names.is_empty() {
+            html.push_str(&escape_html(&names.join(", ")));
+            html.push(' ');
+        }
+        if !self.field("year").is_empty() {
+            html.push_str(&format!("({}). ", escape_html(self.field("year"))));
+        }
+        match self.kind.as_str() {
+            "article" | "inproceedings" | "incollection" => {
+                html.push_str(&format!("{}. ", escape_html(self.field("title"))));
+                let container = if self.kind == "article" { self.field("journal") } else { self.field("booktitle") };
+                if !container.is_empty() {
+                    html.push_str(&format!("<em>{}</em>", escape_html(container)));
+                    if !self.field("volume").is_empty() {
+                        html.push_str(&format!(" {}", escape_html(self.field("volume"))));
+                    }
+                    if !self.field("pages").is_empty() {
+                        html.push_str(&format!(", {}", escape_html(self.field("pages"))));
+                    }
+                    html.push_str(". ");
+                }
+            }
+            _ => {
+                html.push_str(&format!("<em>{}</em>. ", escape_html(self.field("title"))));
+                if !self.field("edition").is_empty() {
+                    html.push_str(&format!("{} edition. ", escape_html(self.field("edition"))));
+                }
+                if !self.field("publisher").is_empty() {
+                    html.push_str(&format!("{}. ", escape_html(self.field("publisher"))));
+                }
+            }
+        }
+        if !self.field("doi").is_empty() {
+            let doi = escape_html(self.field("doi"));
+            html.push_str(&format!(r#"<a href="https://doi.org/{doi}">doi:{doi}</a>"#));
+        } else if !self.field("url").is_empty() {
+            let url = escape_html(self.field("url"));
+            html.push_str(&format!(r#"<a href="{url}">{url}</a>"#));
+        }
+        html.trim_end().to_string()
+    }
+
+}
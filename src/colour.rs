@@ -0,0 +1,274 @@
+//! CSS colours.
+
+/// Colour functions that are accepted without being evaluated.
+const OTHER_FUNCTIONS: [&str; 7] = ["hwb", "lab", "lch", "oklab", "oklch", "color", "color-mix"];
+
+/// Keywords that are valid colours but have no fixed value: `transparent`,
+/// `currentcolor` and the CSS-wide keywords.
+const OTHER_KEYWORDS: [&str; 7] = ["transparent", "currentcolor", "inherit", "initial", "unset", "revert", "revert-layer"];
+
+/// Named CSS colours with their RGB values.
+const NAMED_COLOURS: [(&str, u32); 148] = [
+    ("aliceblue", 0xf0f8ff), ("antiquewhite", 0xfaebd7), ("aqua", 0x00ffff), ("aquamarine", 0x7fffd4),
+    ("azure", 0xf0ffff), ("beige", 0xf5f5dc), ("bisque", 0xffe4c4), ("black", 0x000000),
+    ("blanchedalmond", 0xffebcd), ("blue", 0x0000ff), ("blueviolet", 0x8a2be2), ("brown", 0xa52a2a),
+    ("burlywood", 0xdeb887), ("cadetblue", 0x5f9ea0), ("chartreuse", 0x7fff00), ("chocolate", 0xd2691e),
+    ("coral", 0xff7f50), ("cornflowerblue", 0x6495ed), ("cornsilk", 0xfff8dc), ("crimson", 0xdc143c),
+    ("cyan", 0x00ffff), ("darkblue", 0x00008b), ("darkcyan", 0x008b8b), ("darkgoldenrod", 0xb8860b),
+    ("darkgray", 0xa9a9a9), ("darkgreen", 0x006400), ("darkgrey", 0xa9a9a9), ("darkkhaki", 0xbdb76b),
+    ("darkmagenta", 0x8b008b), ("darkolivegreen", 0x556b2f), ("darkorange", 0xff8c00), ("darkorchid", 0x9932cc),
+    ("darkred", 0x8b0000), ("darksalmon", 0xe9967a), ("darkseagreen", 0x8fbc8f), ("darkslateblue", 0x483d8b),
+    ("darkslategray", 0x2f4f4f), ("darkslategrey", 0x2f4f4f), ("darkturquoise", 0x00ced1), ("darkviolet", 0x9400d3),
+    ("deeppink", 0xff1493), ("deepskyblue", 0x00bfff), ("dimgray", 0x696969), ("dimgrey", 0x696969),
+    ("dodgerblue", 0x1e90ff), ("firebrick", 0xb22222), ("floralwhite", 0xfffaf0), ("forestgreen", 0x228b22),
+    ("fuchsia", 0xff00ff), ("gainsboro", 0xdcdcdc), ("ghostwhite", 0xf8f8ff), ("gold", 0xffd700),
+    ("goldenrod", 0xdaa520), ("gray", 0x808080), ("green", 0x008000), ("greenyellow", 0xadff2f),
+    ("grey", 0x808080), ("honeydew", 0xf0fff0), ("hotpink", 0xff69b4), ("indianred", 0xcd5c5c),
+    ("indigo", 0x4b0082), ("ivory", 0xfffff0), ("khaki", 0xf0e68c), ("lavender", 0xe6e6fa),
+    ("lavenderblush", 0xfff0f5), ("lawngreen", 0x7cfc00), ("lemonchiffon", 0xfffacd), ("lightblue", 0xadd8e6),
+    ("lightcoral", 0xf08080), ("lightcyan", 0xe0ffff), ("lightgoldenrodyellow", 0xfafad2), ("lightgray", 0xd3d3d3),
+    ("lightgreen", 0x90ee90), ("lightgrey", 0xd3d3d3), ("lightpink", 0xffb6c1), ("lightsalmon", 0xffa07a),
+    ("lightseagreen", 0x20b2aa), ("lightskyblue", 0x87cefa), ("lightslategray", 0x778899), ("lightslategrey", 0x778899),
+    ("lightsteelblue", 0xb0c4de), ("lightyellow", 0xffffe0), ("lime", 0x00ff00), ("limegreen", 0x32cd32),
+    ("linen", 0xfaf0e6), ("magenta", 0xff00ff), ("maroon", 0x800000), ("mediumaquamarine", 0x66cdaa),
+    ("mediumblue", 0x0000cd), ("mediumorchid", 0xba55d3), ("mediumpurple", 0x9370db), ("mediumseagreen", 0x3cb371),
+    ("mediumslateblue", 0x7b68ee), ("mediumspringgreen", 0x00fa9a), ("mediumturquoise", 0x48d1cc), ("mediumvioletred", 0xc71585),
+    ("midnightblue", 0x191970), ("mintcream", 0xf5fffa), ("mistyrose", 0xffe4e1), ("moccasin", 0xffe4b5),
+    ("navajowhite", 0xffdead), ("navy", 0x000080), ("oldlace", 0xfdf5e6), ("olive", 0x808000),
+    ("olivedrab", 0x6b8e23), ("orange", 0xffa500), ("orangered", 0xff4500), ("orchid", 0xda70d6),
+    ("palegoldenrod", 0xeee8aa), ("palegreen", 0x98fb98), ("paleturquoise", 0xafeeee), ("palevioletred", 0xdb7093),
+    ("papayawhip", 0xffefd5), ("peachpuff", 0xffdab9), ("peru", 0xcd853f), ("pink", 0xffc0cb),
+    ("plum", 0xdda0dd), ("powderblue", 0xb0e0e6), ("purple", 0x800080), ("rebeccapurple", 0x663399),
+    ("red", 0xff0000), ("rosybrown", 0xbc8f8f), ("royalblue", 0x4169e1), ("saddlebrown", 0x8b4513),
+    ("salmon", 0xfa8072), ("sandybrown", 0xf4a460), ("seagreen", 0x2e8b57), ("seashell", 0xfff5ee),
+    ("sienna", 0xa0522d), ("silver", 0xc0c0c0), ("skyblue", 0x87ceeb), ("slateblue", 0x6a5acd),
+    ("slategray", 0x708090), ("slategrey", 0x708090), ("snow", 0xfffafa), ("springgreen", 0x00ff7f),
+    ("steelblue", 0x4682b4), ("tan", 0xd2b48c), ("teal", 0x008080), ("thistle", 0xd8bfd8),
+    ("tomato", 0xff6347), ("turquoise", 0x40e0d0), ("violet", 0xee82ee), ("wheat", 0xf5deb3),
+    ("white", 0xffffff), ("whitesmoke", 0xf5f5f5), ("yellow", 0xffff00), ("yellowgreen", 0x9acd32),
+];
+
+/// An sRGB colour with channels from 0 to 1.
+#[derive(Copy, Clone)]
+pub struct Rgb(pub f64, pub f64, pub f64);
+
+impl Rgb {
+
+    fn from_hex(hex: u32) -> Self {
+        Rgb((hex >> 16 & 0xff) as f64 / 255.0, (hex >> 8 & 0xff) as f64 / 255.0, (hex & 0xff) as f64 / 255.0)
+    }
+
+    /// Relative luminance as defined by WCAG.
+    pub fn luminance(&self) -> f64 {
+        let linear = |c: f64| if c <= 0.04045 { c / 12.92 } else { ((c + 0.055) / 1.055).powf(2.4) };
+        0.2126 * linear(self.0) + 0.7152 * linear(self.1) + 0.0722 * linear(self.2)
+    }
+
+    /// Hexadecimal notation, as in #1a2b3c.
+    pub fn hex(&self) -> String {
+        let channel = |c: f64| (c.clamp(0.0, 1.0) * 255.0).round() as u8;
+        format!("#{:02x}{:02x}{:02x}", channel(self.0), channel(self.1), channel(self.2))
+    }
+
+    /// WCAG contrast ratio between two colours, from 1 to 21.
+    pub fn contrast(&self, other: &Rgb) -> f64 {
+        let (a, b) = (self.luminance(), other.luminance());
+        (a.max(b) + 0.05) / (a.min(b) + 0.05)
+    }
+
+}
+
+/// Parse a CSS colour.
+///
+/// Hexadecimal, named, `rgb()` and `hsl()` colours are evaluated. Other colour
+/// functions, such as `lch()`, keywords such as `currentcolor` and colours that
+/// use `var()` are accepted but return `None`, as long as their parentheses are
+/// balanced and they contain no `;`, `{`, `}` or line breaks.
+pub fn parse_css_colour(text: &str) -> Result<Option<Rgb>, String> {
+    let text = text.trim().to_lowercase();
+    if OTHER_KEYWORDS.contains(&text.as_str()) {
+        return Ok(None);
+    }
+    if text.contains("var(") {
+        check_unevaluated(&text)?;
+        return Ok(None);
+    }
+    if let Some(hex) = text.strip_prefix('#') {
+        if !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+            return Err(format!("{} is not a hexadecimal colour.", text));
+        }
+        let expanded: String = match hex.len() {
+            3 | 4 => hex.chars().take(3).flat_map(|c| [c, c]).collect(),
+            6 | 8 => hex[..6].to_string(),
+            _ => return Err(format!("{} must have 3, 4, 6 or 8 hexadecimal digits.", text)),
+        };
+        return Ok(Some(Rgb::from_hex(u32::from_str_radix(&expanded, 16).unwrap())));
+    }
+    if let Some((_, hex)) = NAMED_COLOURS.iter().find(|(name, _)| *name == text) {
+        return Ok(Some(Rgb::from_hex(*hex)));
+    }
+    let (function, arguments) = match text.find('(') {
+        Some(open) if text.ends_with(')') => (&text[..open], &text[open + 1..text.len() - 1]),
+        _ => return Err(format!("{} is not a CSS colour.", text)),
+    };
+    match function {
+        "rgb" | "rgba" => {
+            let channels = colour_arguments(&text, arguments)?;
+            let channel = |value: &str| -> Result<f64, String> {
+                match value.strip_suffix('%') {
+                    Some(percent) => parse_number(&text, percent).map(|p| p / 100.0),
+                    None => parse_number(&text, value).map(|v| v / 255.0),
+                }
+            };
+            Ok(Some(Rgb(channel(channels[0])?.clamp(0.0, 1.0), channel(channels[1])?.clamp(0.0, 1.0), channel(channels[2])?.clamp(0.0, 1.0))))
+        }
+        "hsl" | "hsla" => {
+            let channels = colour_arguments(&text, arguments)?;
+            let hue = parse_hue(&text, channels[0])?.rem_euclid(360.0);
+            let saturation = parse_number(&text, channels[1].trim_end_matches('%'))?.clamp(0.0, 100.0) / 100.0;
+            let lightness = parse_number(&text, channels[2].trim_end_matches('%'))?.clamp(0.0, 100.0) / 100.0;
+            let f = |n: f64| {
+                let k = (n + hue / 30.0) % 12.0;
+                let a = saturation * lightness.min(1.0 - lightness);
+                lightness - a * (k - 3.0).min(9.0 - k).min(1.0).max(-1.0)
+            };
+            Ok(Some(Rgb(f(0.0), f(8.0), f(4.0))))
+        }
+        function if OTHER_FUNCTIONS.contains(&function) => check_unevaluated(&text).map(|_| None),
+        _ => Err(format!("{} is not a CSS colour.", text)),
+    }
+}
+
+/// Split the arguments of a colour function, separated by commas or spaces and
+/// optionally followed by an alpha value.
+fn colour_arguments<'a>(text: &str, arguments: &'a str) -> Result<Vec<&'a str>, String> {
+    let arguments: Vec<&str> = arguments.split(|c: char| c == ',' || c == '/' || c.is_whitespace()).filter(|a| !a.is_empty()).collect();
+    if arguments.len() != 3 && arguments.len() != 4 {
+        return Err(format!("{} must have three channels and an optional alpha value.", text));
+    }
+    Ok(arguments)
+}
+
+/// Check that a colour that is not evaluated cannot break out of the CSS
+/// declaration it is written into.
+fn check_unevaluated(text: &str) -> Result<(), String> {
+    if text.contains(|c| c == ';' || c == '{' || c == '}' || c == '\n' || c == '\r') {
+        return Err(format!("{} must not contain ;, {{, }} or line breaks.", text));
+    }
+    let mut depth = 0usize;
+    for c in text.chars() {
+        match c {
+            '(' => depth += 1,
+            ')' => depth = depth.checked_sub(1).ok_or(format!("{} has unbalanced parentheses.", text))?,
+            _ => {}
+        }
+    }
+    if depth != 0 {
+        return Err(format!("{} has unbalanced parentheses.", text));
+    }
+    Ok(())
+}
+
+/// Parse a hue in degrees, or in one of the units `deg`, `grad`, `rad` and
+/// `turn`.
+fn parse_hue(text: &str, hue: &str) -> Result<f64, String> {
+    if let Some(degrees) = hue.strip_suffix("deg") {
+        parse_number(text, degrees)
+    } else if let Some(gradians) = hue.strip_suffix("grad") {
+        parse_number(text, gradians).map(|g| g * 0.9)
+    } else if let Some(radians) = hue.strip_suffix("rad") {
+        parse_number(text, radians).map(|r| r.to_degrees())
+    } else if let Some(turns) = hue.strip_suffix("turn") {
+        parse_number(text, turns).map(|t| t * 360.0)
+    } else {
+        parse_number(text, hue)
+    }
+}
+
+fn parse_number(text: &str, number: &str) -> Result<f64, String> {
+    number.parse().map_err(|_| format!("{} is not a number in colour {}.", number, text))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hex(text: &str) -> String {
+        parse_css_colour(text).unwrap().unwrap().hex()
+    }
+
+    #[test]
+    fn hexadecimal_colours() {
+        assert_eq!(hex("#abc"), "#aabbcc");
+        assert_eq!(hex("#abcd"), "#aabbcc");
+        assert_eq!(hex("#1A2b3C"), "#1a2b3c");
+        assert_eq!(hex("#1a2b3c80"), "#1a2b3c");
+        assert!(parse_css_colour("#12345").is_err());
+        assert!(parse_css_colour("#ggg").is_err());
+        assert!(parse_css_colour("#").is_err());
+    }
+
+    #[test]
+    fn named_colours() {
+        assert_eq!(hex("rebeccapurple"), "#663399");
+        assert_eq!(hex(" White "), "#ffffff");
+        assert!(parse_css_colour("notacolour").is_err());
+    }
+
+    #[test]
+    fn rgb_colours() {
+        assert_eq!(hex("rgb(255, 0, 128)"), "#ff0080");
+        assert_eq!(hex("rgb(255 0 128)"), "#ff0080");
+        assert_eq!(hex("rgb(255 0 128 / 50%)"), "#ff0080");
+        assert_eq!(hex("rgba(100%, 0%, 50%, 0.5)"), "#ff0080");
+        assert!(parse_css_colour("rgb(1, 2)").is_err());
+        assert!(parse_css_colour("rgb(a, b, c)").is_err());
+    }
+
+    #[test]
+    fn hsl_colours() {
+        assert_eq!(hex("hsl(120, 100%, 50%)"), "#00ff00");
+        assert_eq!(hex("hsl(120 100% 50%)"), "#00ff00");
+        assert_eq!(hex("hsl(120deg 100% 50% / 0.5)"), "#00ff00");
+        assert_eq!(hex("hsla(240, 100%, 50%, 0.5)"), "#0000ff");
+        assert_eq!(hex("hsl(0 0% 100%)"), "#ffffff");
+    }
+
+    #[test]
+    fn hue_units() {
+        assert_eq!(hex("hsl(0.5turn 100% 50%)"), "#00ffff");
+        assert_eq!(hex("hsl(3.14159265rad 100% 50%)"), "#00ffff");
+        assert_eq!(hex("hsl(200grad 100% 50%)"), "#00ffff");
+        assert_eq!(hex("hsl(-120 100% 50%)"), "#0000ff");
+        assert!(parse_css_colour("hsl(1foo 100% 50%)").is_err());
+    }
+
+    #[test]
+    fn unevaluated_colours() {
+        assert!(parse_css_colour("currentColor").unwrap().is_none());
+        assert!(parse_css_colour("var(--accent)").unwrap().is_none());
+        assert!(parse_css_colour("lch(50% 30 120)").unwrap().is_none());
+        assert!(parse_css_colour("color-mix(in srgb, var(--a) 50%, white)").unwrap().is_none());
+    }
+
+    #[test]
+    fn unevaluated_colours_cannot_inject_css() {
+        assert!(parse_css_colour("var(--x);} body{display:none").is_err());
+        assert!(parse_css_colour("lch(50% 30 120);} body{display:none;x:(1)").is_err());
+        assert!(parse_css_colour("var(--x))").is_err());
+        assert!(parse_css_colour("var((--x)").is_err());
+        assert!(parse_css_colour("lch(1 2 3)) (").is_err());
+        assert!(parse_css_colour("var(--x)\nbody").is_err());
+    }
+
+    #[test]
+    fn contrast_ratios() {
+        let black = Rgb(0.0, 0.0, 0.0);
+        let white = Rgb(1.0, 1.0, 1.0);
+        assert!((black.contrast(&white) - 21.0).abs() < 1e-9);
+        assert!((white.contrast(&black) - 21.0).abs() < 1e-9);
+        assert!((white.contrast(&white) - 1.0).abs() < 1e-9);
+        let grey = Rgb::from_hex(0x777777);
+        assert!((grey.contrast(&white) - 4.48).abs() < 0.01);
+    }
+}
@@ -1,9 +1,10 @@
 use std::cell::RefCell;
 use std::collections::HashSet;
+use std::path::Path;
 use std::rc::Rc;
 use khi::{Dictionary, List, TaggedTuple, Text, Value};
 use khi::parse::pdm::{ParsedDictionary, ParsedTaggedTuple, ParsedValue, Position};
-use crate::article::{Article, ArticleElement, Class, Articles, verify_parameter_match};
+use crate::article::{Article, ArticleElement, Backlinks, Class, Articles, verify_parameter_match};
 use crate::relation::{RelationClass};
 use crate::compile::template::{read_relation_list, read_relation_term_value, Templates};
 use crate::makro::{MacroMap};
@@ -11,8 +12,10 @@ use crate::key::KeyReader;
 use crate::tex::{write_tex_with, BreakMode};
 use crate::{tex_error_to_text, tuple_split};
 use crate::compile::name::read_names;
+use crate::compile::table::read_table;
+use crate::compile::figure::read_figure;
 use crate::markup::{Markup};
-use crate::preprocess_markup::process_unexpanded_markup;
+use crate::preprocess_markup::{process_inline_markup, process_unexpanded_markup};
 use crate::types::ArticleMeta;
 
 /// Read an article definition.
@@ -23,6 +26,7 @@ pub fn read_article<'a>(
     tag: &'a ParsedTaggedTuple,
     at: Position,
     document_key: &str,
+    source_dir: &Path,
 ) -> Result<Rc<RefCell<Article>>, String> {
     let template_key = tag.name().unwrap().to_string();
     let template = if let Some(template) = templates.get(&template_key) {
@@ -72,13 +76,14 @@ pub fn read_article<'a>(
             articles: vec![],
             relations: HashSet::new(),
             style,
+            backlinks: Backlinks::default(),
         };
         registry.class_map.insert(class_key.clone(), Rc::new(RefCell::new(class)));
     };
     let class = registry.get_class(&class_key).unwrap().clone();
     // Extract content if it is defined.
     let content = if let Some(content) = remove_first(&mut positionals) {
-        process_article_content(macros, content)?
+        process_article_content(macros, source_dir, content)?
     } else {
         vec![]
     };
@@ -193,6 +198,7 @@ pub fn read_article<'a>(
     if positionals.len() != 0 {
         return Err(format!("More arguments than expected in article at {}:{}.", at.line, at.column));
     }
+    class.borrow_mut().relations.extend(relations);
     // Register article. If it exists, create a separator. // TODO: Warn behind document flag/pragma
     if let Some(article) = registry.article_map.get(&article_key) {
         let mut iarticle = article.borrow_mut();
@@ -284,7 +290,7 @@ pub fn read_article_key_declaration(declaration: &str) -> Result<DeclaredKey, St
 }
 
 /// Read the body of an article.
-pub fn process_article_content(macros: &impl MacroMap, input: &ParsedValue) -> Result<Vec<ArticleElement>, String> {
+pub fn process_article_content(macros: &impl MacroMap, source_dir: &Path, input: &ParsedValue) -> Result<Vec<ArticleElement>, String> {
     let mut article_elements = vec![];
     if input.is_list() {
         let content = input.as_list().unwrap();
@@ -305,7 +311,7 @@ pub fn process_article_content(macros: &impl MacroMap, input: &ParsedValue) -> R
                     "H2" => 2, "H3" => 3, "H4" => 4, "H5" => 5, "H6" => 6,
                     _ => unreachable!(),
                 };
-                let tex = process_unexpanded_markup(macros, tuple.get(0).unwrap())?;
+                let tex = process_inline_markup(macros, tuple.get(0).unwrap(), "heading")?;
                 article_elements.push(ArticleElement::Heading { level, markup: tex });
             } else if name == "P" {
                 let tex = process_unexpanded_markup(macros, tuple.get(0).unwrap())?;
@@ -339,6 +345,10 @@ pub fn process_article_content(macros: &impl MacroMap, input: &ParsedValue) -> R
             //     }
             //     html.push_str("</ul>");
             //     article_elements.push(ArticleElement::Html(html));
+            } else if name == "T" {
+                article_elements.push(ArticleElement::Table(read_table(macros, tag)?));
+            } else if name == "img" || name == "Figure" {
+                article_elements.push(ArticleElement::Figure(read_figure(macros, source_dir, tag, c.from())?));
             } else if name == "L" {
                 let numbered = if tag.has_attributes() {
                     true
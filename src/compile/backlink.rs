@@ -0,0 +1,93 @@
+//! Build the reverse index from classes to the documents and articles that
+//! refer to them.
+
+use std::cell::RefCell;
+use std::rc::Rc;
+use crate::article::{Articles, Class};
+use crate::compile::reference::{article_markups, document_markups};
+use crate::document::{Documents, PanelElement, DocumentElement};
+use crate::markup::{find_references, Markup};
+use crate::name::NameElement;
+
+/// Record on each class which documents and articles refer to it.
+///
+/// Documents refer to a class when a panel links to the class or one of its
+/// articles, or when an inline reference points to it. Articles refer to a
+/// class when a name uses it as a parameter or an inline reference points to
+/// it. An article referring to its own class is not recorded.
+pub fn link_backlinks(articles: &Articles, documents: &Documents) {
+    for document in documents.iter() {
+        let mut classes = vec![];
+        for element in document.structure.iter() {
+            if let DocumentElement::Panel(elements) = element {
+                for element in elements {
+                    match element {
+                        PanelElement::ArticleLink { key, .. } => {
+                            if let Some(article) = articles.get_article(key) {
+                                classes.push(article.borrow().get_class());
+                            }
+                        }
+                        PanelElement::ClassLink { key, .. } => {
+                            if let Some(class) = articles.get_class(key) {
+                                classes.push(class.clone());
+                            }
+                        }
+                        PanelElement::Heading { .. } => {}
+                    }
+                }
+            }
+        }
+        for markup in document_markups(document) {
+            collect_referenced_classes(&mut classes, articles, markup);
+        }
+        for class in classes {
+            let mut class = class.borrow_mut();
+            let backlinks = &mut class.backlinks.documents;
+            if !backlinks.iter().any(|d| std::ptr::eq(d.as_ptr(), Rc::as_ptr(document))) {
+                backlinks.push(Rc::downgrade(document));
+            }
+        }
+    }
+    for (article_key, article) in articles.get_articles().iter() {
+        let article = article.borrow();
+        let own_class = article.get_class();
+        let mut classes = vec![];
+        for name in article.names.iter() {
+            for element in name.iter() {
+                if let NameElement::Parameter { class, .. } = element {
+                    if let Some(class) = articles.get_class(class) {
+                        classes.push(class.clone());
+                    }
+                }
+            }
+        }
+        for markup in article_markups(&article) {
+            collect_referenced_classes(&mut classes, articles, markup);
+        }
+        for class in classes {
+            if Rc::ptr_eq(&class, &own_class) {
+                continue;
+            }
+            let mut class = class.borrow_mut();
+            if !class.backlinks.articles.contains(article_key) {
+                class.backlinks.articles.push(article_key.clone());
+            }
+        }
+    }
+    for class in articles.get_classes().values() {
+        class.borrow_mut().backlinks.articles.sort();
+    }
+}
+
+fn collect_referenced_classes(classes: &mut Vec<Rc<RefCell<Class>>>, articles: &Articles, markup: &Markup) {
+    for reference in find_references(&markup.0) {
+        let key = reference.key.as_str();
+        if key.contains('@') {
+            if let Some(article) = articles.get_article(key) {
+                classes.push(article.borrow().get_class());
+            }
+        } else if let Some(class) = articles.get_class(key) {
+            classes.push(class.clone());
+        }
+    }
+}
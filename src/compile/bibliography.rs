@@ -0,0 +1,287 @@
+//! Read BibTeX files.
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+use crate::bibliography::{BibEntry, Bibliography, CitationStyle};
+use crate::file::read_file_to_string;
+
+/// Read the entries of all BibTeX files into a bibliography.
+pub fn read_bibliography_files(paths: &[PathBuf], style: CitationStyle) -> Result<Bibliography, String> {
+    let mut entries: Vec<BibEntry> = vec![];
+    for path in paths {
+        let content = read_file_to_string(path, "bibliography")?;
+        let file_entries = match parse_bibtex(&content) {
+            Ok(entries) => entries,
+            Err(e) => return Err(format!("Error reading bibliography file {}: {}", path.to_str().unwrap(), e)),
+        };
+        for entry in file_entries {
+            if entries.iter().any(|e| e.key == entry.key) {
+                return Err(format!("Bibliography entry {} in {} is defined more than once.", entry.key, path.to_str().unwrap()));
+            }
+            entries.push(entry);
+        }
+    }
+    Ok(Bibliography::new(entries, style))
+}
+
+/// Parse the entries of a BibTeX file.
+///
+/// `@comment`, `@preamble` and `@string` blocks are skipped. String macros are
+/// not expanded.
+fn parse_bibtex(content: &str) -> Result<Vec<BibEntry>, String> {
+    let mut entries = vec![];
+    let mut parser = BibParser { chars: content.char_indices().peekable(), content, line: 1 };
+    while parser.skip_to('@') {
+        let kind = parser.read_identifier().to_lowercase();
+        parser.skip_whitespace();
+        let close = match parser.next() {
+            Some('{') => '}',
+            Some('(') => ')',
+            _ => return Err(format!("Expected {{ after @{} at line {}.", kind, parser.line)),
+        };
+        if kind == "comment" || kind == "preamble" || kind == "string" {
+            parser.skip_group(close)?;
+            continue;
+        }
+        parser.skip_whitespace();
+        let key = parser.read_until(&[',', close]).trim().to_string();
+        if key.is_empty() {
+            return Err(format!("Entry @{} at line {} has no key.", kind, parser.line));
+        }
+        let mut fields = HashMap::new();
+        loop {
+            parser.skip_whitespace();
+            match parser.next() {
+                Some(',') => {}
+                Some(c) if c == close => break,
+                _ => return Err(format!("Expected , or {} in entry {} at line {}.", close, key, parser.line)),
+            }
+            parser.skip_whitespace();
+            if parser.peek() == Some(close) {
+                parser.next();
+                break;
+            }
+            let name = parser.read_identifier().to_lowercase();
+            if name.is_empty() {
+                return Err(format!("Expected field name in entry {} at line {}.", key, parser.line));
+            }
+            parser.skip_whitespace();
+            if parser.next() != Some('=') {
+                return Err(format!("Expected = after field {} in entry {} at line {}.", name, key, parser.line));
+            }
+            let value = parser.read_value(close)?;
+            fields.insert(name, clean_value(&value));
+        }
+        entries.push(BibEntry { key, kind, fields });
+    }
+    Ok(entries)
+}
+
+struct BibParser<'a> {
+    chars: std::iter::Peekable<std::str::CharIndices<'a>>,
+    content: &'a str,
+    line: usize,
+}
+
+impl<'a> BibParser<'a> {
+
+    fn next(&mut self) -> Option<char> {
+        let (_, c) = self.chars.next()?;
+        if c == '\n' {
+            self.line += 1;
+        }
+        Some(c)
+    }
+
+    fn peek(&mut self) -> Option<char> {
+        self.chars.peek().map(|(_, c)| *c)
+    }
+
+    fn position(&mut self) -> usize {
+        self.chars.peek().map(|(i, _)| *i).unwrap_or(self.content.len())
+    }
+
+    /// Skip past the next occurrence of a character. Returns false at the end of input.
+    fn skip_to(&mut self, target: char) -> bool {
+        while let Some(c) = self.next() {
+            if c == target {
+                return true;
+            }
+        }
+        false
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.peek().is_some_and(|c| c.is_whitespace()) {
+            self.next();
+        }
+    }
+
+    fn read_identifier(&mut self) -> &'a str {
+        let start = self.position();
+        while self.peek().is_some_and(|c| c.is_alphanumeric() || "_-:.".contains(c)) {
+            self.next();
+        }
+        &self.content[start..self.position()]
+    }
+
+    fn read_until(&mut self, ends: &[char]) -> &'a str {
+        let start = self.position();
+        while self.peek().is_some_and(|c| !ends.contains(&c)) {
+            self.next();
+        }
+        &self.content[start..self.position()]
+    }
+
+    /// Skip the rest of a block, including nested braces.
+    fn skip_group(&mut self, close: char) -> Result<(), String> {
+        let mut depth = 0;
+        while let Some(c) = self.next() {
+            if c == '{' {
+                depth += 1;
+            } else if c == '}' && depth > 0 {
+                depth -= 1;
+            } else if c == close && depth == 0 {
+                return Ok(());
+            }
+        }
+        Err(format!("Unclosed block at end of file."))
+    }
+
+    /// Read a field value: braced, quoted, a number or a string macro name,
+    /// possibly concatenated with #.
+    fn read_value(&mut self, close: char) -> Result<String, String> {
+        let mut value = String::new();
+        loop {
+            self.skip_whitespace();
+            match self.peek() {
+                Some('{') => {
+                    self.next();
+                    let start = self.position();
+                    let mut depth = 0;
+                    loop {
+                        match self.next() {
+                            Some('{') => depth += 1,
+                            Some('}') if depth == 0 => break,
+                            Some('}') => depth -= 1,
+                            Some(_) => {}
+                            None => return Err(format!("Unclosed {{ in field value.")),
+                        }
+                    }
+                    let end = self.position() - 1;
+                    value.push_str(&self.content[start..end]);
+                }
+                Some('"') => {
+                    self.next();
+                    let start = self.position();
+                    let mut depth = 0;
+                    loop {
+                        match self.next() {
+                            Some('{') => depth += 1,
+                            Some('}') => depth -= 1,
+                            Some('"') if depth == 0 => break,
+                            Some(_) => {}
+                            None => return Err(format!("Unclosed \" in field value.")),
+                        }
+                    }
+                    let end = self.position() - 1;
+                    value.push_str(&self.content[start..end]);
+                }
+                _ => {
+                    let word = self.read_until(&[',', '#', close]).trim();
+                    value.push_str(word);
+                }
+            }
+            self.skip_whitespace();
+            if self.peek() == Some('#') {
+                self.next();
+            } else {
+                return Ok(value);
+            }
+        }
+    }
+
+}
+
+/// Remove braces, collapse whitespace and replace common TeX escapes.
+fn clean_value(value: &str) -> String {
+    let value = value
+        .replace("\\&", "&")
+        .replace("\\%", "%")
+        .replace("\\_", "_")
+        .replace("---", "\u{2014}")
+        .replace("--", "\u{2013}")
+        .replace('~', " ");
+    let value: String = value.chars().filter(|c| *c != '{' && *c != '}').collect();
+    value.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn field<'a>(entry: &'a BibEntry, name: &str) -> &'a str {
+        entry.fields.get(name).map(|value| value.as_str()).unwrap_or("")
+    }
+
+    #[test]
+    fn braced_quoted_and_bare_values() {
+        let entries = parse_bibtex(r#"
+            Text outside entries is ignored.
+            @Book{knuth84,
+              AUTHOR = {Donald E. Knuth},
+              title = "The {\TeX}book",
+              year = 1984,
+              publisher = {Addison-Wesley},
+            }
+        "#).unwrap();
+        assert_eq!(entries.len(), 1);
+        let entry = &entries[0];
+        assert_eq!(entry.key, "knuth84");
+        assert_eq!(entry.kind, "book");
+        assert_eq!(field(entry, "author"), "Donald E. Knuth");
+        assert_eq!(field(entry, "title"), r"The \TeXbook");
+        assert_eq!(field(entry, "year"), "1984");
+        assert_eq!(field(entry, "publisher"), "Addison-Wesley");
+    }
+
+    #[test]
+    fn nested_braces_concatenation_and_escapes() {
+        let entries = parse_bibtex(r#"@article{a, title = {On {Nested {Braces}}} # " and " # {Q\&A},
+            pages = {1--10}, note = {A---B~C \%\_}}"#).unwrap();
+        let entry = &entries[0];
+        assert_eq!(field(entry, "title"), "On Nested Braces and Q&A");
+        assert_eq!(field(entry, "pages"), "1\u{2013}10");
+        assert_eq!(field(entry, "note"), "A\u{2014}B C %_");
+    }
+
+    #[test]
+    fn parentheses_and_multiline_values() {
+        let entries = parse_bibtex("@misc(b, title = {A title\n   over    lines})").unwrap();
+        assert_eq!(entries[0].key, "b");
+        assert_eq!(field(&entries[0], "title"), "A title over lines");
+    }
+
+    #[test]
+    fn skipped_blocks() {
+        let entries = parse_bibtex(r#"
+            @comment{ an {unbalanced} comment }
+            @string{ pub = "Publisher" }
+            @preamble{ "\newcommand{\x}{y}" }
+            @misc{c, title = {C}}
+        "#).unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].key, "c");
+    }
+
+    #[test]
+    fn errors() {
+        assert!(parse_bibtex("@book{, title = {T}}").is_err());
+        assert!(parse_bibtex("@book{k, title = {T}").is_err());
+        assert!(parse_bibtex("@book{k, title {T}}").is_err());
+        assert!(parse_bibtex("@book{k, title = {Unclosed}").is_err());
+        assert!(parse_bibtex("@book k").is_err());
+        assert!(parse_bibtex("@comment{ unclosed").is_err());
+    }
+
+}
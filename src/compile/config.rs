@@ -1,13 +1,15 @@
 use std::fs::{read_dir};
 use std::path::{Path, PathBuf};
+use crate::compile::locale::read_locale_file;
 use crate::compile::makro::read_macro_definition_file;
 use crate::compile::template::{read_template_file, Templates};
+use crate::locale::Locales;
 use crate::makro::{Macros};
 
-pub fn read_configuration_files(templates: &mut Templates, macros: &mut Macros, paths: &[PathBuf]) -> Result<(), String> {
+pub fn read_configuration_files(templates: &mut Templates, macros: &mut Macros, locales: &mut Locales, paths: &[PathBuf]) -> Result<(), String> {
     for path in paths {
         if path.is_dir() {
-            read_config_dir(macros, templates, path)?;
+            read_config_dir(macros, templates, locales, path)?;
         } else {
             let file_name = path.file_name().unwrap();
             if file_name.as_encoded_bytes().ends_with(b".macros.khi") {
@@ -18,8 +20,12 @@ pub fn read_configuration_files(templates: &mut Templates, macros: &mut Macros,
                 let file_path = path.join(&file_name);
                 eprintln!("Reading template file {}", file_path.display());
                 read_template_file(templates, &file_path)?;
+            } else if file_name.as_encoded_bytes().ends_with(b".locale.khi") {
+                let file_path = path.join(&file_name);
+                eprintln!("Reading locale file {}", file_path.display());
+                read_locale_file(locales, &file_path)?;
             } else {
-                return Err(format!("Configuration file {} must be either a .macros.khi or a .templates.khi file.", file_name.to_str().unwrap()));
+                return Err(format!("Configuration file {} must be a .macros.khi, .templates.khi or .locale.khi file.", file_name.to_str().unwrap()));
             }
         }
     }
@@ -27,7 +33,7 @@ pub fn read_configuration_files(templates: &mut Templates, macros: &mut Macros,
 }
 
 /// Read a configuration directory.
-pub fn read_config_dir(macros: &mut Macros, templates: &mut Templates, path: &Path) -> Result<(), String> {
+pub fn read_config_dir(macros: &mut Macros, templates: &mut Templates, locales: &mut Locales, path: &Path) -> Result<(), String> {
     for dir_entry in read_dir(&path).unwrap() {
         let dir_entry = dir_entry.unwrap();
         let file_name = dir_entry.file_name();
@@ -41,10 +47,14 @@ pub fn read_config_dir(macros: &mut Macros, templates: &mut Templates, path: &Pa
                 let file_path = path.join(&file_name);
                 read_template_file(templates, &file_path)?;
                 eprintln!("Read template file {}", file_path.display());
+            } else if file_name.as_encoded_bytes().ends_with(b".locale.khi") {
+                let file_path = path.join(&file_name);
+                read_locale_file(locales, &file_path)?;
+                eprintln!("Read locale file {}", file_path.display());
             }
         } else if entry_type.is_dir() {
             let dir_path = path.join(&file_name);
-            read_config_dir(macros, templates, &dir_path)?;
+            read_config_dir(macros, templates, locales, &dir_path)?;
         }
     }
     Ok(())
@@ -14,14 +14,17 @@ use crate::article::{Articles};
 use crate::compile::article::{read_article};
 use crate::compile::makro::{read_macro_definitions_list};
 use crate::compile::project::{DependencyInclude, ResolutionPaths};
+use crate::compile::table::read_table;
+use crate::compile::figure::read_figure;
 use crate::compile::template::{Templates};
 use crate::dir::Dir;
 use crate::document::{Document, DocumentElement, PanelElement};
 use crate::file::{read_excludable_file_to_string, read_file_content_to_dictionary};
 use crate::makro::{LocalMacroRegistry, MacroMap, Macros};
-use crate::key::KeyReader;
+use crate::bibliography::Bibliography;
+use crate::key::{read_include_key, LinkKey};
 use crate::markup::{Markup};
-use crate::preprocess_markup::process_unexpanded_markup;
+use crate::preprocess_markup::{process_inline_markup, process_unexpanded_markup};
 use crate::tuple_split;
 
 /// Directory configuration. Stored in dir.khi files. Only contains Directory name at the moment.
@@ -57,13 +60,13 @@ pub(crate) fn read_dir_file(dir_file_path: &Path) -> Result<DirConfig, String> {
 }
 
 /// Read a source dir. Recursively reads all nested directories and document files.
-pub fn read_source_dir(templates: &Templates, resolution_paths: &ResolutionPaths, macros: &Macros, data: &mut Articles, documents: &mut Vec<Rc<Document>>, path: &Path, file_name: OsString) -> Result<Rc<Dir>, String> {
-    read_document_dir(templates, resolution_paths, macros, data, documents, path, file_name, None)
+pub fn read_source_dir(templates: &Templates, resolution_paths: &ResolutionPaths, macros: &Macros, bibliography: &Bibliography, data: &mut Articles, documents: &mut Vec<Rc<Document>>, path: &Path, file_name: OsString) -> Result<Rc<Dir>, String> {
+    read_document_dir(templates, resolution_paths, macros, bibliography, data, documents, path, file_name, None)
 }
 
 /// Read a document dir.
 fn read_document_dir(
-    templates: &Templates, resolution_paths: &ResolutionPaths, macros: &Macros,
+    templates: &Templates, resolution_paths: &ResolutionPaths, macros: &Macros, bibliography: &Bibliography,
     data: &mut Articles, documents: &mut Vec<Rc<Document>>, path: &Path,
     file_name: OsString, parent: Option<Weak<Dir>>,
 ) -> Result<Rc<Dir>, String> {
@@ -101,14 +104,14 @@ fn read_document_dir(
             if file_name.as_encoded_bytes().ends_with(b".document.khi") || file_name.as_encoded_bytes().ends_with(b".doc.khi") {
                 let document_path = path.join(&file_name);
                 eprintln!("Reading document file {}", document_path.to_str().unwrap());
-                let subdoc = read_document_file(templates, documents, data, macros, DependencyInclude::All, file_name, &document_path, w.clone())?;
+                let subdoc = read_document_file(templates, documents, data, macros, bibliography, DependencyInclude::All, file_name, &document_path, w.clone())?;
                 if let Some(subdoc) = subdoc { // If the file is not excluded.
                     subdocs.push(subdoc);
                 }
             }
         } else if entry_type.is_dir() {
             let dir_path = path.join(&file_name);
-            let subdir = read_document_dir(templates, resolution_paths, macros, data, documents, &dir_path, file_name, Some(w.clone()))?;
+            let subdir = read_document_dir(templates, resolution_paths, macros, bibliography, data, documents, &dir_path, file_name, Some(w.clone()))?;
             subdirs.push(subdir);
         }
     }
@@ -129,17 +132,17 @@ fn read_document_dir(
 
 pub struct DocumentKey(String);
 
-pub fn read_document_file(templates: &Templates, documents: &mut Vec<Rc<Document>>, registry: &mut Articles, macros: &Macros, include: DependencyInclude, file_name: OsString, path: &Path, parent_dir: Weak<Dir>) -> Result<Option<Rc<Document>>, String> {
+pub fn read_document_file(templates: &Templates, documents: &mut Vec<Rc<Document>>, registry: &mut Articles, macros: &Macros, bibliography: &Bibliography, include: DependencyInclude, file_name: OsString, path: &Path, parent_dir: Weak<Dir>) -> Result<Option<Rc<Document>>, String> {
     let content = match read_excludable_file_to_string(path, "document")? {
         None => return Ok(None),
         Some(c) => c,
     };
     let dict = read_file_content_to_dictionary(path, "document", &content)?;
-    let document = read_document_khidict(templates, documents, registry, macros, include, file_name, &dict, parent_dir)?;
+    let document = read_document_khidict(templates, documents, registry, macros, bibliography, include, file_name, path.parent().unwrap(), &dict, parent_dir)?;
     Ok(Some(document))
 }
 
-pub fn read_document_khidict(templates: &Templates, documents: &mut Vec<Rc<Document>>, registry: &mut Articles, macros: &Macros, include: DependencyInclude, file_name: OsString, document: &ParsedDictionary, parent_dir: Weak<Dir>) -> Result<Rc<Document>, String> {
+pub fn read_document_khidict(templates: &Templates, documents: &mut Vec<Rc<Document>>, registry: &mut Articles, macros: &Macros, bibliography: &Bibliography, include: DependencyInclude, file_name: OsString, source_dir: &Path, document: &ParsedDictionary, parent_dir: Weak<Dir>) -> Result<Rc<Document>, String> {
     let key = if let Some(key) = document.get("Key") {
         if !key.is_text() {
             return Err(format!("Key in document must be text."));
@@ -164,7 +167,36 @@ pub fn read_document_khidict(templates: &Templates, documents: &mut Vec<Rc<Docum
     } else {
         None
     };
-    // TODO: Include Author?
+    let authors = if let Some(authors) = document.get("Authors") {
+        if authors.is_text() {
+            vec![authors.as_text().unwrap().as_str().to_string()]
+        } else if authors.is_list() {
+            let mut names = vec![];
+            for author in authors.as_list().unwrap().iter() {
+                if !author.is_text() {
+                    return Err(format!("Authors in document must be text."));
+                }
+                names.push(author.as_text().unwrap().as_str().to_string());
+            }
+            names
+        } else {
+            return Err(format!("Authors in document must be text or a list of text."));
+        }
+    } else {
+        vec![]
+    };
+    let published = if let Some(published) = document.get("Published") {
+        let published = match published.as_text() {
+            Some(published) => published.as_str().to_string(),
+            None => return Err(format!("Published in document must be text.")),
+        };
+        if !is_date(&published) {
+            return Err(format!("Published in document must be a date as in 2024-07-31, not {}.", published));
+        }
+        Some(published)
+    } else {
+        None
+    };
     // Read local macro definitions.
     let mut document_macros = Macros::new();
     if let Some(macros) = document.get("Macros") {
@@ -174,7 +206,7 @@ pub fn read_document_khidict(templates: &Templates, documents: &mut Vec<Rc<Docum
         let list = macros.as_list().unwrap();
         read_macro_definitions_list(&mut document_macros, list)?
     };
-    let local_macros = LocalMacroRegistry::new(macros, &document_macros);
+    let local_macros = LocalMacroRegistry::new(macros, &document_macros, key.as_str(), bibliography);
     // Read resolution paths.
     let resolution_paths = if let Some(resolution_paths) = document.get("Resolve") {
         if !resolution_paths.is_list() {
@@ -209,7 +241,7 @@ pub fn read_document_khidict(templates: &Templates, documents: &mut Vec<Rc<Docum
             return Err(format!("The Content section must be a list."));
         }
         let parsed_content = parsed_content.as_list().unwrap();
-        read_content_section(templates, registry, &aliases, &local_macros, parsed_content, key.as_str())?
+        read_content_section(templates, registry, &aliases, &local_macros, parsed_content, key.as_str(), source_dir)?
     } else {
         vec![]
     };
@@ -228,7 +260,7 @@ pub fn read_document_khidict(templates: &Templates, documents: &mut Vec<Rc<Docum
         }
     }
     // Register document.
-    let document = Document { key, title, description, resolution_paths, file_name, parent_dir, structure };
+    let document = Document { key, title, description, authors, published, resolution_paths, file_name, parent_dir, structure };
     let document = Rc::new(document);
     documents.push(document.clone());
     Ok(document)
@@ -241,7 +273,8 @@ fn read_content_section(
     aliases: &HashMap<String, String>,
     macro_map: &LocalMacroRegistry,
     content_list: &ParsedList,
-    document_key: &str
+    document_key: &str,
+    source_dir: &Path,
 ) -> Result<Vec<DocumentElement>, String> {
     let mut elements = vec![];
     let mut heading_level = 1; // Keep track of heading level to prevent bad sectioning structure.
@@ -270,6 +303,10 @@ fn read_content_section(
             }
             let argument = tuple.get(0).unwrap();
             elements.push(DocumentElement::Paragraph(process_unexpanded_markup(macro_map, argument)?));
+        } else if name == "T" {
+            elements.push(DocumentElement::Table(read_table(macro_map, tag)?));
+        } else if name == "img" || name == "Figure" {
+            elements.push(DocumentElement::Figure(read_figure(macro_map, source_dir, tag, at)?));
         } else if name == "@" {
             let include = read_include_element(aliases, tag, at, document_key)?;
             append_paneled_element(&mut elements, include)?;
@@ -280,7 +317,7 @@ fn read_content_section(
 //            let argument = tuple.get(0).unwrap();
 //            read_inline_grouping(&mut read_elements, &mut read_articles, argument, at, document_key)?;
         } else {
-            let article_link = read_article_element(templates, articles, aliases, macro_map, tag, at, document_key)?;
+            let article_link = read_article_element(templates, articles, aliases, macro_map, tag, at, document_key, source_dir)?;
             append_paneled_element(&mut elements, article_link)?;
         }
     }
@@ -372,10 +409,10 @@ fn read_heading_element(macros: &impl MacroMap, tag: &ParsedTaggedTuple) -> Resu
         let index = positional.get(0).unwrap();
         if !index.is_text() { return Err(format!("Heading at {}:{} must be tuple with 1 or 2 elements.", "?", "?")) } // TODO .at()
         let index = index.as_text().unwrap().as_str().to_string();
-        let heading = Markup::from_markup(macros, positional.get(1).unwrap())?;
+        let heading = process_inline_markup(macros, positional.get(1).unwrap(), "heading")?;
         (Some(index), heading)
     } else if tag.len() == 1 {
-        let heading = Markup::from_markup(macros, positional.get(0).unwrap())?;
+        let heading = process_inline_markup(macros, positional.get(0).unwrap(), "heading")?;
         (None, heading)
     } else {
         return Err(format!("Heading at {}:{} must be tuple with 1 or 2 elements.", "?", "?")); // TODO .at()
@@ -393,8 +430,8 @@ fn read_heading_element(macros: &impl MacroMap, tag: &ParsedTaggedTuple) -> Resu
     Ok(heading)
 }
 
-fn read_article_element(templates: &Templates, registry: &mut Articles, aliases: &HashMap<String, String>, macros: &impl MacroMap, tag: &ParsedTaggedTuple, at: Position, document_key: &str) -> Result<PanelElement, String> {
-    let read_article = read_article(templates, macros, registry, tag, at, document_key)?;
+fn read_article_element(templates: &Templates, registry: &mut Articles, aliases: &HashMap<String, String>, macros: &impl MacroMap, tag: &ParsedTaggedTuple, at: Position, document_key: &str, source_dir: &Path) -> Result<PanelElement, String> {
+    let read_article = read_article(templates, macros, registry, tag, at, document_key, source_dir)?;
     let key = read_article.borrow().key.clone();
     let element = PanelElement::ArticleLink { key, index: None };
     Ok(element)
@@ -419,35 +456,6 @@ fn read_include_element(aliases: &HashMap<String, String>, tag: &ParsedTaggedTup
     }
 }
 
-enum LinkKey {
-    Class(String), Article(String)
-}
-
-/// Read the key in an include element. This key can be a class key or an
-/// article key.
-fn read_include_key(document_key: &str, key: &str) -> Result<LinkKey, String> {
-    let mut reader = KeyReader::new(key);
-    if reader.is_plain_key() {
-        let (key, article) = reader.parse_plain()?;
-        if !reader.is_at_end() {
-            return Err(format!("Expected end in key."));
-        }
-        if article {
-            Ok(LinkKey::Article(key))
-        } else {
-            Ok(LinkKey::Class(key))
-        }
-    } else if reader.is_parenthesized() {
-        let key = reader.parse_parenthesized()?;
-        if !reader.is_at_end() {
-            return Err(format!("Expected end in key."));
-        }
-        Ok(LinkKey::Article(format!("{}@{}", &key, document_key)))
-    } else {
-        return Err(format!("Invalid declaration key."));
-    }
-}
-
 fn read_resolution_paths(parsed_paths: &ParsedList) -> Result<Vec<String>, String> {
     let mut paths = vec![];
     for parsed_path in parsed_paths.iter() {
@@ -467,3 +475,10 @@ fn resolve_alias<'a>(aliases: &'a HashMap<String, String>, alias: &'a str) -> &'
         alias
     }
 }
+
+/// Whether text is a date as in 2024-07-31.
+fn is_date(text: &str) -> bool {
+    let parts: Vec<&str> = text.split('-').collect();
+    parts.len() == 3
+        && [4, 2, 2].iter().zip(parts.iter()).all(|(length, part)| part.len() == *length && part.chars().all(|c| c.is_ascii_digit()))
+}
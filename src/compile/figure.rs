@@ -0,0 +1,67 @@
+//! Read images and figures in article and document content.
+
+use std::fs;
+use std::path::Path;
+use khi::{TaggedTuple, Text, Value};
+use khi::parse::pdm::{ParsedTaggedTuple, Position};
+use crate::figure::Figure;
+use crate::makro::MacroMap;
+use crate::preprocess_markup::process_inline_markup;
+use crate::tuple_split;
+
+/// Read an image command `<img>:path:alt` or a figure command
+/// `<Figure>:path:alt:caption`.
+///
+/// The path is relative to the directory of the document.
+pub fn read_figure(macros: &impl MacroMap, source_dir: &Path, tag: &ParsedTaggedTuple, at: Position) -> Result<Figure, String> {
+    let command = tag.name().unwrap();
+    let (arguments, _) = tuple_split(tag);
+    let with_caption = command == "Figure";
+    if with_caption && arguments.len() != 3 {
+        return Err(format!("<Figure> at {}:{} takes a path, an alt text and a caption.", at.line, at.column));
+    } else if !with_caption && arguments.len() != 2 {
+        if arguments.len() == 1 {
+            return Err(format!("Image at {}:{} is missing alt text.", at.line, at.column));
+        }
+        return Err(format!("<img> at {}:{} takes a path and an alt text.", at.line, at.column));
+    }
+    let path = arguments.get(0).unwrap();
+    if !path.is_text() {
+        return Err(format!("Image path at {}:{} must be text.", path.from().line, path.from().column));
+    }
+    let source = source_dir.join(path.as_text().unwrap().as_str());
+    let alt = arguments.get(1).unwrap();
+    if !alt.is_text() {
+        return Err(format!("Image alt text at {}:{} must be text.", alt.from().line, alt.from().column));
+    }
+    let alt = alt.as_text().unwrap().as_str().trim().to_string();
+    if alt.is_empty() {
+        return Err(format!("Image at {}:{} is missing alt text.", at.line, at.column));
+    }
+    let caption = if with_caption {
+        Some(process_inline_markup(macros, arguments.get(2).unwrap(), "figure caption")?)
+    } else {
+        None
+    };
+    let content = match fs::read(&source) {
+        Ok(content) => content,
+        Err(_) => return Err(format!("Image file {} at {}:{} does not exist.", source.to_str().unwrap(), at.line, at.column)),
+    };
+    let file_name = hashed_file_name(&source, &content);
+    Ok(Figure { source, file_name, alt, caption })
+}
+
+/// Name a file after a hash of its content, keeping the extension.
+fn hashed_file_name(path: &Path, content: &[u8]) -> String {
+    // 64-bit FNV-1a, which is stable between builds.
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for byte in content {
+        hash ^= *byte as u64;
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+    if let Some(extension) = path.extension() {
+        format!("{:016x}.{}", hash, extension.to_str().unwrap().to_ascii_lowercase())
+    } else {
+        format!("{:016x}", hash)
+    }
+}
@@ -0,0 +1,47 @@
+//! Read locale files.
+
+use std::collections::BTreeMap;
+use std::path::Path;
+use khi::{Dictionary, Text, Value};
+use crate::file::{read_file_content_to_dictionary, read_file_to_string};
+use crate::locale::{Locale, Locales};
+
+/// Read the built-in English and Norwegian locales.
+pub fn read_default_locales(locales: &mut Locales) -> Result<(), String> {
+    read_locale_str(locales, Path::new("en.locale.khi"), include_str!("../../locales/en.locale.khi"))?;
+    read_locale_str(locales, Path::new("no.locale.khi"), include_str!("../../locales/no.locale.khi"))?;
+    Ok(())
+}
+
+/// Read a locale file.
+///
+/// A locale file is a dictionary with a `Language` entry and an entry for each
+/// string. It overrides the strings of an existing locale, or adds a new
+/// locale where strings that are not given are taken from English.
+pub fn read_locale_file(locales: &mut Locales, path: &Path) -> Result<(), String> {
+    let content = read_file_to_string(path, "locale")?;
+    read_locale_str(locales, path, &content)
+}
+
+fn read_locale_str(locales: &mut Locales, path: &Path, content: &str) -> Result<(), String> {
+    let dictionary = read_file_content_to_dictionary(path, "locale", content)?;
+    let language = match dictionary.get("Language") {
+        Some(language) if language.is_text() => language.as_text().unwrap().as_str().to_string(),
+        _ => return Err(format!("Locale file {} must have a Language entry.", path.to_str().unwrap())),
+    };
+    if !locales.contains_key(&language) {
+        let strings = match locales.get("en") {
+            Some(english) => english.strings.clone(),
+            None => BTreeMap::new(),
+        };
+        locales.insert(language.clone(), Locale { language: language.clone(), strings });
+    }
+    let locale = locales.get_mut(&language).unwrap();
+    for (key, value) in dictionary.iter() {
+        if !value.is_text() {
+            return Err(format!("Locale string {} in {} must be text.", key, path.to_str().unwrap()));
+        }
+        locale.strings.insert(String::from(key), value.as_text().unwrap().as_str().to_string());
+    }
+    Ok(())
+}
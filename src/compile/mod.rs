@@ -7,3 +7,8 @@ pub mod name;
 pub mod style;
 pub mod config;
 pub mod template;
+pub mod table;
+pub mod figure;
+pub mod reference;
+pub mod backlink;
+pub mod locale;
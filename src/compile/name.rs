@@ -5,7 +5,7 @@ use crate::article::{verify_parameter_match, Parameters};
 use crate::name::{Name, NameElement};
 use crate::makro::MacroMap;
 use crate::markup::{Markup};
-use crate::preprocess_markup::{preprocess_markup_level, process_unexpanded_markup};
+use crate::preprocess_markup::{preprocess_markup_level, process_inline_markup};
 use crate::tuple_split;
 
 pub fn read_names(macros: &impl MacroMap, name_value: &ParsedValue) -> Result<(Vec<Name>, Parameters), String> {
@@ -90,11 +90,11 @@ fn read_name_element(macros: &impl MacroMap, parametrization: &mut Vec<NameEleme
                 let (argument, named) = tuple_split(tagged);
                 if argument.len() == 1 {
                     let argument = argument.get(0).unwrap();
-                    let text = process_unexpanded_markup(macros, argument)?;
+                    let text = process_inline_markup(macros, argument, "name")?;
                     parametrization.push(NameElement::Name(text));
                 } else if argument.len() == 2 {
                     let argument = argument.get(1).unwrap();
-                    let text = process_unexpanded_markup(macros, argument)?;
+                    let text = process_inline_markup(macros, argument, "name")?;
                     let key = Rc::from(name);
                     parametrization.push(NameElement::Parameter { markup: text, class: key });
                     parameters.push(Rc::from(name));
@@ -102,16 +102,16 @@ fn read_name_element(macros: &impl MacroMap, parametrization: &mut Vec<NameEleme
                     return Err(format!("Parameter takes 1 or 2 arguments."));
                 }
             } else {
-                let text = process_unexpanded_markup(macros, element)?;
+                let text = process_inline_markup(macros, element, "name")?;
                 parametrization.push(NameElement::Preposition(text));
             }
         }
         ParsedValue::Text(..) => {
-            let text = process_unexpanded_markup(macros, element)?;
+            let text = process_inline_markup(macros, element, "name")?;
             parametrization.push(NameElement::Preposition(text));
         }
         ParsedValue::Catenation(..) | ParsedValue::Nil(..) => {
-            let text = process_unexpanded_markup(macros, element)?;
+            let text = process_inline_markup(macros, element, "name")?;
             parametrization.push(NameElement::Preposition(text));
         }
         ParsedValue::Dictionary(..) | ParsedValue::List(..) => {
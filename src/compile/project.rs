@@ -4,12 +4,38 @@ use std::path::{Path, PathBuf};
 use khi::{Dictionary, List, Text, Value};
 use khi::parse::pdm::{ParsedDictionary};
 use crate::file::{read_file_content_to_dictionary, read_file_to_string};
+use crate::bibliography::CitationStyle;
+use crate::makro::Macros;
+use crate::markup::Markup;
+use crate::preprocess_markup::process_inline_markup;
+use crate::web::Theme;
 
 pub struct ProjectSettings {
     pub(crate) resolution_paths: ResolutionPaths,
     pub(crate) style_path: Option<PathBuf>,
     pub(crate) config_paths: Vec<PathBuf>,
     pub(crate) dependencies: Vec<DependencySettings>,
+    /// Directory containing local copies of the front-end libraries.
+    pub(crate) vendor_path: Option<PathBuf>,
+    /// Show footnotes as margin notes instead of at the end of articles.
+    pub(crate) margin_notes: bool,
+    /// BibTeX files of the project.
+    pub(crate) bibliography_paths: Vec<PathBuf>,
+    pub(crate) citation_style: CitationStyle,
+    /// Title of the website.
+    pub(crate) title: Option<String>,
+    /// Description of the website.
+    pub(crate) description: Option<String>,
+    /// Introduction shown on the front page.
+    pub(crate) front_page: Option<Markup>,
+    /// Absolute URL of the website root without a trailing slash.
+    pub(crate) base_url: Option<String>,
+    /// Language code of the locale of the website user interface.
+    pub(crate) language: String,
+    /// Default colour theme of the website.
+    pub(crate) theme: Theme,
+    /// Project stylesheets added to every page.
+    pub(crate) stylesheet_paths: Vec<PathBuf>,
 }
 
 pub struct DependencySettings {
@@ -60,7 +86,21 @@ pub fn read_project_file_contents(project: &ParsedDictionary) -> Result<ProjectS
     let style_path = read_style_path(project)?;
     let config_paths = read_configuration_paths(project)?;
     let dependencies = read_dependency_settings(project)?;
-    Ok(ProjectSettings { resolution_paths, style_path, config_paths, dependencies })
+    let vendor_path = read_vendor_path(project)?;
+    let margin_notes = read_margin_notes(project)?;
+    let bibliography_paths = read_bibliography_paths(project)?;
+    let citation_style = read_citation_style(project)?;
+    let title = read_text_entry(project, "Title")?;
+    let description = read_text_entry(project, "Description")?;
+    let front_page = read_front_page(project)?;
+    let base_url = read_base_url(project)?;
+    let language = read_text_entry(project, "Language")?.unwrap_or_else(|| String::from("en"));
+    let theme = read_theme(project)?;
+    let stylesheet_paths = read_stylesheet_paths(project)?;
+    Ok(ProjectSettings {
+        resolution_paths, style_path, config_paths, dependencies, vendor_path, margin_notes, bibliography_paths,
+        citation_style, title, description, front_page, base_url, language, theme, stylesheet_paths,
+    })
 }
 
 fn read_resolution_paths(project: &ParsedDictionary) -> Result<ResolutionPaths, String> {
@@ -95,6 +135,135 @@ fn read_style_path(project: &ParsedDictionary) -> Result<Option<PathBuf>, String
     }
 }
 
+fn read_vendor_path(project: &ParsedDictionary) -> Result<Option<PathBuf>, String> {
+    if let Some(vendor_path) = project.get("VendorDir") {
+        if !vendor_path.is_text() {
+            return Err(format!(r#"The VendorDir entry must be a file system path."#));
+        }
+        let vendor_path = PathBuf::from(vendor_path.as_text().unwrap().as_str());
+        Ok(Some(vendor_path))
+    } else {
+        Ok(None)
+    }
+}
+
+fn read_text_entry(project: &ParsedDictionary, key: &str) -> Result<Option<String>, String> {
+    if let Some(text) = project.get(key) {
+        if !text.is_text() {
+            return Err(format!(r#"The {} entry must be text."#, key));
+        }
+        Ok(Some(text.as_text().unwrap().as_str().to_string()))
+    } else {
+        Ok(None)
+    }
+}
+
+fn read_base_url(project: &ParsedDictionary) -> Result<Option<String>, String> {
+    if let Some(base_url) = read_text_entry(project, "BaseUrl")? {
+        if !base_url.starts_with("https://") && !base_url.starts_with("http://") {
+            return Err(format!(r#"The BaseUrl entry must be an absolute http or https URL, as in https://host/path/."#));
+        }
+        Ok(Some(base_url.trim_end_matches('/').to_string()))
+    } else {
+        Ok(None)
+    }
+}
+
+/// Read the front page introduction. Project macros are not read yet, so only
+/// built-in commands are available.
+fn read_front_page(project: &ParsedDictionary) -> Result<Option<Markup>, String> {
+    if let Some(front_page) = project.get("FrontPage") {
+        Ok(Some(process_inline_markup(&Macros::new(), front_page, "the front page")?))
+    } else {
+        Ok(None)
+    }
+}
+
+fn read_margin_notes(project: &ParsedDictionary) -> Result<bool, String> {
+    if let Some(footnotes) = project.get("Footnotes") {
+        if !footnotes.is_text() {
+            return Err(format!(r#"The Footnotes entry must be End or Margin."#));
+        }
+        match footnotes.as_text().unwrap().as_str() {
+            "End" => Ok(false),
+            "Margin" => Ok(true),
+            other => Err(format!(r#"Unknown Footnotes option {}. Must be End or Margin."#, other)),
+        }
+    } else {
+        Ok(false)
+    }
+}
+
+fn read_theme(project: &ParsedDictionary) -> Result<Theme, String> {
+    if let Some(theme) = project.get("Theme") {
+        if !theme.is_text() {
+            return Err(format!(r#"The Theme entry must be Auto, Light or Dark."#));
+        }
+        match theme.as_text().unwrap().as_str() {
+            "Auto" => Ok(Theme::Auto),
+            "Light" => Ok(Theme::Light),
+            "Dark" => Ok(Theme::Dark),
+            other => Err(format!(r#"Unknown Theme {}. Must be Auto, Light or Dark."#, other)),
+        }
+    } else {
+        Ok(Theme::Auto)
+    }
+}
+
+fn read_stylesheet_paths(project: &ParsedDictionary) -> Result<Vec<PathBuf>, String> {
+    let mut paths: Vec<PathBuf> = vec![];
+    if let Some(stylesheets) = project.get("Stylesheets") {
+        if !stylesheets.is_list() {
+            return Err(format!(r#"The Stylesheets entry must be a list."#));
+        }
+        for path in stylesheets.as_list().unwrap().iter() {
+            if !path.is_text() {
+                return Err(format!("A Stylesheets entry must be a file system path."));
+            }
+            let path = PathBuf::from(path.as_text().unwrap().as_str());
+            if path.file_name().is_none() {
+                return Err(format!("Stylesheet {} is not a file.", path.to_str().unwrap()));
+            }
+            if paths.iter().any(|other| other.file_name() == path.file_name()) {
+                return Err(format!("Stylesheets {} and another stylesheet have the same file name.", path.to_str().unwrap()));
+            }
+            paths.push(path);
+        }
+    }
+    Ok(paths)
+}
+
+fn read_bibliography_paths(project: &ParsedDictionary) -> Result<Vec<PathBuf>, String> {
+    let mut paths = vec![];
+    if let Some(bibliography) = project.get("Bibliography") {
+        if !bibliography.is_list() {
+            return Err(format!(r#"The Bibliography entry must be a list."#));
+        }
+        for path in bibliography.as_list().unwrap().iter() {
+            if !path.is_text() {
+                return Err(format!("A Bibliography entry must be a file system path."));
+            }
+            paths.push(PathBuf::from(path.as_text().unwrap().as_str()));
+        }
+    }
+    Ok(paths)
+}
+
+fn read_citation_style(project: &ParsedDictionary) -> Result<CitationStyle, String> {
+    if let Some(style) = project.get("CitationStyle") {
+        if !style.is_text() {
+            return Err(format!(r#"The CitationStyle entry must be AuthorYear or Numeric."#));
+        }
+        match style.as_text().unwrap().as_str() {
+            "AuthorYear" => Ok(CitationStyle::AuthorYear),
+            "Numeric" => Ok(CitationStyle::Numeric),
+            other => Err(format!(r#"Unknown CitationStyle {}. Must be AuthorYear or Numeric."#, other)),
+        }
+    } else {
+        Ok(CitationStyle::AuthorYear)
+    }
+}
+
 fn read_configuration_paths(project: &ParsedDictionary) -> Result<Vec<PathBuf>, String> {
     let mut paths = vec![];
     if let Some(preamble) = project.get("ConfigFiles") {
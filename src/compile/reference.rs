@@ -0,0 +1,98 @@
+//! Validate inline references after all documents have been read.
+
+use crate::article::{Article, ArticleElement, Articles};
+use crate::document::{Document, DocumentElement, Documents, PanelElement};
+use crate::markup::{find_references, Markup};
+use crate::name::NameElement;
+
+/// Verify that every inline reference refers to an existing class or article.
+///
+/// Reports all dangling references at once.
+pub fn validate_references(articles: &Articles, documents: &Documents) -> Result<(), String> {
+    let mut errors = vec![];
+    for (article_key, article) in articles.get_articles().iter() {
+        let article = article.borrow();
+        for markup in article_markups(&article) {
+            validate_markup_references(&mut errors, articles, markup, &format!("article {}", article_key));
+        }
+    }
+    for document in documents.iter() {
+        for markup in document_markups(document) {
+            validate_markup_references(&mut errors, articles, markup, &format!("document {}", document.key));
+        }
+    }
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(errors.join("\n"))
+    }
+}
+
+/// Collect all markup of an article, including its names.
+pub(crate) fn article_markups(article: &Article) -> Vec<&Markup> {
+    let mut markups = vec![];
+    for name in article.names.iter() {
+        for element in name.iter() {
+            match element {
+                NameElement::Name(markup) | NameElement::Preposition(markup) => markups.push(markup),
+                NameElement::Parameter { markup, .. } => markups.push(markup),
+            }
+        }
+    }
+    for element in article.content.iter() {
+        collect_article_element_markup(&mut markups, element);
+    }
+    markups
+}
+
+/// Collect all markup of a document.
+pub(crate) fn document_markups(document: &Document) -> Vec<&Markup> {
+    let mut markups = vec![];
+    for element in document.structure.iter() {
+        match element {
+            DocumentElement::Heading { heading, .. } => markups.push(heading),
+            DocumentElement::Paragraph(markup) => markups.push(markup),
+            DocumentElement::Table(table) => {
+                markups.extend(table.header.iter().flatten());
+                markups.extend(table.rows.iter().flatten());
+                markups.extend(table.caption.iter());
+            }
+            DocumentElement::Figure(figure) => markups.extend(figure.caption.iter()),
+            DocumentElement::Panel(elements) => {
+                for element in elements {
+                    if let PanelElement::Heading { heading, .. } = element {
+                        markups.push(heading);
+                    }
+                }
+            }
+        }
+    }
+    markups
+}
+
+fn collect_article_element_markup<'a>(markups: &mut Vec<&'a Markup>, element: &'a ArticleElement) {
+    match element {
+        ArticleElement::Heading { markup, .. } => markups.push(markup),
+        ArticleElement::Markup(markup) => markups.push(markup),
+        ArticleElement::Table(table) => {
+            markups.extend(table.header.iter().flatten());
+            markups.extend(table.rows.iter().flatten());
+            markups.extend(table.caption.iter());
+        }
+        ArticleElement::Figure(figure) => markups.extend(figure.caption.iter()),
+        ArticleElement::LocalSeparator => {}
+    }
+}
+
+fn validate_markup_references(errors: &mut Vec<String>, articles: &Articles, markup: &Markup, location: &str) {
+    for reference in find_references(&markup.0) {
+        let key = reference.key.as_str();
+        if key.contains('@') {
+            if articles.get_article(key).is_none() {
+                errors.push(format!("Reference to unknown article {} in {}.", key, location));
+            }
+        } else if articles.get_class(key).is_none() {
+            errors.push(format!("Reference to unknown class {} in {}.", key, location));
+        }
+    }
+}
@@ -1,21 +1,31 @@
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::path::Path;
 use khi::{Dictionary, Text, Value};
 use khi::parse::pdm::{ParsedDictionary};
+use crate::article::Articles;
+use crate::colour::{parse_css_colour, Rgb};
+use crate::compile::template::Templates;
 use crate::file::{read_file_content_to_dictionary, read_file_to_string};
-use crate::style::{Style, Styles};
+use crate::style::{Style, Styles, Symbol};
+
+/// Minimum contrast ratio between a style colour and the text on it, the WCAG
+/// minimum for large text.
+const MINIMUM_CONTRAST: f64 = 3.0;
+/// Text colours of article links in the dark and light themes.
+const TEXT_COLOURS: [(&str, Rgb); 2] = [("dark", Rgb(0.867, 0.867, 0.867)), ("light", Rgb(0.133, 0.133, 0.133))];
 
 pub fn read_style_file(path: &Path) -> Result<Styles, String> {
     let style = read_file_to_string(path, "style")?;
     let style = read_file_content_to_dictionary(path, "style", &style)?;
-    read_style_dictionary(&style)
+    read_style_dictionary(&style, path.parent().unwrap_or(Path::new("")))
 }
 
-pub fn read_style_dictionary(style_dictionary: &ParsedDictionary) -> Result<Styles, String> {
+/// Read styles. Symbol images are relative to `dir`.
+pub fn read_style_dictionary(style_dictionary: &ParsedDictionary, dir: &Path) -> Result<Styles, String> {
     let mut styles = HashMap::new();
     for (class_key, class_style) in style_dictionary.iter() {
         if !class_style.is_dictionary() {
-            eprintln!(r#"The type "{class_key}" at {}:{} must be a dictionary."#, class_style.from().line, class_style.from().column);
+            return Err(format!(r#"The type "{class_key}" at {}:{} must be a dictionary."#, class_style.from().line, class_style.from().column));
         }
         let at = class_style.from();
         let class_style = class_style.as_dictionary().unwrap();
@@ -50,23 +60,89 @@ pub fn read_style_dictionary(style_dictionary: &ParsedDictionary) -> Result<Styl
             if !colour.is_text() {
                 return Err(format!("Colour in type {class_key} at {}:{} must be text.", colour.from().line, colour.from().column));
             }
-            Some(colour.as_text().unwrap().as_str().to_string())
-            // TODO: Verify colour value
+            let text = colour.as_text().unwrap().as_str();
+            match parse_css_colour(text) {
+                Ok(Some(rgb)) => warn_low_contrast(&class_key, text, rgb),
+                Ok(None) => {}
+                Err(e) => return Err(format!("Colour in type {class_key} at {}:{}: {e}", colour.from().line, colour.from().column)),
+            }
+            Some(text.to_string())
+        } else {
+            None
+        };
+        // Read symbol.
+        let symbol = if let Some(symbol) = class_style.get("Symbol") {
+            if !symbol.is_text() {
+                return Err(format!("Symbol in type {class_key} at {}:{} must be text.", symbol.from().line, symbol.from().column));
+            }
+            Some(read_symbol(&class_key, symbol.as_text().unwrap().as_str(), dir).map_err(|e| {
+                format!("Symbol in type {class_key} at {}:{}: {e}", symbol.from().line, symbol.from().column)
+            })?)
         } else {
             None
         };
-        // let symbol = if let Some(s) = type_value.get("Symbol") { // TODO: Fix symbol path
-        //     if !s.is_text() {
-        //         eprintln!("Error: Symbol must be text.");
-        //         continue;
-        //     };
-        //     let s = s.as_text().unwrap().as_str();
-        //     Some(String::from(s))
-        // } else {
-        //     None
-        // };
-        let style = Style { name: name.clone(), description, colour, abbreviation, symbol_path: None };
-        styles.insert(name, style);
+        let style = Style { key: class_key.to_string(), name, description, colour, abbreviation, symbol };
+        styles.insert(class_key.to_string(), style);
     }
     Ok(styles)
 }
+
+/// Read a symbol, which is either the path of an SVG or PNG image or a short
+/// glyph. Text containing `.` or `/` is a path.
+fn read_symbol(style_key: &str, symbol: &str, dir: &Path) -> Result<Symbol, String> {
+    if !symbol.contains(|c| c == '.' || c == '/') {
+        if symbol.chars().count() > 4 {
+            return Err(format!("A glyph symbol must be at most four characters, not {symbol}."));
+        }
+        return Ok(Symbol::Glyph(symbol.to_string()));
+    }
+    let source = dir.join(symbol);
+    let extension = source.extension().and_then(|e| e.to_str()).map(|e| e.to_lowercase());
+    let extension = match extension.as_deref() {
+        Some("svg") => "svg",
+        Some("png") => "png",
+        _ => return Err(format!("The symbol image {symbol} must be an SVG or PNG file.")),
+    };
+    if !source.is_file() {
+        return Err(format!("The symbol image {} does not exist.", source.to_str().unwrap()));
+    }
+    Ok(Symbol::Image { source, file_name: format!("{style_key}.{extension}") })
+}
+
+/// Warn if the text of neither theme is readable on a style colour.
+fn warn_low_contrast(style_key: &str, colour: &str, rgb: Rgb) {
+    let contrasts: Vec<String> = TEXT_COLOURS.iter()
+        .map(|(theme, text)| (theme, rgb.contrast(text)))
+        .filter(|(_, contrast)| *contrast < MINIMUM_CONTRAST)
+        .map(|(theme, contrast)| format!("{contrast:.1} with the {theme} theme"))
+        .collect();
+    if contrasts.len() == TEXT_COLOURS.len() {
+        eprintln!("[Warning] Colour {colour} of style {style_key} has a contrast ratio of {} with the text. At least {MINIMUM_CONTRAST:.1} is recommended.", contrasts.join(" and "));
+    }
+}
+
+/// Report templates and classes that refer to styles that are not defined.
+pub fn validate_style_references(styles: &Styles, templates: &Templates, articles: &Articles) {
+    let mut template_keys: Vec<_> = templates.keys().collect();
+    template_keys.sort();
+    for key in template_keys {
+        if let Some(style) = &templates.get(key).unwrap().style {
+            if !styles.contains_key(style.as_ref()) {
+                eprintln!("[Warning] Template {key} refers to unknown style {style}.");
+            }
+        }
+    }
+    let mut unknown: BTreeMap<String, Vec<String>> = BTreeMap::new();
+    for class in articles.get_classes().values() {
+        let class = class.borrow();
+        if let Some(style) = &class.style {
+            if !styles.contains_key(style.as_ref()) {
+                unknown.entry(style.to_string()).or_default().push(class.key.to_string());
+            }
+        }
+    }
+    for (style, mut classes) in unknown {
+        classes.sort();
+        eprintln!("[Warning] Classes {} refer to unknown style {style}.", classes.join(", "));
+    }
+}
@@ -0,0 +1,118 @@
+//! Read tables in article and document content.
+
+use khi::{Dictionary, List, TaggedTuple, Text, Value};
+use khi::parse::pdm::{ParsedTaggedTuple, ParsedValue};
+use crate::makro::MacroMap;
+use crate::markup::Markup;
+use crate::preprocess_markup::{process_inline_markup, process_unexpanded_markup};
+use crate::table::{Alignment, Table};
+use crate::tuple_split;
+
+/// Read a table command.
+///
+/// Takes a list of rows, and optionally a dictionary with the entries Header
+/// (number of header rows), Align (one of l, c or r per column) and Caption.
+/// A row is a tuple of cells, or a single cell.
+pub fn read_table(macros: &impl MacroMap, tag: &ParsedTaggedTuple) -> Result<Table, String> {
+    let (arguments, _) = tuple_split(tag);
+    if arguments.len() != 1 && arguments.len() != 2 {
+        return Err(format!("<T> takes a list of rows and an optional dictionary of options."));
+    }
+    let rows_value = arguments.get(0).unwrap();
+    if !rows_value.is_list() {
+        return Err(format!("Rows of table at {}:{} must be a list.", rows_value.from().line, rows_value.from().column));
+    }
+    let mut rows = vec![];
+    for row in rows_value.as_list().unwrap().iter() {
+        rows.push(read_row(macros, row)?);
+    }
+    let mut header_rows = 0;
+    let mut alignments = vec![];
+    let mut caption = None;
+    if let Some(options) = arguments.get(1) {
+        if !options.is_dictionary() {
+            return Err(format!("Options of table at {}:{} must be a dictionary.", options.from().line, options.from().column));
+        }
+        let options = options.as_dictionary().unwrap();
+        if let Some(header) = options.get("Header") {
+            let parsed = if header.is_text() {
+                header.as_text().unwrap().as_str().parse::<usize>().ok()
+            } else {
+                None
+            };
+            header_rows = match parsed {
+                Some(n) => n,
+                None => return Err(format!("Header of table at {}:{} must be a number of rows.", header.from().line, header.from().column)),
+            };
+        }
+        if let Some(align) = options.get("Align") {
+            if !align.is_text() {
+                return Err(format!("Align of table at {}:{} must be text.", align.from().line, align.from().column));
+            }
+            alignments = read_alignments(align.as_text().unwrap().as_str())?;
+        }
+        if let Some(parsed_caption) = options.get("Caption") {
+            caption = Some(process_inline_markup(macros, parsed_caption, "table caption")?);
+        }
+        for (key, value) in options.iter() {
+            if key != "Header" && key != "Align" && key != "Caption" {
+                return Err(format!("Table at {}:{} has unknown option {}.", value.from().line, value.from().column, key));
+            }
+        }
+    }
+    if header_rows > rows.len() {
+        return Err(format!("Table at {}:{} has more header rows than rows.", rows_value.from().line, rows_value.from().column));
+    }
+    let body = rows.split_off(header_rows);
+    Ok(Table { header: rows, rows: body, alignments, caption })
+}
+
+fn read_row(macros: &impl MacroMap, row: &ParsedValue) -> Result<Vec<Markup>, String> {
+    let mut cells = vec![];
+    match row.as_tagged_tuple() {
+        Some(tuple) if tuple.name().is_none() => {
+            let (columns, _) = tuple_split(tuple);
+            for column in columns {
+                cells.push(process_unexpanded_markup(macros, column)?);
+            }
+        }
+        _ => {
+            cells.push(process_unexpanded_markup(macros, row)?);
+        }
+    }
+    Ok(cells)
+}
+
+fn read_alignments(align: &str) -> Result<Vec<Alignment>, String> {
+    let mut alignments = vec![];
+    for c in align.chars() {
+        match c {
+            'l' => alignments.push(Alignment::Left),
+            'c' => alignments.push(Alignment::Center),
+            'r' => alignments.push(Alignment::Right),
+            ' ' => {}
+            c => return Err(format!("Table column alignment must be l, c or r, found '{c}'.")),
+        }
+    }
+    Ok(alignments)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn alignments() {
+        assert!(read_alignments("lcr").unwrap() == vec![Alignment::Left, Alignment::Center, Alignment::Right]);
+        assert!(read_alignments("l c  r").unwrap() == vec![Alignment::Left, Alignment::Center, Alignment::Right]);
+        assert!(read_alignments("").unwrap().is_empty());
+    }
+
+    #[test]
+    fn invalid_alignments() {
+        assert_eq!(read_alignments("lx").err().unwrap(), "Table column alignment must be l, c or r, found 'x'.");
+        assert!(read_alignments("L").is_err());
+        assert!(read_alignments("l,c").is_err());
+    }
+
+}
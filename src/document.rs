@@ -1,7 +1,12 @@
 use std::ffi::OsString;
+use std::path::PathBuf;
+use std::time::SystemTime;
 use std::rc::{Rc, Weak};
 use crate::dir::Dir;
 use crate::markup::Markup;
+use crate::table::Table;
+use crate::figure::Figure;
+use crate::file::{creation_time, format_timestamp, modification_time};
 
 pub type Documents = Vec<Rc<Document>>;
 
@@ -12,6 +17,9 @@ pub struct Document {
     pub(crate) file_name: OsString, // Todo: Specific to reading from fs. But can be here for now since that is the only option.
     pub(crate) title: String,
     pub(crate) description: Option<String>,
+    pub(crate) authors: Vec<String>,
+    /// Publication date as in 2024-07-31, from the Published entry.
+    pub(crate) published: Option<String>,
 //    pub(crate) localized_macros: Macros, // We don't need this since all macros are expanded into Markup during reading of document.
     pub(crate) resolution_paths: Vec<String>,
     pub(crate) structure: Vec<DocumentElement>,
@@ -19,6 +27,49 @@ pub struct Document {
 
 impl Document {
 
+    /// Path of the document page on the website.
+    pub fn web_path(&self) -> String {
+        let mut path = String::new();
+        for dir in self.dirtrail() {
+            path.push('/');
+            path.push_str(dir.file_name.to_str().unwrap());
+        }
+        let file_name = self.file_name.to_str().unwrap();
+        let file_name = if file_name.ends_with(".doc.khi") {
+            file_name.trim_end_matches(".doc.khi")
+        } else {
+            file_name.trim_end_matches(".document.khi")
+        };
+        path.push('/');
+        path.push_str(file_name);
+        path.push_str(".html");
+        path
+    }
+
+    /// Path of the document file in the project.
+    pub fn source_path(&self) -> PathBuf {
+        let mut path = PathBuf::from("src");
+        for dir in self.dirtrail().iter().skip(1) { // The tree root is named documents but read from src.
+            path.push(&dir.file_name);
+        }
+        path.push(&self.file_name);
+        path
+    }
+
+    /// Modification time of the document file.
+    pub fn modified(&self) -> Option<SystemTime> {
+        modification_time(&self.source_path())
+    }
+
+    /// Publication date: the Published entry, or else the creation time of the
+    /// document file if the file system records it.
+    pub fn published(&self) -> Option<String> {
+        match &self.published {
+            Some(published) => Some(published.clone()),
+            None => creation_time(&self.source_path()).map(format_timestamp),
+        }
+    }
+
     pub fn dirtrail(&self) -> Vec<Rc<Dir>> {
         let mut trail = vec![];
         trail.push(self.parent_dir.upgrade().unwrap());
@@ -37,6 +88,8 @@ impl Document {
 pub enum DocumentElement {
     Heading { level: u8, heading: Markup, index: Option<String> },
     Paragraph(Markup),
+    Table(Table),
+    Figure(Figure),
     Panel(Vec<PanelElement>),
 }
 
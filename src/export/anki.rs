@@ -0,0 +1,142 @@
+//! Export of articles as Anki decks.
+//!
+//! Each document is written as a tab-separated file of Basic notes, with the
+//! article name on the front and its content on the back. Anki renders the
+//! `\(…\)` and `\[…\]` math delimiters of markup with MathJax. The GUID of a note
+//! is derived from the article key, so importing a file again updates its notes
+//! instead of duplicating them.
+
+use std::fs;
+use std::path::Path;
+use std::rc::Rc;
+use crate::article::{ArticleElement, Articles};
+use crate::document::{Document, DocumentElement};
+use crate::export::panel_article;
+use crate::markup::escape_html;
+use crate::web::class::generate_article_content;
+use crate::web::document::make_long_name;
+use crate::web::reference::resolve_references;
+use crate::web::SiteSettings;
+
+/// Write a deck file for each document with articles, with the images of the
+/// articles in `media`.
+pub fn write_anki_decks(root_path: &Path, site: &SiteSettings, articles: &Articles, documents: &[Rc<Document>], resolution_paths: &[String]) -> Result<(), String> {
+    if fs::exists(root_path).unwrap() {
+        fs::remove_dir_all(root_path).or(Err(format!("Error deleting Anki directory {}.", root_path.to_str().unwrap())))?;
+    }
+    let media_path = root_path.join("media");
+    fs::create_dir_all(&media_path).or(Err(format!("Error creating Anki directory {}.", media_path.to_str().unwrap())))?;
+    for document in documents {
+        let deck = generate_deck(site, articles, document, resolution_paths, &media_path)?;
+        if let Some(deck) = deck {
+            let path = root_path.join(document.web_path().trim_start_matches('/').replace(".html", ".tsv"));
+            fs::create_dir_all(path.parent().unwrap()).or(Err(format!("Error creating Anki directory {}.", path.parent().unwrap().to_str().unwrap())))?;
+            fs::write(&path, deck.as_bytes()).or(Err(format!("Error writing to Anki file {}.", path.to_str().unwrap())))?;
+        }
+    }
+    Ok(())
+}
+
+/// Generate the deck of the articles in the panels of a document, or `None` if
+/// it has no articles.
+fn generate_deck(site: &SiteSettings, articles: &Articles, document: &Document, resolution_paths: &[String], media_path: &Path) -> Result<Option<String>, String> {
+    // Links to the website are absolute if the base URL is known.
+    let base = site.base_url.as_deref().unwrap_or("");
+    let mut keys: Vec<Rc<str>> = vec![];
+    let mut rows = String::new();
+    for element in &document.structure {
+        let elements = match element {
+            DocumentElement::Panel(elements) => elements,
+            _ => continue,
+        };
+        for element in elements {
+            let article = match panel_article(articles, element, resolution_paths) {
+                Some(article) => article,
+                None => continue,
+            };
+            let article = article.borrow();
+            if keys.contains(&article.key) {
+                continue;
+            }
+            keys.push(article.key.clone());
+            let mut front = vec![];
+            make_long_name(&mut front, &article.names[0]);
+            let mut back = vec![];
+            generate_article_content(&mut back, article.content.as_slice(), &article.key, &site.locale, base);
+            let back = resolve_references(&String::from_utf8(back).unwrap(), articles, resolution_paths, base);
+            for element in &article.content {
+                if let ArticleElement::Figure(figure) = element {
+                    let path = media_path.join(&figure.file_name);
+                    fs::copy(&figure.source, &path).or(Err(format!("Error copying image {} to {}.", figure.source.to_str().unwrap(), path.to_str().unwrap())))?;
+                }
+            }
+            let class = article.get_class();
+            let class = class.borrow();
+            let tags = class.style.as_ref().map(|style| style.replace(char::is_whitespace, "_")).unwrap_or_default();
+            rows.push_str(&format!(
+                "{}\t{}\t{}\t{}\n",
+                note_guid(&article.key),
+                quote_field(&String::from_utf8(front).unwrap()),
+                quote_field(&media_images(base, &back)),
+                quote_field(&tags),
+            ));
+        }
+    }
+    if keys.is_empty() {
+        return Ok(None);
+    }
+    let mut deck_name = vec![site.title.clone()];
+    for dir in document.dirtrail().iter().skip(1) { // Skip the root of the tree.
+        deck_name.push(dir.name.clone());
+    }
+    deck_name.push(document.title.clone());
+    let header = format!(
+        "#separator:tab\n#html:true\n#notetype:Basic\n#deck:{}\n#guid column:1\n#tags column:4\n",
+        deck_name.join("::").replace(['\t', '\n'], " "),
+    );
+    Ok(Some(header + &rows))
+}
+
+/// Point the images of a field, whose website paths are prefixed with `base`,
+/// to the Anki media.
+fn media_images(base: &str, html: &str) -> String {
+    html.replace(&format!(r#"src="{}/assets/content/"#, escape_html(base)), r#"src=""#)
+}
+
+/// GUID of the note of an article: a 64-bit FNV-1a hash of the article key.
+fn note_guid(key: &str) -> String {
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for byte in key.as_bytes() {
+        hash ^= *byte as u64;
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+    format!("lexikon-{:016x}", hash)
+}
+
+/// Quote a field, so that it can contain tabs, newlines and quotes.
+fn quote_field(field: &str) -> String {
+    format!("\"{}\"", field.replace('"', "\"\""))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn note_guids_are_stable() {
+        // Changing these breaks updating notes that were imported before.
+        assert_eq!(note_guid(""), "lexikon-cbf29ce484222325");
+        assert_eq!(note_guid("a"), "lexikon-af63dc4c8601ec8c");
+        assert_eq!(note_guid("foobar"), "lexikon-85944171f73967e8");
+        assert_eq!(note_guid("graph.dijkstra"), "lexikon-ea01a7721f3c7b29");
+    }
+
+    #[test]
+    fn fields_are_quoted() {
+        assert_eq!(quote_field("plain"), "\"plain\"");
+        assert_eq!(quote_field("a\tb\nc"), "\"a\tb\nc\"");
+        assert_eq!(quote_field(r#"say "hi""#), r#""say ""hi""""#);
+        assert_eq!(quote_field(""), "\"\"");
+    }
+
+}
@@ -0,0 +1,424 @@
+//! Export of a project as an EPUB 3 book.
+//!
+//! Each document is a chapter, generated from its static page, and the
+//! directory tree is the table of contents. Math is converted to MathML, as
+//! readers cannot run MathJax. The book is a ZIP file without compression.
+
+use std::collections::{BTreeSet, HashSet};
+use std::fs;
+use std::path::Path;
+use std::rc::Rc;
+use std::time::SystemTime;
+use crate::article::Articles;
+use crate::colour::{parse_css_colour, Rgb};
+use crate::dir::Dir;
+use crate::document::Document;
+use crate::export::page::{article_locations, content_figures, generate_static_page};
+use crate::export::{element_end, relative_url, tokenize_markup, MarkupToken, VOID_ELEMENTS};
+use crate::file::format_timestamp;
+use crate::markup::escape_html;
+use crate::mathml::tex_to_mathml;
+use crate::style::Styles;
+use crate::web::SiteSettings;
+
+const STYLESHEET: &str = "body { font-family: serif; line-height: 1.5; }
+h1, h2, h3, h4, h5, h6 { font-family: sans-serif; }
+.article { margin: 1.5em 0; padding: 0 0 0 0.75em; border-left: 3px solid #999999; }
+.article > :first-child .style { padding: 0 0.25em; border-radius: 0.2em; }
+table { border-collapse: collapse; margin: 1em auto; }
+th, td { border: 1px solid #999999; padding: 0.25em 0.5em; }
+.center { text-align: center; }
+.right { text-align: right; }
+figure { text-align: center; }
+img { max-width: 100%; }
+pre { white-space: pre-wrap; }
+math[display=\"block\"] { margin: 0.5em 0; }
+.footnotes, .references { font-size: 0.9em; }
+nav ol { list-style-type: none; }
+";
+
+/// Write the book `book.epub` in `root_path`.
+pub fn write_epub_book(
+    root_path: &Path, site: &SiteSettings, styles: &Styles, articles: &Articles, documents: &[Rc<Document>], tree: &Dir, resolution_paths: &[String],
+) -> Result<(), String> {
+    if fs::exists(root_path).unwrap() {
+        fs::remove_dir_all(root_path).or(Err(format!("Error deleting EPUB directory {}.", root_path.to_str().unwrap())))?;
+    }
+    fs::create_dir_all(root_path).or(Err(format!("Error creating EPUB directory {}.", root_path.to_str().unwrap())))?;
+    let locations = article_locations(articles, documents, resolution_paths);
+    let figures = content_figures(articles, documents);
+    let language = site.locale.get("Language");
+    let mut chapters = vec![];
+    collect_chapters(tree, &mut chapters);
+    let mut zip = ZipWriter::new();
+    zip.add("mimetype", b"application/epub+zip");
+    zip.add("META-INF/container.xml", CONTAINER.as_bytes());
+    let mut images = BTreeSet::new();
+    let mut unsupported = HashSet::new();
+    let mut manifest = String::new();
+    let mut spine = String::new();
+    for (n, document) in chapters.iter().enumerate() {
+        let path = chapter_path(document);
+        let page = generate_static_page(site, styles, articles, document, resolution_paths, &locations, ".xhtml", true);
+        let body = finish_xhtml(&page, &path, &mut images, &mut unsupported);
+        let properties = if body.contains("<math") { r#" properties="mathml""# } else { "" };
+        manifest.push_str(&format!(
+            "<item id=\"chapter-{}\" href=\"{}\" media-type=\"application/xhtml+xml\"{}/>\n", n, escape_html(path.trim_start_matches('/')), properties,
+        ));
+        spine.push_str(&format!("<itemref idref=\"chapter-{}\"/>\n", n));
+        let stylesheet = relative_url(&path, "/style.css");
+        zip.add(&format!("OEBPS{}", path), xhtml_page(language, &document.title, &stylesheet, &body).as_bytes());
+    }
+    let mut commands: Vec<&String> = unsupported.iter().collect();
+    commands.sort();
+    for command in commands {
+        eprintln!("[Warning] Math command \\{} is not supported in EPUB and is shown as an error.", command);
+    }
+    for (n, file_name) in images.iter().enumerate() {
+        let source = &figures[file_name];
+        let bytes = fs::read(source).or(Err(format!("Error reading image {}.", source.to_str().unwrap())))?;
+        manifest.push_str(&format!(
+            "<item id=\"image-{}\" href=\"images/{}\" media-type=\"{}\"/>\n", n, escape_html(file_name), image_media_type(source),
+        ));
+        zip.add(&format!("OEBPS/images/{}", file_name), &bytes);
+    }
+    let mut nav = format!("<nav epub:type=\"toc\" id=\"toc\">\n<h1>{}</h1>\n", escape_html(&site.title));
+    generate_nav_list(&mut nav, tree);
+    nav.push_str("</nav>");
+    zip.add("OEBPS/nav.xhtml", xhtml_page(language, &site.title, "style.css", &nav).as_bytes());
+    zip.add("OEBPS/style.css", generate_stylesheet(styles).as_bytes());
+    zip.add("OEBPS/content.opf", generate_package(site, &manifest, &spine).as_bytes());
+    let path = root_path.join("book.epub");
+    fs::write(&path, zip.finish()).or(Err(format!("Error writing to EPUB file {}.", path.to_str().unwrap())))
+}
+
+const CONTAINER: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<container version="1.0" xmlns="urn:oasis:names:tc:opendocument:xmlns:container">
+<rootfiles>
+<rootfile full-path="OEBPS/content.opf" media-type="application/oebps-package+xml"/>
+</rootfiles>
+</container>
+"#;
+
+/// Documents in the order of the table of contents.
+fn collect_chapters(dir: &Dir, chapters: &mut Vec<Rc<Document>>) {
+    chapters.extend(dir.subdocs.iter().cloned());
+    for subdir in &dir.subdirs {
+        collect_chapters(subdir, chapters);
+    }
+}
+
+fn has_documents(dir: &Dir) -> bool {
+    !dir.subdocs.is_empty() || dir.subdirs.iter().any(|subdir| has_documents(subdir))
+}
+
+/// Path of the chapter of a document in the OEBPS directory.
+fn chapter_path(document: &Document) -> String {
+    let path = document.web_path();
+    format!("{}.xhtml", path.strip_suffix(".html").unwrap_or(&path))
+}
+
+/// Generate the table of contents of a directory, without the directory itself.
+fn generate_nav_list(nav: &mut String, dir: &Dir) {
+    nav.push_str("<ol>\n");
+    for document in &dir.subdocs {
+        nav.push_str(&format!(
+            "<li><a href=\"{}\">{}</a></li>\n", escape_html(chapter_path(document).trim_start_matches('/')), escape_html(&document.title),
+        ));
+    }
+    for subdir in &dir.subdirs {
+        if has_documents(subdir) {
+            nav.push_str(&format!("<li><span>{}</span>\n", escape_html(&subdir.name)));
+            generate_nav_list(nav, subdir);
+            nav.push_str("</li>\n");
+        }
+    }
+    nav.push_str("</ol>\n");
+}
+
+fn xhtml_page(language: &str, title: &str, stylesheet: &str, body: &str) -> String {
+    format!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<!DOCTYPE html>\n<html xmlns=\"http://www.w3.org/1999/xhtml\" xmlns:epub=\"http://www.idpf.org/2007/ops\" lang=\"{lang}\" xml:lang=\"{lang}\">\n<head>\n<meta charset=\"UTF-8\"/>\n<title>{}</title>\n<link rel=\"stylesheet\" type=\"text/css\" href=\"{}\"/>\n</head>\n<body>\n{}\n</body>\n</html>\n",
+        escape_html(title), escape_html(stylesheet), body, lang = escape_html(language),
+    )
+}
+
+fn generate_package(site: &SiteSettings, manifest: &str, spine: &str) -> String {
+    let identifier = match &site.base_url {
+        Some(base_url) => base_url.clone(),
+        None => format!("urn:lexikon:{}", site.title),
+    };
+    let description = match &site.description {
+        Some(description) => format!("<dc:description>{}</dc:description>\n", escape_html(description)),
+        None => String::new(),
+    };
+    format!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>
+<package xmlns=\"http://www.idpf.org/2007/opf\" version=\"3.0\" unique-identifier=\"book-id\">
+<metadata xmlns:dc=\"http://purl.org/dc/elements/1.1/\">
+<dc:identifier id=\"book-id\">{}</dc:identifier>
+<dc:title>{}</dc:title>
+<dc:language>{}</dc:language>
+{}<meta property=\"dcterms:modified\">{}</meta>
+</metadata>
+<manifest>
+<item id=\"nav\" href=\"nav.xhtml\" media-type=\"application/xhtml+xml\" properties=\"nav\"/>
+<item id=\"style\" href=\"style.css\" media-type=\"text/css\"/>
+{}</manifest>
+<spine>
+{}</spine>
+</package>
+",
+        escape_html(&identifier), escape_html(&site.title), escape_html(site.locale.get("Language")), description,
+        format_timestamp(SystemTime::now()), manifest, spine,
+    )
+}
+
+/// The base stylesheet, with article headers in the colours of their styles.
+fn generate_stylesheet(styles: &Styles) -> String {
+    let mut css = STYLESHEET.to_string();
+    let mut keys: Vec<&String> = styles.keys().collect();
+    keys.sort();
+    for key in keys {
+        let colour = match styles[key].colour.as_ref().and_then(|colour| parse_css_colour(colour).ok().flatten()) {
+            Some(colour) => colour,
+            None => continue,
+        };
+        let (black, white) = (Rgb(0.0, 0.0, 0.0), Rgb(1.0, 1.0, 1.0));
+        let text = if colour.contrast(&black) >= colour.contrast(&white) { black } else { white };
+        css.push_str(&format!(
+            ".{key}-style {{ border-left-color: {}; }}\n.{key}-style > :first-child .style {{ background-color: {}; color: {}; }}\n",
+            colour.hex(), colour.hex(), text.hex(),
+        ));
+    }
+    css
+}
+
+/// Write a static page as XHTML with MathML, link its images in the images
+/// directory, and remove the margin notes and the links to the bibliography
+/// page. Images are added to `images` and unsupported math commands to `unsupported`.
+fn finish_xhtml(page: &str, page_path: &str, images: &mut BTreeSet<String>, unsupported: &mut HashSet<String>) -> String {
+    let tokens = tokenize_markup(page);
+    let mut html = String::with_capacity(page.len());
+    // Whether the open elements were kept.
+    let mut kept: Vec<bool> = vec![];
+    let mut code = 0;
+    let mut i = 0;
+    while i < tokens.len() {
+        let token = &tokens[i];
+        i += 1;
+        match token {
+            MarkupToken::Text(text) => html.push_str(&escape_html(text)),
+            MarkupToken::Math { tex, display } => {
+                if code > 0 && *display {
+                    html.push_str(&format!("\\[{}\\]", escape_html(tex)));
+                } else if code > 0 {
+                    html.push_str(&format!("\\({}\\)", escape_html(tex)));
+                } else {
+                    html.push_str(&tex_to_mathml(tex, *display, unsupported));
+                }
+            }
+            MarkupToken::Close(name) => {
+                if *name == "pre" {
+                    code -= 1;
+                }
+                if kept.pop().unwrap_or(true) {
+                    html.push_str(&format!("</{}>", name));
+                }
+            }
+            MarkupToken::Open { name, attributes } => {
+                let class = token.attribute("class");
+                if class == Some("margin-note") || class == Some("bib-link") {
+                    i = element_end(&tokens, i - 1);
+                    continue;
+                }
+                if *name == "a" && class == Some("cite") {
+                    kept.push(false);
+                    continue;
+                }
+                if *name == "pre" {
+                    code += 1;
+                }
+                html.push('<');
+                html.push_str(name);
+                for (attribute, value) in attributes {
+                    let file_name = value.strip_prefix("/assets/content/").filter(|_| *name == "img" && *attribute == "src");
+                    let value = match file_name {
+                        Some(file_name) => {
+                            images.insert(file_name.to_string());
+                            relative_url(page_path, &format!("/images/{}", file_name))
+                        }
+                        None => value.clone(),
+                    };
+                    html.push_str(&format!(r#" {}="{}""#, attribute, escape_html(&value)));
+                }
+                if VOID_ELEMENTS.contains(name) {
+                    html.push_str("/>");
+                } else {
+                    html.push('>');
+                    kept.push(true);
+                }
+            }
+        }
+    }
+    html
+}
+
+fn image_media_type(source: &Path) -> &'static str {
+    let extension = source.extension().and_then(|e| e.to_str()).map(|e| e.to_lowercase()).unwrap_or_default();
+    match extension.as_str() {
+        "png" => "image/png",
+        "jpg" | "jpeg" => "image/jpeg",
+        "gif" => "image/gif",
+        "webp" => "image/webp",
+        "svg" => "image/svg+xml",
+        _ => "application/octet-stream",
+    }
+}
+
+/// A ZIP archive of stored, uncompressed files.
+struct ZipWriter {
+    data: Vec<u8>,
+    /// Name, CRC-32, size and offset of each file.
+    entries: Vec<(String, u32, u32, u32)>,
+}
+
+impl ZipWriter {
+
+    fn new() -> Self {
+        ZipWriter { data: vec![], entries: vec![] }
+    }
+
+    fn add(&mut self, name: &str, bytes: &[u8]) {
+        let crc = crc32(bytes);
+        let offset = self.data.len() as u32;
+        self.data.extend_from_slice(&0x04034b50u32.to_le_bytes());
+        self.write_header(name, crc, bytes.len() as u32);
+        self.data.extend_from_slice(name.as_bytes());
+        self.data.extend_from_slice(bytes);
+        self.entries.push((name.to_string(), crc, bytes.len() as u32, offset));
+    }
+
+    /// Version, flags, method, time, date, CRC-32, sizes and name length, which
+    /// local and central headers have in common.
+    fn write_header(&mut self, name: &str, crc: u32, size: u32) {
+        self.data.extend_from_slice(&20u16.to_le_bytes());
+        // UTF-8 names.
+        self.data.extend_from_slice(&0x0800u16.to_le_bytes());
+        self.data.extend_from_slice(&0u16.to_le_bytes());
+        // 1980-01-01 00:00.
+        self.data.extend_from_slice(&0u16.to_le_bytes());
+        self.data.extend_from_slice(&0x0021u16.to_le_bytes());
+        self.data.extend_from_slice(&crc.to_le_bytes());
+        self.data.extend_from_slice(&size.to_le_bytes());
+        self.data.extend_from_slice(&size.to_le_bytes());
+        self.data.extend_from_slice(&(name.len() as u16).to_le_bytes());
+        self.data.extend_from_slice(&0u16.to_le_bytes());
+    }
+
+    fn finish(mut self) -> Vec<u8> {
+        let directory = self.data.len() as u32;
+        let entries = std::mem::take(&mut self.entries);
+        for (name, crc, size, offset) in &entries {
+            self.data.extend_from_slice(&0x02014b50u32.to_le_bytes());
+            self.data.extend_from_slice(&20u16.to_le_bytes());
+            self.write_header(name, *crc, *size);
+            // Comment length, disk, internal and external attributes.
+            self.data.extend_from_slice(&[0; 10]);
+            self.data.extend_from_slice(&offset.to_le_bytes());
+            self.data.extend_from_slice(name.as_bytes());
+        }
+        let size = self.data.len() as u32 - directory;
+        self.data.extend_from_slice(&0x06054b50u32.to_le_bytes());
+        self.data.extend_from_slice(&[0; 4]);
+        self.data.extend_from_slice(&(entries.len() as u16).to_le_bytes());
+        self.data.extend_from_slice(&(entries.len() as u16).to_le_bytes());
+        self.data.extend_from_slice(&size.to_le_bytes());
+        self.data.extend_from_slice(&directory.to_le_bytes());
+        self.data.extend_from_slice(&0u16.to_le_bytes());
+        self.data
+    }
+
+}
+
+fn crc32(bytes: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for byte in bytes {
+        crc ^= *byte as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 == 1 { crc >> 1 ^ 0xedb88320 } else { crc >> 1 };
+        }
+    }
+    !crc
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn u16_at(data: &[u8], i: usize) -> usize {
+        u16::from_le_bytes([data[i], data[i + 1]]) as usize
+    }
+
+    fn u32_at(data: &[u8], i: usize) -> u32 {
+        u32::from_le_bytes([data[i], data[i + 1], data[i + 2], data[i + 3]])
+    }
+
+    /// Read the files of a stored ZIP archive through its central directory.
+    fn read_zip(data: &[u8]) -> Vec<(String, Vec<u8>)> {
+        let end = data.len() - 22;
+        assert_eq!(u32_at(data, end), 0x06054b50);
+        let count = u16_at(data, end + 10);
+        let mut entry = u32_at(data, end + 16) as usize;
+        let mut files = vec![];
+        for _ in 0..count {
+            assert_eq!(u32_at(data, entry), 0x02014b50);
+            let crc = u32_at(data, entry + 16);
+            let size = u32_at(data, entry + 20) as usize;
+            let name_length = u16_at(data, entry + 28);
+            let offset = u32_at(data, entry + 42) as usize;
+            let name = String::from_utf8(data[entry + 46..entry + 46 + name_length].to_vec()).unwrap();
+            assert_eq!(u32_at(data, offset), 0x04034b50);
+            assert_eq!(u16_at(data, offset + 8), 0, "files are stored");
+            let start = offset + 30 + u16_at(data, offset + 26) + u16_at(data, offset + 28);
+            let bytes = data[start..start + size].to_vec();
+            assert_eq!(crc32(&bytes), crc);
+            files.push((name, bytes));
+            entry += 46 + name_length;
+        }
+        files
+    }
+
+    #[test]
+    fn crc32_known_values() {
+        assert_eq!(crc32(b""), 0);
+        assert_eq!(crc32(b"a"), 0xe8b7be43);
+        assert_eq!(crc32(b"123456789"), 0xcbf43926);
+        assert_eq!(crc32(b"The quick brown fox jumps over the lazy dog"), 0x414fa339);
+    }
+
+    #[test]
+    fn zip_round_trip() {
+        let files = [
+            ("mimetype", "application/epub+zip".as_bytes().to_vec()),
+            ("OEBPS/é.xhtml", "<p>Ünïcode</p>".as_bytes().to_vec()),
+            ("empty", vec![]),
+            ("binary", (0..=255).collect()),
+        ];
+        let mut zip = ZipWriter::new();
+        for (name, bytes) in &files {
+            zip.add(name, bytes);
+        }
+        let data = zip.finish();
+        // The mimetype comes first and uncompressed, as EPUB readers require.
+        assert_eq!(&data[30..38], b"mimetype");
+        assert_eq!(&data[38..58], b"application/epub+zip");
+        let read = read_zip(&data);
+        assert_eq!(read.len(), files.len());
+        for ((name, bytes), (read_name, read_bytes)) in files.iter().zip(read.iter()) {
+            assert_eq!(name, read_name);
+            assert_eq!(bytes, read_bytes);
+        }
+    }
+
+}
@@ -0,0 +1,83 @@
+//! Export of the project graph as GraphViz DOT and GraphML.
+
+use std::fs;
+use std::path::Path;
+use crate::graph::{EdgeKind, Graph, NodeKind};
+use crate::markup::escape_html;
+
+/// Write `graph.dot` and `graph.graphml` to `root_path`.
+pub fn write_graph_files(root_path: &Path, graph: &Graph) -> Result<(), String> {
+    fs::create_dir_all(root_path).or(Err(format!("Error creating graph directory {}.", root_path.to_str().unwrap())))?;
+    let path = root_path.join("graph.dot");
+    fs::write(&path, generate_dot(graph).as_bytes()).or(Err(format!("Error writing to graph file {}.", path.to_str().unwrap())))?;
+    let path = root_path.join("graph.graphml");
+    fs::write(&path, generate_graphml(graph).as_bytes()).or(Err(format!("Error writing to graph file {}.", path.to_str().unwrap())))
+}
+
+fn generate_dot(graph: &Graph) -> String {
+    let mut dot = String::from("digraph lexikon {\n    node [fontname=\"sans-serif\"];\n    edge [fontname=\"sans-serif\", fontsize=10];\n");
+    for node in &graph.nodes {
+        let shape = match node.kind {
+            NodeKind::Class => "ellipse",
+            NodeKind::Article => "note",
+            NodeKind::Document => "box",
+        };
+        dot.push_str(&format!("    {} [label={}, shape={}, class={}", quote_dot(&node.id), quote_dot(&node.label), shape, node.kind.name()));
+        if let Some(colour) = &node.colour {
+            dot.push_str(&format!(", style=filled, fillcolor={}", quote_dot(colour)));
+        }
+        dot.push_str("];\n");
+    }
+    for edge in &graph.edges {
+        let style = match edge.kind {
+            EdgeKind::Member => "arrowhead=empty",
+            EdgeKind::Relation => "label=\"is\"",
+            EdgeKind::Parameter => "style=dashed",
+            EdgeKind::Panel => "style=bold",
+            EdgeKind::Backlink => "style=dotted",
+        };
+        dot.push_str(&format!("    {} -> {} [class={}, {}];\n", quote_dot(&edge.source), quote_dot(&edge.target), edge.kind.name(), style));
+    }
+    dot.push_str("}\n");
+    dot
+}
+
+/// Quote a DOT identifier.
+fn quote_dot(id: &str) -> String {
+    format!("\"{}\"", id.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', " "))
+}
+
+fn generate_graphml(graph: &Graph) -> String {
+    let mut xml = String::from(r#"<?xml version="1.0" encoding="UTF-8"?>
+<graphml xmlns="http://graphml.graphdrawing.org/xmlns">
+  <key id="type" for="node" attr.name="type" attr.type="string"/>
+  <key id="key" for="node" attr.name="key" attr.type="string"/>
+  <key id="label" for="node" attr.name="label" attr.type="string"/>
+  <key id="style" for="node" attr.name="style" attr.type="string"/>
+  <key id="colour" for="node" attr.name="colour" attr.type="string"/>
+  <key id="kind" for="edge" attr.name="kind" attr.type="string"/>
+  <graph id="lexikon" edgedefault="directed">
+"#);
+    for node in &graph.nodes {
+        xml.push_str(&format!(r#"    <node id="{}">"#, escape_html(&node.id)));
+        xml.push_str(&format!(r#"<data key="type">{}</data>"#, node.kind.name()));
+        xml.push_str(&format!(r#"<data key="key">{}</data>"#, escape_html(&node.key)));
+        xml.push_str(&format!(r#"<data key="label">{}</data>"#, escape_html(&node.label)));
+        if let Some(style) = &node.style {
+            xml.push_str(&format!(r#"<data key="style">{}</data>"#, escape_html(style)));
+        }
+        if let Some(colour) = &node.colour {
+            xml.push_str(&format!(r#"<data key="colour">{}</data>"#, colour));
+        }
+        xml.push_str("</node>\n");
+    }
+    for edge in &graph.edges {
+        xml.push_str(&format!(
+            r#"    <edge source="{}" target="{}"><data key="kind">{}</data></edge>"#,
+            escape_html(&edge.source), escape_html(&edge.target), edge.kind.name(),
+        ));
+        xml.push('\n');
+    }
+    xml.push_str("  </graph>\n</graphml>\n");
+    xml
+}
@@ -0,0 +1,150 @@
+//! Export of documents as single-file static HTML pages.
+//!
+//! Each page holds the articles of its document, a small stylesheet and its
+//! images as data URLs, so it can be used without the rest of the website.
+//! Math is written as `\(…\)` and `\[…\]` TeX, which MathJax renders when the
+//! page is online and which stays readable when it is not. Dollar signs are
+//! left as text.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+use crate::article::Articles;
+use crate::document::Document;
+use crate::export::page::{article_locations, content_figures, generate_static_page};
+use crate::export::{element_end, tokenize_markup, MarkupToken, VOID_ELEMENTS};
+use crate::markup::escape_html;
+use crate::style::Styles;
+use crate::web::SiteSettings;
+
+const STYLESHEET: &str = "body { max-width: 48rem; margin: 2rem auto; padding: 0 1rem; font-family: serif; line-height: 1.5; }
+h1, h2, h3, h4, h5, h6 { font-family: sans-serif; }
+.article { margin: 1.5rem 0; padding: 0 1rem; border-left: 3px solid #999999; }
+.article > :first-child .style { font-weight: normal; color: #666666; }
+table { border-collapse: collapse; margin: 1rem auto; }
+th, td { border: 1px solid #999999; padding: 0.25rem 0.5rem; }
+.center { text-align: center; }
+.right { text-align: right; }
+figure { text-align: center; }
+img { max-width: 100%; }
+pre { overflow-x: auto; padding: 0.5rem; background-color: #f2f2f2; }
+.footnotes, .references { font-size: 0.9em; }
+";
+
+/// Write a page for each document in `root_path`, at its path on the website.
+pub fn write_html_pages(root_path: &Path, site: &SiteSettings, styles: &Styles, articles: &Articles, documents: &[Rc<Document>], resolution_paths: &[String]) -> Result<(), String> {
+    if fs::exists(root_path).unwrap() {
+        fs::remove_dir_all(root_path).or(Err(format!("Error deleting HTML directory {}.", root_path.to_str().unwrap())))?;
+    }
+    let locations = article_locations(articles, documents, resolution_paths);
+    let figures = content_figures(articles, documents);
+    for document in documents {
+        let page = generate_static_page(site, styles, articles, document, resolution_paths, &locations, ".html", false);
+        let body = finish_html(&page, &figures)?;
+        let html = format!(
+            "<!DOCTYPE html>\n<html lang=\"{}\">\n<head>\n<meta charset=\"UTF-8\">\n<title>{}</title>\n<style>\n{}</style>\n{}\n</head>\n<body>\n{}\n</body>\n</html>\n",
+            escape_html(site.locale.get("Language")), escape_html(&document.title), STYLESHEET, mathjax_tags(), body,
+        );
+        let path = root_path.join(document.web_path().trim_start_matches('/'));
+        fs::create_dir_all(path.parent().unwrap()).or(Err(format!("Error creating HTML directory {}.", path.parent().unwrap().to_str().unwrap())))?;
+        fs::write(&path, html.as_bytes()).or(Err(format!("Error writing to HTML file {}.", path.to_str().unwrap())))?;
+    }
+    Ok(())
+}
+
+/// MathJax with its default delimiters, which do not include `$`.
+fn mathjax_tags() -> &'static str {
+    r#"<script id="MathJax-script" async src="https://cdn.jsdelivr.net/npm/mathjax@3/es5/tex-mml-chtml.js"></script>"#
+}
+
+/// Write math with its TeX delimiters, embed the images, and remove the margin
+/// notes and the links to the bibliography page.
+fn finish_html(page: &str, figures: &HashMap<String, PathBuf>) -> Result<String, String> {
+    let tokens = tokenize_markup(page);
+    let mut html = String::with_capacity(page.len());
+    // Whether the open elements were kept.
+    let mut kept: Vec<bool> = vec![];
+    let mut i = 0;
+    while i < tokens.len() {
+        let token = &tokens[i];
+        i += 1;
+        match token {
+            MarkupToken::Text(text) => html.push_str(&escape_html(text)),
+            MarkupToken::Math { tex, display } => {
+                let tex = escape_html(tex);
+                if *display {
+                    html.push_str(&format!("\\[{}\\]", tex));
+                } else {
+                    html.push_str(&format!("\\({}\\)", tex));
+                }
+            }
+            MarkupToken::Close(name) => {
+                if kept.pop().unwrap_or(true) {
+                    html.push_str(&format!("</{}>", name));
+                }
+            }
+            MarkupToken::Open { name, attributes } => {
+                let class = token.attribute("class");
+                if class == Some("margin-note") || class == Some("bib-link") {
+                    i = element_end(&tokens, i - 1);
+                    continue;
+                }
+                if *name == "a" && class == Some("cite") {
+                    kept.push(false);
+                    continue;
+                }
+                html.push('<');
+                html.push_str(name);
+                for (attribute, value) in attributes {
+                    let value = if *name == "img" && *attribute == "src" {
+                        embed_image(value, figures)?
+                    } else {
+                        value.clone()
+                    };
+                    html.push_str(&format!(r#" {}="{}""#, attribute, escape_html(&value)));
+                }
+                html.push('>');
+                if !VOID_ELEMENTS.contains(name) {
+                    kept.push(true);
+                }
+            }
+        }
+    }
+    Ok(html)
+}
+
+/// Data URL of a content image, or the source unchanged if it is not one.
+fn embed_image(src: &str, figures: &HashMap<String, PathBuf>) -> Result<String, String> {
+    let source = match src.strip_prefix("/assets/content/").and_then(|file_name| figures.get(file_name)) {
+        Some(source) => source,
+        None => return Ok(src.to_string()),
+    };
+    let extension = source.extension().and_then(|e| e.to_str()).map(|e| e.to_lowercase()).unwrap_or_default();
+    let media_type = match extension.as_str() {
+        "png" => "image/png",
+        "jpg" | "jpeg" => "image/jpeg",
+        "gif" => "image/gif",
+        "webp" => "image/webp",
+        "svg" => "image/svg+xml",
+        _ => "application/octet-stream",
+    };
+    let bytes = fs::read(source).or(Err(format!("Error reading image {}.", source.to_str().unwrap())))?;
+    Ok(format!("data:{};base64,{}", media_type, encode_base64(&bytes)))
+}
+
+fn encode_base64(bytes: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut encoded = String::with_capacity((bytes.len() + 2) / 3 * 4);
+    for chunk in bytes.chunks(3) {
+        let n = (chunk[0] as u32) << 16 | (*chunk.get(1).unwrap_or(&0) as u32) << 8 | *chunk.get(2).unwrap_or(&0) as u32;
+        for j in 0..4 {
+            if j <= chunk.len() {
+                encoded.push(ALPHABET[(n >> (18 - 6 * j) & 63) as usize] as char);
+            } else {
+                encoded.push('=');
+            }
+        }
+    }
+    encoded
+}
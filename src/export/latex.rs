@@ -0,0 +1,488 @@
+//! Export of a project as a LaTeX book.
+//!
+//! Each document becomes a chapter. Headings become unnumbered sections and
+//! each article in a panel becomes a `lexarticle` environment headed by its
+//! style and name. The book compiles with pdfLaTeX or LuaLaTeX.
+
+use std::cell::RefCell;
+use std::collections::HashSet;
+use std::fs;
+use std::path::Path;
+use std::rc::Rc;
+use crate::article::{Article, ArticleElement, Articles};
+use crate::document::{Document, DocumentElement, PanelElement};
+use crate::export::{panel_article, tokenize_markup, MarkupToken, VOID_ELEMENTS};
+use crate::figure::Figure;
+use crate::locale::Locale;
+use crate::makro::Macros;
+use crate::markup::{find_citations, InlineStyle, Markup};
+use crate::name::{Name, NameElement};
+use crate::style::Styles;
+use crate::table::{Alignment, Table};
+use crate::tex::{escape_tex_text, write_tex_with, BreakMode};
+use crate::tex_error_to_text;
+use crate::web::reference::{reference_name, referenced_article};
+
+/// Image types that pdfLaTeX and LuaLaTeX include without conversion.
+const IMAGE_EXTENSIONS: [&str; 4] = ["png", "jpg", "jpeg", "pdf"];
+
+const PREAMBLE: &str = r"\documentclass{book}
+\usepackage{iftex}
+\ifPDFTeX
+\usepackage[T1]{fontenc}
+\usepackage{textcomp}
+\else
+\usepackage{fontspec}
+\fi
+\usepackage{amsmath}
+\usepackage{amssymb}
+\usepackage{graphicx}
+\usepackage[normalem]{ulem}
+\usepackage{hyperref}
+\setcounter{tocdepth}{0}
+\newenvironment{lexarticle}[2]{\par\bigskip\noindent\if\relax\detokenize{#1}\relax\else{\bfseries #1}\enspace\fi#2\par\nopagebreak\smallskip}{\par\medskip}
+";
+
+/// Settings of a LaTeX book.
+pub struct LatexBook<'a> {
+    pub title: &'a str,
+    pub description: Option<&'a str>,
+    pub locale: &'a Locale,
+}
+
+/// Write the project as `book.tex` in `root_path`, with its images in `figures`.
+pub fn write_latex_book(root_path: &Path, book: &LatexBook, styles: &Styles, macros: &Macros, articles: &Articles, documents: &[Rc<Document>], resolution_paths: &[String]) -> Result<(), String> {
+    if fs::exists(root_path).unwrap() {
+        fs::remove_dir_all(root_path).or(Err(format!("Error deleting LaTeX directory {}.", root_path.to_str().unwrap())))?;
+    }
+    let figures_path = root_path.join("figures");
+    fs::create_dir_all(&figures_path).or(Err(format!("Error creating LaTeX directory {}.", figures_path.to_str().unwrap())))?;
+    let mut writer = BookWriter {
+        tex: String::new(),
+        styles,
+        articles,
+        resolution_paths,
+        locale: book.locale,
+        included: included_articles(articles, documents, resolution_paths),
+        labelled: HashSet::new(),
+        figures: vec![],
+    };
+    writer.tex.push_str(PREAMBLE);
+    write_macro_definitions(&mut writer.tex, macros)?;
+    writer.tex.push_str(&format!("\\title{{{}", escape_tex_text(book.title)));
+    if let Some(description) = book.description {
+        writer.tex.push_str(&format!("\\\\[1ex]\\large {}", escape_tex_text(description)));
+    }
+    writer.tex.push_str("}\n\\date{}\n\\begin{document}\n\\maketitle\n\\tableofcontents\n");
+    for document in documents {
+        writer.write_document(document);
+    }
+    writer.tex.push_str("\\end{document}\n");
+    for figure in &writer.figures {
+        let path = figures_path.join(&figure.file_name);
+        fs::copy(&figure.source, &path).or(Err(format!("Error copying image {} to {}.", figure.source.to_str().unwrap(), path.to_str().unwrap())))?;
+    }
+    let path = root_path.join("book.tex");
+    fs::write(&path, writer.tex.as_bytes()).or(Err(format!("Error writing to LaTeX file {}.", path.to_str().unwrap())))
+}
+
+/// Define the project macros. Math in content has its macros expanded already,
+/// so `\providecommand` is used to never replace a command of LaTeX itself.
+fn write_macro_definitions(tex: &mut String, macros: &Macros) -> Result<(), String> {
+    let mut names: Vec<&String> = macros.keys().collect();
+    names.sort();
+    for name in names {
+        if !name.chars().all(|c| c.is_ascii_alphabetic()) {
+            tex.push_str(&format!("% Macro <{}> has no LaTeX name.\n", name));
+            continue;
+        }
+        let makro = macros.get(name).unwrap();
+        let expansion = write_tex_with(&makro.expansion, &Macros::new(), BreakMode::Never).or_else(tex_error_to_text)?;
+        if makro.arity == 0 {
+            tex.push_str(&format!("\\providecommand{{\\{}}}{{{}}}\n", name, expansion));
+        } else {
+            tex.push_str(&format!("\\providecommand{{\\{}}}[{}]{{{}}}\n", name, makro.arity, expansion));
+        }
+    }
+    Ok(())
+}
+
+/// Keys of the articles shown in panels, which references can link to.
+fn included_articles(articles: &Articles, documents: &[Rc<Document>], resolution_paths: &[String]) -> HashSet<Rc<str>> {
+    let mut included = HashSet::new();
+    for document in documents {
+        for element in &document.structure {
+            if let DocumentElement::Panel(elements) = element {
+                for element in elements {
+                    if let Some(article) = panel_article(articles, element, resolution_paths) {
+                        included.insert(article.borrow().key.clone());
+                    }
+                }
+            }
+        }
+    }
+    included
+}
+
+struct BookWriter<'a> {
+    tex: String,
+    styles: &'a Styles,
+    articles: &'a Articles,
+    resolution_paths: &'a [String],
+    locale: &'a Locale,
+    /// Articles in panels of the book.
+    included: HashSet<Rc<str>>,
+    /// Articles whose label has been written.
+    labelled: HashSet<Rc<str>>,
+    /// Images to copy to the figures directory.
+    figures: Vec<Figure>,
+}
+
+impl BookWriter<'_> {
+
+    fn write_document(&mut self, document: &Document) {
+        self.tex.push_str(&format!("\n\\chapter{{{}}}\n", escape_tex_text(&document.title)));
+        if let Some(description) = &document.description {
+            self.tex.push_str(&format!("\\textit{{{}}}\n\n", escape_tex_text(description)));
+        }
+        for element in &document.structure {
+            match element {
+                DocumentElement::Heading { level, heading, index } => {
+                    self.write_heading(*level, heading, index.as_ref());
+                }
+                DocumentElement::Paragraph(text) => {
+                    self.write_markup(&text.0);
+                    self.tex.push_str("\n\n");
+                }
+                DocumentElement::Table(table) => self.write_table(table),
+                DocumentElement::Figure(figure) => self.write_figure(figure),
+                DocumentElement::Panel(elements) => {
+                    for element in elements {
+                        match element {
+                            PanelElement::Heading { level, heading, index } => {
+                                self.write_heading(*level, heading, index.as_ref());
+                            }
+                            PanelElement::ArticleLink { index, .. } | PanelElement::ClassLink { index, .. } => {
+                                if let Some(article) = self.panel_article(element) {
+                                    self.write_article(&article.borrow(), index.as_ref());
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    fn panel_article(&self, element: &PanelElement) -> Option<Rc<RefCell<Article>>> {
+        panel_article(self.articles, element, self.resolution_paths)
+    }
+
+    /// Write a document heading, which is at least level 2.
+    fn write_heading(&mut self, level: u8, heading: &Markup, index: Option<&String>) {
+        let command = match level {
+            0..=2 => "section",
+            3 => "subsection",
+            4 => "subsubsection",
+            _ => "paragraph",
+        };
+        self.tex.push_str(&format!("\\{}*{{", command));
+        if let Some(index) = index {
+            self.tex.push_str(&escape_tex_text(index));
+            self.tex.push_str("\\enspace ");
+        }
+        self.write_markup(&heading.0);
+        self.tex.push_str("}\n\n");
+    }
+
+    fn write_article(&mut self, article: &Article, index: Option<&String>) {
+        let class = article.get_class();
+        let class = class.borrow();
+        let mut style = class.style.as_ref()
+            .and_then(|style| self.styles.get(style.as_ref()))
+            .map(|style| escape_tex_text(&style.name))
+            .unwrap_or_default();
+        if let Some(index) = index {
+            if !style.is_empty() {
+                style.push(' ');
+            }
+            style.push_str(&escape_tex_text(index));
+        }
+        self.tex.push_str(&format!("\\begin{{lexarticle}}{{{}}}{{", style));
+        self.write_long_name(&article.names[0]);
+        self.tex.push_str("}\n");
+        if self.labelled.insert(article.key.clone()) {
+            self.tex.push_str(&format!("\\phantomsection\\label{{{}}}\n", article_label(&article.key)));
+        }
+        let mut markup = String::new();
+        for element in &article.content {
+            match element {
+                ArticleElement::Heading { level, markup: heading } => {
+                    self.tex.push_str(if *level <= 2 { "\\subsubsection*{" } else { "\\paragraph*{" });
+                    self.write_markup(&heading.0);
+                    self.tex.push_str("}\n\n");
+                }
+                ArticleElement::Markup(text) => {
+                    markup.push_str(&text.0);
+                    self.write_markup(&text.0);
+                    self.tex.push_str("\n\n");
+                }
+                ArticleElement::Table(table) => self.write_table(table),
+                ArticleElement::Figure(figure) => self.write_figure(figure),
+                ArticleElement::LocalSeparator => self.tex.push_str("\\medskip\\hrule\\medskip\n\n"),
+            }
+        }
+        self.write_references(&markup);
+        self.tex.push_str("\\end{lexarticle}\n");
+    }
+
+    fn write_long_name(&mut self, name: &Name) {
+        for element in name {
+            match element {
+                NameElement::Name(markup) => {
+                    self.tex.push_str("\\textbf{");
+                    self.write_markup(&markup.0);
+                    self.tex.push('}');
+                }
+                NameElement::Preposition(markup) | NameElement::Parameter { markup, .. } => {
+                    self.write_markup(&markup.0);
+                }
+            }
+        }
+    }
+
+    /// Write the bibliography entries cited in article content.
+    fn write_references(&mut self, markup: &str) {
+        let mut keys = vec![];
+        let mut references = vec![];
+        for citation in find_citations(markup) {
+            if !keys.contains(&citation.key) {
+                keys.push(citation.key);
+                references.push(citation.reference);
+            }
+        }
+        if references.is_empty() {
+            return;
+        }
+        self.tex.push_str(&format!("\\paragraph*{{{}}}\n\\begin{{itemize}}\n", escape_tex_text(self.locale.get("References"))));
+        for reference in references {
+            self.tex.push_str("\\item ");
+            self.write_markup(&reference);
+            self.tex.push('\n');
+        }
+        self.tex.push_str("\\end{itemize}\n");
+    }
+
+    fn write_table(&mut self, table: &Table) {
+        let columns = table.header.iter().chain(table.rows.iter()).map(|row| row.len()).max().unwrap_or(0);
+        if columns == 0 {
+            return;
+        }
+        let alignments: String = (0..columns).map(|column| match table.alignment(column) {
+            Alignment::Left => 'l',
+            Alignment::Center => 'c',
+            Alignment::Right => 'r',
+        }).collect();
+        self.tex.push_str(&format!("\\begin{{center}}\n\\begin{{tabular}}{{{}}}\n\\hline\n", alignments));
+        for row in &table.header {
+            self.write_table_row(row);
+        }
+        if !table.header.is_empty() {
+            self.tex.push_str("\\hline\n");
+        }
+        for row in &table.rows {
+            self.write_table_row(row);
+        }
+        self.tex.push_str("\\hline\n\\end{tabular}\n");
+        if let Some(caption) = &table.caption {
+            self.tex.push_str("\\par\\smallskip ");
+            self.write_markup(&caption.0);
+            self.tex.push('\n');
+        }
+        self.tex.push_str("\\end{center}\n\n");
+    }
+
+    fn write_table_row(&mut self, row: &[Markup]) {
+        for (column, cell) in row.iter().enumerate() {
+            if column > 0 {
+                self.tex.push_str(" & ");
+            }
+            self.write_markup(&cell.0);
+        }
+        self.tex.push_str(" \\\\\n");
+    }
+
+    fn write_figure(&mut self, figure: &Figure) {
+        let extension = figure.source.extension().and_then(|e| e.to_str()).map(|e| e.to_lowercase()).unwrap_or_default();
+        self.tex.push_str("\\begin{center}\n");
+        if IMAGE_EXTENSIONS.contains(&extension.as_str()) {
+            self.tex.push_str(&format!("\\includegraphics[width=0.8\\linewidth,height=0.4\\textheight,keepaspectratio]{{figures/{}}}\n", figure.file_name));
+            if !self.figures.iter().any(|f| f.file_name == figure.file_name) {
+                self.figures.push(figure.clone());
+            }
+        } else {
+            eprintln!("[Warning] Image {} cannot be included in LaTeX. Its alt text is shown instead.", figure.source.to_str().unwrap());
+            self.tex.push_str(&format!("\\fbox{{{}}}\n", escape_tex_text(&figure.alt)));
+        }
+        if let Some(caption) = &figure.caption {
+            self.tex.push_str("\\par\\smallskip ");
+            self.write_markup(&caption.0);
+            self.tex.push('\n');
+        }
+        self.tex.push_str("\\end{center}\n\n");
+    }
+
+    /// Write markup HTML as LaTeX.
+    fn write_markup(&mut self, markup: &str) {
+        let tokens = tokenize_markup(markup);
+        // Text written when each open element is closed.
+        let mut closers: Vec<&str> = vec![];
+        let mut i = 0;
+        while i < tokens.len() {
+            let token = &tokens[i];
+            i += 1;
+            let name = match token {
+                MarkupToken::Text(text) => {
+                    self.tex.push_str(&escape_tex_text(text));
+                    continue;
+                }
+                MarkupToken::Math { tex, display: false } => {
+                    self.tex.push_str(&format!("\\({}\\)", tex));
+                    continue;
+                }
+                MarkupToken::Math { tex, display: true } => {
+                    self.tex.push_str(&format!("\\[{}\\]", tex));
+                    continue;
+                }
+                MarkupToken::Close(_) => {
+                    if let Some(closer) = closers.pop() {
+                        self.tex.push_str(closer);
+                    }
+                    continue;
+                }
+                MarkupToken::Open { name, .. } => *name,
+            };
+            let closer = match name {
+                "p" => "\n\n",
+                "ul" => {
+                    self.tex.push_str("\\begin{itemize}\n");
+                    "\\end{itemize}\n"
+                }
+                "ol" => {
+                    self.tex.push_str("\\begin{enumerate}\n");
+                    "\\end{enumerate}\n"
+                }
+                "li" => {
+                    self.tex.push_str("\\item ");
+                    "\n"
+                }
+                "br" => {
+                    self.tex.push_str("\\\\\n");
+                    continue;
+                }
+                "hr" => {
+                    self.tex.push_str("\n\n\\medskip\\hrule\\medskip\n\n");
+                    continue;
+                }
+                "pre" => { // Code blocks are verbatim, so the highlighting is dropped.
+                    let mut code = String::new();
+                    while i < tokens.len() && !matches!(tokens[i], MarkupToken::Close("pre")) {
+                        match &tokens[i] {
+                            MarkupToken::Text(text) => code.push_str(text),
+                            MarkupToken::Math { tex, display: false } => code.push_str(&format!("\\({}\\)", tex)),
+                            MarkupToken::Math { tex, display: true } => code.push_str(&format!("\\[{}\\]", tex)),
+                            _ => {}
+                        }
+                        i += 1;
+                    }
+                    i += 1;
+                    self.tex.push_str(&format!("\n\\begin{{verbatim}}\n{}\n\\end{{verbatim}}\n", code.trim_end()));
+                    continue;
+                }
+                name if InlineStyle::from_markup_element(name).is_some() => {
+                    self.open_style(InlineStyle::from_markup_element(name).unwrap())
+                }
+                "em" => self.open("\\emph{"),
+                "strong" | "b" => self.open("\\textbf{"),
+                "sub" => self.open("\\textsubscript{"),
+                "sup" => self.open("\\textsuperscript{"),
+                "s" => self.open("\\sout{"),
+                "kbd" | "code" => self.open("\\texttt{"),
+                "fn-note" => self.open("\\footnote{"),
+                "q" => {
+                    self.tex.push_str("``");
+                    "''"
+                }
+                "span" if token.attribute("class") == Some("small-caps") => self.open("\\textsc{"),
+                "a" if token.attribute("class") == Some("ref") => {
+                    let key = token.attribute("data-ref").unwrap_or("");
+                    let empty = matches!(tokens.get(i), Some(MarkupToken::Close(_)));
+                    self.open_reference(key, empty)
+                }
+                "a" if token.attribute("href").is_some() && token.attribute("class") != Some("cite") => {
+                    let href = token.attribute("href").unwrap();
+                    self.tex.push_str(&format!("\\href{{{}}}{{", escape_url(href)));
+                    "}"
+                }
+                name if VOID_ELEMENTS.contains(&name) => continue,
+                _ => "",
+            };
+            closers.push(closer);
+        }
+        for closer in closers.into_iter().rev() {
+            self.tex.push_str(closer);
+        }
+    }
+
+    /// Open text of an inline style.
+    fn open_style(&mut self, style: InlineStyle) -> &'static str {
+        match style {
+            InlineStyle::Emphasis => self.open("\\emph{"),
+            InlineStyle::Strong => self.open("\\textbf{"),
+            InlineStyle::Subscript => self.open("\\textsubscript{"),
+            InlineStyle::Superscript => self.open("\\textsuperscript{"),
+            InlineStyle::Strikethrough => self.open("\\sout{"),
+            InlineStyle::SmallCaps => self.open("\\textsc{"),
+            InlineStyle::Keyboard => self.open("\\texttt{"),
+            InlineStyle::Quotation => {
+                self.tex.push_str("``");
+                "''"
+            }
+        }
+    }
+
+    fn open(&mut self, command: &str) -> &'static str {
+        self.tex.push_str(command);
+        "}"
+    }
+
+    /// Open a reference to an article in the book. An empty label is replaced by
+    /// the name of the article.
+    fn open_reference(&mut self, key: &str, empty: bool) -> &'static str {
+        let article = match referenced_article(self.articles, key, self.resolution_paths) {
+            Some(article) => article,
+            None => return "", // Dangling references are reported when validating.
+        };
+        let article = article.borrow();
+        let linked = self.included.contains(&article.key);
+        if linked {
+            self.tex.push_str(&format!("\\hyperref[{}]{{", article_label(&article.key)));
+        }
+        if empty {
+            self.write_markup(&reference_name(&article));
+        }
+        if linked { "}" } else { "" }
+    }
+
+}
+
+/// Label of an article, with the characters that are special in labels replaced.
+fn article_label(key: &str) -> String {
+    let key: String = key.chars().map(|c| if c.is_ascii_alphanumeric() || "@/.:-".contains(c) { c } else { '-' }).collect();
+    format!("article:{}", key)
+}
+
+/// Escape the characters of a URL that are special in the argument of `\href`.
+fn escape_url(url: &str) -> String {
+    url.replace('\\', "/").replace('#', "\\#").replace('%', "\\%").replace('{', "%7B").replace('}', "%7D")
+}
@@ -0,0 +1,409 @@
+//! Export of documents as Markdown.
+//!
+//! Each document is written as a Markdown file with the articles of its panels
+//! inlined. Math is written as `$…$` and `$$…$$` TeX, footnotes as `[^…]`
+//! footnotes, and markup that Markdown has no syntax for, such as subscripts,
+//! as inline HTML.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+use crate::article::Articles;
+use crate::document::Document;
+use crate::export::page::{article_locations, content_figures, generate_static_page};
+use crate::export::{element_end, relative_url, tokenize_markup, MarkupToken};
+use crate::markup::escape_html;
+use crate::style::Styles;
+use crate::web::SiteSettings;
+
+/// Write a Markdown file for each document in `root_path`, at its path on the
+/// website, with the images in `figures`.
+pub fn write_markdown_pages(root_path: &Path, site: &SiteSettings, styles: &Styles, articles: &Articles, documents: &[Rc<Document>], resolution_paths: &[String]) -> Result<(), String> {
+    if fs::exists(root_path).unwrap() {
+        fs::remove_dir_all(root_path).or(Err(format!("Error deleting Markdown directory {}.", root_path.to_str().unwrap())))?;
+    }
+    let figures_path = root_path.join("figures");
+    fs::create_dir_all(&figures_path).or(Err(format!("Error creating Markdown directory {}.", figures_path.to_str().unwrap())))?;
+    let locations = article_locations(articles, documents, resolution_paths);
+    let figures = content_figures(articles, documents);
+    for document in documents {
+        let page = generate_static_page(site, styles, articles, document, resolution_paths, &locations, ".md", false);
+        let web_path = document.web_path();
+        let mut writer = MarkdownWriter { md: String::new(), page_path: &web_path, lists: vec![], footnotes: false, code: 0 };
+        writer.write_html(&tokenize_markup(&page));
+        copy_figures(&page, &figures, &figures_path)?;
+        let path = root_path.join(web_path.trim_start_matches('/').replace(".html", ".md"));
+        fs::create_dir_all(path.parent().unwrap()).or(Err(format!("Error creating Markdown directory {}.", path.parent().unwrap().to_str().unwrap())))?;
+        fs::write(&path, tidy_markdown(&writer.md).as_bytes()).or(Err(format!("Error writing to Markdown file {}.", path.to_str().unwrap())))?;
+    }
+    Ok(())
+}
+
+/// Copy the content images of a page to the figures directory.
+fn copy_figures(page: &str, figures: &HashMap<String, PathBuf>, figures_path: &Path) -> Result<(), String> {
+    for (file_name, source) in figures {
+        if page.contains(&format!(r#"src="/assets/content/{}""#, file_name)) {
+            let path = figures_path.join(file_name);
+            fs::copy(source, &path).or(Err(format!("Error copying image {} to {}.", source.to_str().unwrap(), path.to_str().unwrap())))?;
+        }
+    }
+    Ok(())
+}
+
+/// Collapse runs of blank lines outside code blocks.
+fn tidy_markdown(md: &str) -> String {
+    let mut tidy = String::with_capacity(md.len());
+    let mut fenced = false;
+    let mut blank = true;
+    for line in md.lines() {
+        let line = if fenced { line } else { line.trim_end_matches(' ') };
+        if line.starts_with("```") {
+            fenced = !fenced;
+        }
+        if line.trim().is_empty() && !fenced {
+            if !blank {
+                tidy.push('\n');
+            }
+            blank = true;
+            continue;
+        }
+        tidy.push_str(line);
+        tidy.push('\n');
+        blank = false;
+    }
+    tidy.trim_end().to_string() + "\n"
+}
+
+/// Escape the characters of text that are special in Markdown.
+fn escape_markdown(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        if "\\`*_[]<>$|".contains(c) {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+struct MarkdownWriter<'a> {
+    md: String,
+    /// Web path of the page, which image paths are relative to.
+    page_path: &'a str,
+    /// Open lists with the number of the next item of ordered lists.
+    lists: Vec<Option<u32>>,
+    /// Whether the open list is the list of footnotes.
+    footnotes: bool,
+    /// Depth of code elements, in which text is not escaped.
+    code: u32,
+}
+
+impl MarkdownWriter<'_> {
+
+    /// Write page HTML as Markdown.
+    fn write_html(&mut self, tokens: &[MarkupToken]) {
+        // Names of the open elements with the text written when they are closed.
+        let mut closers: Vec<(&str, String)> = vec![];
+        let mut i = 0;
+        while i < tokens.len() {
+            let token = &tokens[i];
+            i += 1;
+            let name = match token {
+                MarkupToken::Text(text) => {
+                    if self.code > 0 {
+                        self.md.push_str(text);
+                    } else {
+                        self.md.push_str(&escape_markdown(text).replace('\n', " "));
+                    }
+                    continue;
+                }
+                MarkupToken::Math { tex, display: false } => {
+                    self.md.push_str(&format!("${}$", tex.trim()));
+                    continue;
+                }
+                MarkupToken::Math { tex, display: true } => {
+                    self.md.push_str(&format!("\n\n$$\n{}\n$$\n\n", tex.trim()));
+                    continue;
+                }
+                MarkupToken::Close(_) => {
+                    if let Some((name, closer)) = closers.pop() {
+                        self.close(name);
+                        self.md.push_str(&closer);
+                    }
+                    continue;
+                }
+                MarkupToken::Open { name, .. } => *name,
+            };
+            let class = token.attribute("class");
+            if let Some(id) = token.attribute("id").filter(|_| name == "section") {
+                self.md.push_str(&format!("\n\n<a id=\"{}\"></a>\n\n", escape_html(id)));
+            }
+            let closer = match name {
+                "h1" | "h2" | "h3" | "h4" | "h5" | "h6" => {
+                    let level = name[1..].parse().unwrap();
+                    self.md.push_str(&format!("\n\n{} ", "#".repeat(level)));
+                    String::from("\n\n")
+                }
+                "table" => {
+                    let end = element_end(tokens, i - 1);
+                    self.write_table(&tokens[i..end - 1]);
+                    i = end;
+                    continue;
+                }
+                "p" | "section" | "figure" | "figcaption" => {
+                    self.md.push_str("\n\n");
+                    String::from("\n\n")
+                }
+                "ul" | "ol" => {
+                    if self.lists.is_empty() {
+                        self.md.push_str("\n\n");
+                    }
+                    self.footnotes = class == Some("footnotes");
+                    if !self.footnotes {
+                        self.lists.push(if name == "ol" { Some(1) } else { None });
+                    }
+                    String::from("\n\n")
+                }
+                "li" if self.footnotes => {
+                    let label = token.attribute("id").unwrap_or("").trim_start_matches("fn-");
+                    self.md.push_str(&format!("\n[^{}]: ", footnote_label(label)));
+                    String::new()
+                }
+                "li" => {
+                    let indent = "    ".repeat(self.lists.len().saturating_sub(1));
+                    let marker = match self.lists.last_mut() {
+                        Some(Some(n)) => {
+                            let number = *n;
+                            *n += 1;
+                            format!("{}.", number)
+                        }
+                        _ => String::from("-"),
+                    };
+                    self.md.push_str(&format!("\n{}{} ", indent, marker));
+                    String::new()
+                }
+                "br" => {
+                    self.md.push_str("\\\n");
+                    continue;
+                }
+                "hr" => {
+                    self.md.push_str("\n\n---\n\n");
+                    continue;
+                }
+                "img" => {
+                    let src = token.attribute("src").unwrap_or("");
+                    let src = match src.strip_prefix("/assets/content/") {
+                        Some(file_name) => relative_url(self.page_path, &format!("/figures/{}", file_name)),
+                        None => src.to_string(),
+                    };
+                    self.md.push_str(&format!("![{}]({})", escape_markdown(token.attribute("alt").unwrap_or("")), src.replace(' ', "%20")));
+                    continue;
+                }
+                "pre" => { // The language is on the code element of the code block.
+                    let end = element_end(tokens, i - 1);
+                    let language = tokens[i..end].iter()
+                        .find_map(|token| token.attribute("class"))
+                        .and_then(|class| class.strip_prefix("language-"))
+                        .unwrap_or("");
+                    let mut code = String::new();
+                    for token in &tokens[i..end] {
+                        match token {
+                            MarkupToken::Text(text) => code.push_str(text),
+                            MarkupToken::Math { tex, display: false } => code.push_str(&format!("\\({}\\)", tex)),
+                            MarkupToken::Math { tex, display: true } => code.push_str(&format!("\\[{}\\]", tex)),
+                            _ => {}
+                        }
+                    }
+                    self.md.push_str(&format!("\n\n```{}\n{}\n```\n\n", language, code.trim_end()));
+                    i = end;
+                    continue;
+                }
+                "em" | "i" => self.wrap("*"),
+                "strong" | "b" => self.wrap("**"),
+                "s" => self.wrap("~~"),
+                "q" => self.wrap("\""),
+                "code" | "kbd" => {
+                    self.code += 1;
+                    self.md.push('`');
+                    String::from("`")
+                }
+                "sub" | "sup" if class != Some("fn-ref") => {
+                    self.md.push_str(&format!("<{}>", name));
+                    format!("</{}>", name)
+                }
+                "sup" => { // A footnote reference, which links to the footnote as in #fn-key-1.
+                    let end = element_end(tokens, i - 1);
+                    let label = tokens[i..end].iter()
+                        .find_map(|token| token.attribute("href"))
+                        .map(|href| href.trim_start_matches("#fn-"))
+                        .unwrap_or("");
+                    self.md.push_str(&format!("[^{}]", footnote_label(label)));
+                    i = end;
+                    continue;
+                }
+                "span" | "a" if class == Some("margin-note") || class == Some("bib-link") || class == Some("fn-back") => {
+                    i = element_end(tokens, i - 1);
+                    continue;
+                }
+                "a" if class != Some("cite") && token.attribute("href").is_some() => {
+                    self.md.push('[');
+                    format!("]({})", token.attribute("href").unwrap().replace(' ', "%20").replace(')', "%29"))
+                }
+                _ => String::new(),
+            };
+            closers.push((name, closer));
+        }
+    }
+
+    /// Leave the state of an element that is closed.
+    fn close(&mut self, name: &str) {
+        match name {
+            "ul" | "ol" if self.footnotes => self.footnotes = false,
+            "ul" | "ol" => {
+                self.lists.pop();
+            }
+            "code" | "kbd" => self.code -= 1,
+            _ => {}
+        }
+    }
+
+    fn wrap(&mut self, delimiter: &str) -> String {
+        self.md.push_str(delimiter);
+        delimiter.to_string()
+    }
+
+    /// Write a table as a pipe table, with the caption after it.
+    fn write_table(&mut self, tokens: &[MarkupToken]) {
+        let mut caption = String::new();
+        let mut rows: Vec<Vec<String>> = vec![];
+        let mut alignments: Vec<&str> = vec![];
+        let mut i = 0;
+        while i < tokens.len() {
+            let token = &tokens[i];
+            let end = element_end(tokens, i);
+            match token {
+                MarkupToken::Open { name: "caption", .. } => caption = self.write_inline(&tokens[i + 1..end - 1]),
+                MarkupToken::Open { name: "tr", .. } => {
+                    rows.push(vec![]);
+                    i += 1;
+                    continue;
+                }
+                MarkupToken::Open { name: "th" | "td", .. } => {
+                    if rows.len() == 1 { // Columns are aligned by the cells of the first row.
+                        alignments.push(match token.attribute("class") {
+                            Some("center") => ":---:",
+                            Some("right") => "---:",
+                            _ => "---",
+                        });
+                    }
+                    let cell = self.write_inline(&tokens[i + 1..end - 1]);
+                    if let Some(row) = rows.last_mut() {
+                        row.push(cell);
+                    }
+                }
+                _ => {
+                    i += 1;
+                    continue;
+                }
+            }
+            i = end;
+        }
+        if rows.is_empty() {
+            return;
+        }
+        let columns = rows.iter().map(|row| row.len()).max().unwrap_or(0).max(1);
+        alignments.resize(columns, "---");
+        self.md.push_str("\n\n");
+        for (r, row) in rows.iter().enumerate() {
+            self.md.push('|');
+            for column in 0..columns {
+                self.md.push_str(&format!(" {} |", row.get(column).map(|cell| cell.as_str()).unwrap_or("")));
+            }
+            self.md.push('\n');
+            if r == 0 {
+                self.md.push('|');
+                for alignment in &alignments {
+                    self.md.push_str(&format!(" {} |", alignment));
+                }
+                self.md.push('\n');
+            }
+        }
+        if !caption.is_empty() {
+            self.md.push_str(&format!("\n{}\n", caption));
+        }
+        self.md.push('\n');
+    }
+
+    /// Write the tokens of an inline element on a single line.
+    fn write_inline(&mut self, tokens: &[MarkupToken]) -> String {
+        let md = std::mem::take(&mut self.md);
+        self.write_html(tokens);
+        let inline = std::mem::replace(&mut self.md, md);
+        inline.split_whitespace().collect::<Vec<_>>().join(" ")
+    }
+
+}
+
+/// Footnote label from a footnote id of a page, which may contain characters
+/// that are not allowed in labels.
+fn footnote_label(id: &str) -> String {
+    id.chars().map(|c| if c.is_whitespace() || c == ']' || c == '[' || c == '^' { '-' } else { c }).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Convert page HTML to tidy Markdown.
+    fn convert(html: &str) -> String {
+        let mut writer = MarkdownWriter { md: String::new(), page_path: "/docs/page.html", lists: vec![], footnotes: false, code: 0 };
+        writer.write_html(&tokenize_markup(html));
+        tidy_markdown(&writer.md)
+    }
+
+    #[test]
+    fn special_characters_are_escaped() {
+        assert_eq!(escape_markdown("$5 | *a* _b_ [c] <d> `e` \\"), "\\$5 \\| \\*a\\* \\_b\\_ \\[c\\] \\<d\\> \\`e\\` \\\\");
+        assert_eq!(convert("<p>Costs $5 &amp; 2*3 | more</p>"), "Costs \\$5 & 2\\*3 \\| more\n");
+    }
+
+    #[test]
+    fn code_is_not_escaped() {
+        assert_eq!(convert("<p><code>a*b | $c</code></p>"), "`a*b | $c`\n");
+        assert_eq!(convert(r#"<pre><code class="language-rust">let x = *y;</code></pre>"#), "```rust\nlet x = *y;\n```\n");
+    }
+
+    #[test]
+    fn blank_lines_are_collapsed_outside_code_blocks() {
+        assert_eq!(tidy_markdown("\n\na  \n\n\n\nb\n```\nx  \n\n\ny\n```\n\n"), "a\n\nb\n```\nx  \n\n\ny\n```\n");
+    }
+
+    #[test]
+    fn pipe_tables() {
+        let html = concat!(
+            "<table><caption>Sizes of <em>things</em></caption><tbody>",
+            "<tr><th>Name</th><th class=\"center\">Size</th><th class=\"right\">Count</th></tr>",
+            "<tr><td>a | b</td><td>2</td></tr>",
+            "</tbody></table>",
+        );
+        assert_eq!(convert(html), concat!(
+            "| Name | Size | Count |\n",
+            "| --- | :---: | ---: |\n",
+            "| a \\| b | 2 |  |\n",
+            "\n",
+            "Sizes of *things*\n",
+        ));
+    }
+
+    #[test]
+    fn footnotes() {
+        let html = concat!(
+            r##"<p>Text<sup class="fn-ref" id="fnref-doc-1"><a href="#fn-doc-1">1</a></sup><span class="margin-note"><sup>1</sup> Note</span>.</p>"##,
+            r##"<ol class="footnotes"><li id="fn-doc-1">Note <a class="fn-back" href="#fnref-doc-1">&#8617;</a></li></ol>"##,
+        );
+        assert_eq!(convert(html), "Text[^doc-1].\n\n[^doc-1]: Note\n");
+        assert_eq!(footnote_label("a b]c[d^e"), "a-b-c-d-e");
+    }
+
+}
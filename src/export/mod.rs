@@ -0,0 +1,214 @@
+//! Export of a project to formats other than the website.
+//!
+//! Markup is stored as HTML, so exporters to formats other than HTML read it
+//! back as tokens with [tokenize_markup].
+
+pub mod latex;
+pub mod anki;
+pub mod graph;
+pub mod page;
+pub mod markdown;
+pub mod html;
+pub mod epub;
+
+use std::cell::RefCell;
+use std::rc::Rc;
+use crate::article::{Article, Articles};
+use crate::document::PanelElement;
+use crate::markup::unescape_html;
+
+/// A token of markup HTML.
+pub(crate) enum MarkupToken<'a> {
+    /// Unescaped text.
+    Text(String),
+    /// TeX of inline or display math, which is not escaped in markup.
+    Math { tex: &'a str, display: bool },
+    /// An opening or void element.
+    Open { name: &'a str, attributes: Vec<(&'a str, String)> },
+    Close(&'a str),
+}
+
+impl MarkupToken<'_> {
+
+    /// Value of an attribute of an opening element.
+    pub(crate) fn attribute(&self, attribute: &str) -> Option<&str> {
+        match self {
+            MarkupToken::Open { attributes, .. } => attributes.iter().find(|(name, _)| *name == attribute).map(|(_, value)| value.as_str()),
+            _ => None,
+        }
+    }
+
+}
+
+/// The article that a panel element links to.
+pub(crate) fn panel_article(articles: &Articles, element: &PanelElement, resolution_paths: &[String]) -> Option<Rc<RefCell<Article>>> {
+    match element {
+        PanelElement::ArticleLink { key, .. } => articles.get_article(key).cloned(),
+        PanelElement::ClassLink { key, .. } => articles.get_class(key).map(|class| class.borrow().resolve(resolution_paths)),
+        PanelElement::Heading { .. } => None,
+    }
+}
+
+/// URL of the web path `to` relative to the page at web path `from`.
+pub(crate) fn relative_url(from: &str, to: &str) -> String {
+    let from: Vec<&str> = from.trim_start_matches('/').split('/').collect();
+    let to: Vec<&str> = to.trim_start_matches('/').split('/').collect();
+    let from_dirs = &from[..from.len() - 1];
+    let common = from_dirs.iter().zip(to.iter()).take_while(|(a, b)| a == b).count();
+    let mut url = "../".repeat(from_dirs.len() - common);
+    url.push_str(&to[common..].join("/"));
+    url
+}
+
+/// Index after the close of the element opened at `start`.
+pub(crate) fn element_end(tokens: &[MarkupToken], start: usize) -> usize {
+    let name = match &tokens[start] {
+        MarkupToken::Open { name, .. } => *name,
+        _ => return start + 1,
+    };
+    if VOID_ELEMENTS.contains(&name) {
+        return start + 1;
+    }
+    let mut depth = 0;
+    for (i, token) in tokens.iter().enumerate().skip(start) {
+        match token {
+            MarkupToken::Open { name: open, .. } if *open == name => depth += 1,
+            MarkupToken::Close(close) if *close == name => {
+                depth -= 1;
+                if depth == 0 {
+                    return i + 1;
+                }
+            }
+            _ => {}
+        }
+    }
+    tokens.len()
+}
+
+/// Elements that have no closing tag.
+pub(crate) const VOID_ELEMENTS: [&str; 3] = ["br", "hr", "img"];
+
+/// Split markup HTML into text, math and elements.
+pub(crate) fn tokenize_markup(markup: &str) -> Vec<MarkupToken> {
+    let mut tokens = vec![];
+    let mut text_start = 0;
+    let mut i = 0;
+    while i < markup.len() {
+        let rest = &markup[i..];
+        let token = if rest.starts_with("\\(") || rest.starts_with("\\[") {
+            let display = rest.starts_with("\\[");
+            let end = if display { "\\]" } else { "\\)" };
+            rest[2..].find(end).map(|length| (MarkupToken::Math { tex: &rest[2..2 + length], display }, 4 + length))
+        } else if rest.starts_with('<') {
+            rest.find('>').map(|length| (read_element(&rest[1..length]), length + 1))
+        } else {
+            None
+        };
+        if let Some((token, length)) = token {
+            if text_start < i {
+                tokens.push(MarkupToken::Text(unescape_html(&markup[text_start..i])));
+            }
+            tokens.push(token);
+            i += length;
+            text_start = i;
+        } else {
+            i += rest.chars().next().unwrap().len_utf8();
+        }
+    }
+    if text_start < markup.len() {
+        tokens.push(MarkupToken::Text(unescape_html(&markup[text_start..])));
+    }
+    tokens
+}
+
+/// Read the inside of a tag.
+fn read_element(tag: &str) -> MarkupToken {
+    if let Some(name) = tag.strip_prefix('/') {
+        return MarkupToken::Close(name.trim());
+    }
+    let tag = tag.trim_end_matches('/');
+    let name_end = tag.find(char::is_whitespace).unwrap_or(tag.len());
+    let name = &tag[..name_end];
+    let mut attributes = vec![];
+    let mut rest = tag[name_end..].trim_start();
+    while !rest.is_empty() {
+        let attribute_end = rest.find(|c: char| c == '=' || c.is_whitespace()).unwrap_or(rest.len());
+        let attribute = &rest[..attribute_end];
+        rest = &rest[attribute_end..];
+        if let Some(value) = rest.strip_prefix("=\"") {
+            let value_end = value.find('"').unwrap_or(value.len());
+            attributes.push((attribute, unescape_html(&value[..value_end])));
+            rest = value.get(value_end + 1..).unwrap_or("");
+        } else {
+            attributes.push((attribute, String::new()));
+        }
+        rest = rest.trim_start();
+    }
+    MarkupToken::Open { name, attributes }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::markup::escape_html;
+
+    /// Describe tokens as text, since tokens borrow from the markup.
+    fn describe(markup: &str) -> Vec<String> {
+        tokenize_markup(markup).iter().map(|token| match token {
+            MarkupToken::Text(text) => format!("text {}", text),
+            MarkupToken::Math { tex, display } => format!("math {} {}", display, tex),
+            MarkupToken::Open { name, attributes } => {
+                let attributes: Vec<String> = attributes.iter().map(|(name, value)| format!(" {}={}", name, value)).collect();
+                format!("open {}{}", name, attributes.concat())
+            }
+            MarkupToken::Close(name) => format!("close {}", name),
+        }).collect()
+    }
+
+    #[test]
+    fn relative_urls_from_the_root() {
+        assert_eq!(relative_url("/index.html", "/figures/a.png"), "figures/a.png");
+        assert_eq!(relative_url("/a.html", "/b.html#x"), "b.html#x");
+        assert_eq!(relative_url("/a.html", "/docs/b/c.html"), "docs/b/c.html");
+    }
+
+    #[test]
+    fn relative_urls_between_directories() {
+        assert_eq!(relative_url("/docs/x/page.html", "/docs/x/other.html"), "other.html");
+        assert_eq!(relative_url("/docs/x/page.html", "/docs/y/other.html#s"), "../y/other.html#s");
+        assert_eq!(relative_url("/docs/x/page.html", "/figures/a.png"), "../../figures/a.png");
+        assert_eq!(relative_url("/docs/page.html", "/docs/x/other.html"), "x/other.html");
+        assert_eq!(relative_url("/docs/x/page.html", "/index.html"), "../../index.html");
+    }
+
+    #[test]
+    fn elements_text_and_math() {
+        assert_eq!(
+            describe(r#"<p>A <a class="ref" data-ref="x">link</a> and \(x < y\)<br>\[a\]</p>"#),
+            [
+                "open p", "text A ", "open a class=ref data-ref=x", "text link", "close a", "text  and ",
+                "math false x < y", "open br", "math true a", "close p",
+            ],
+        );
+        assert_eq!(describe(r#"<img src="/a.png" alt="a &quot;b&quot;"/>"#), [r#"open img src=/a.png alt=a "b""#]);
+    }
+
+    #[test]
+    fn unclosed_math_and_tags_are_text() {
+        assert_eq!(describe(r"cost \(5"), [r"text cost \(5"]);
+        assert_eq!(describe("a <b"), ["text a <b"]);
+    }
+
+    #[test]
+    fn escaped_text_is_not_read_as_elements() {
+        assert_eq!(describe(&escape_html("a < b and c > d & e")), ["text a < b and c > d & e"]);
+    }
+
+    #[test]
+    fn escaped_text_round_trips() {
+        for text in ["a & b", "5 < 6 > 4", "&lt;", "&#38;lt;", "&amp;", "\"quoted\"", "$x$"] {
+            assert_eq!(describe(&format!("<p>{}</p>", escape_html(text))), ["open p".to_string(), format!("text {}", text), "close p".to_string()]);
+        }
+    }
+
+}
@@ -0,0 +1,198 @@
+//! Static pages of documents, with the articles of their panels inlined.
+//!
+//! The Markdown and HTML exports are generated from these pages. They are HTML
+//! like the website, but every article is in the page of its document, so they
+//! need no script.js. References link to the article in the page that inlines
+//! it, relative to the page.
+
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
+use std::rc::Rc;
+use crate::article::{Article, ArticleElement, Articles};
+use crate::document::{Document, DocumentElement, PanelElement};
+use crate::export::{panel_article, relative_url};
+use crate::locale::Locale;
+use crate::markup::{escape_html, find_references, Markup};
+use crate::style::Styles;
+use crate::web::citation::generate_references;
+use crate::web::class::generate_article_content;
+use crate::web::document::make_long_name;
+use crate::web::figure::generate_figure;
+use crate::web::footnote::generate_footnotes;
+use crate::web::reference::{reference_name, referenced_article};
+use crate::web::table::generate_table;
+use crate::web::SiteSettings;
+
+/// Web path of the first document that inlines each article.
+pub(crate) fn article_locations(articles: &Articles, documents: &[Rc<Document>], resolution_paths: &[String]) -> HashMap<Rc<str>, String> {
+    let mut locations = HashMap::new();
+    for document in documents {
+        for element in &document.structure {
+            if let DocumentElement::Panel(elements) = element {
+                for element in elements {
+                    if let Some(article) = panel_article(articles, element, resolution_paths) {
+                        locations.entry(article.borrow().key.clone()).or_insert_with(|| document.web_path());
+                    }
+                }
+            }
+        }
+    }
+    locations
+}
+
+/// Source files of the images in content by their file names in the website assets.
+pub(crate) fn content_figures(articles: &Articles, documents: &[Rc<Document>]) -> HashMap<String, PathBuf> {
+    let mut figures = HashMap::new();
+    for article in articles.get_articles().values() {
+        for element in article.borrow().content.iter() {
+            if let ArticleElement::Figure(figure) = element {
+                figures.insert(figure.file_name.clone(), figure.source.clone());
+            }
+        }
+    }
+    for document in documents {
+        for element in &document.structure {
+            if let DocumentElement::Figure(figure) = element {
+                figures.insert(figure.file_name.clone(), figure.source.clone());
+            }
+        }
+    }
+    figures
+}
+
+/// Id of an inlined article, the same as on the website.
+pub(crate) fn article_anchor(key: &str) -> String {
+    format!("article.{}", key)
+}
+
+/// Generate the static page of a document. Links to other pages have the file
+/// extension `extension`. Articles are headed by the abbreviation of their style
+/// if `abbreviate` is set and the style has one, else by its name.
+pub(crate) fn generate_static_page(
+    site: &SiteSettings, styles: &Styles, articles: &Articles, document: &Document, resolution_paths: &[String],
+    locations: &HashMap<Rc<str>, String>, extension: &str, abbreviate: bool,
+) -> String {
+    let mut body = vec![];
+    body.extend_from_slice(format!("<h1>{}</h1>", escape_html(&document.title)).as_bytes());
+    if let Some(description) = &document.description {
+        body.extend_from_slice(format!("<p><em>{}</em></p>", escape_html(description)).as_bytes());
+    }
+    // Markup of the document itself, for its list of references.
+    let mut paragraphs = String::new();
+    let mut anchored = HashSet::new();
+    let mut level = 1;
+    for element in &document.structure {
+        match element {
+            DocumentElement::Heading { level: heading_level, heading, index } => {
+                generate_heading(&mut body, *heading_level, heading, index.as_ref());
+                level = *heading_level;
+            }
+            DocumentElement::Paragraph(text) => {
+                body.extend_from_slice(format!("<p>{}</p>", text.to_html()).as_bytes());
+                paragraphs.push_str(&text.0);
+            }
+            DocumentElement::Table(table) => generate_table(&mut body, table),
+            DocumentElement::Figure(figure) => generate_figure(&mut body, figure, ""),
+            DocumentElement::Panel(elements) => {
+                for element in elements {
+                    match element {
+                        PanelElement::Heading { level: heading_level, heading, index } => {
+                            generate_heading(&mut body, *heading_level, heading, index.as_ref());
+                            level = *heading_level;
+                        }
+                        PanelElement::ArticleLink { index, .. } | PanelElement::ClassLink { index, .. } => {
+                            if let Some(article) = panel_article(articles, element, resolution_paths) {
+                                let article = article.borrow();
+                                let anchor = anchored.insert(article.key.clone());
+                                generate_static_article(&mut body, styles, &site.locale, &article, index.as_ref(), (level + 1).min(6), anchor, abbreviate);
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+    let body = String::from_utf8(body).unwrap();
+    let mut html = vec![];
+    generate_footnotes(&mut html, &body, &document.key);
+    generate_references(&mut html, &paragraphs, &site.locale, "");
+    let html = String::from_utf8(html).unwrap();
+    resolve_static_references(&html, articles, resolution_paths, locations, &document.web_path(), extension)
+}
+
+fn generate_heading(html: &mut Vec<u8>, level: u8, heading: &Markup, index: Option<&String>) {
+    match index {
+        Some(index) => html.extend_from_slice(format!("<h{level}>{} {}</h{level}>", escape_html(index), heading.to_html()).as_bytes()),
+        None => html.extend_from_slice(format!("<h{level}>{}</h{level}>", heading.to_html()).as_bytes()),
+    }
+}
+
+/// Generate an article headed by its style, index and name. Only the first
+/// occurrence of an article in a page has an id.
+fn generate_static_article(html: &mut Vec<u8>, styles: &Styles, locale: &Locale, article: &Article, index: Option<&String>, level: u8, anchor: bool, abbreviate: bool) {
+    let class = article.get_class();
+    let class = class.borrow();
+    let style = class.style.as_ref().and_then(|style| styles.get(style.as_ref()));
+    let mut label = style
+        .map(|style| style.abbreviation.as_ref().filter(|_| abbreviate).unwrap_or(&style.name).clone())
+        .unwrap_or_default();
+    if let Some(index) = index {
+        if !label.is_empty() {
+            label.push(' ');
+        }
+        label.push_str(index);
+    }
+    match &class.style {
+        Some(style) => html.extend_from_slice(format!(r#"<section class="article {}-style""#, escape_html(style)).as_bytes()),
+        None => html.extend_from_slice(b"<section class=\"article\""),
+    }
+    if anchor {
+        html.extend_from_slice(format!(r#" id="{}""#, escape_html(&article_anchor(&article.key))).as_bytes());
+    }
+    html.extend_from_slice(format!("><h{level}>").as_bytes());
+    if !label.is_empty() {
+        html.extend_from_slice(format!(r#"<span class="style">{}</span> "#, escape_html(&label)).as_bytes());
+    }
+    make_long_name(html, &article.names[0]);
+    html.extend_from_slice(format!("</h{level}>").as_bytes());
+    generate_article_content(html, article.content.as_slice(), &article.key, locale, "");
+    html.extend_from_slice(b"</section>");
+}
+
+/// Replace the reference placeholders in HTML with links to the inlined
+/// articles, relative to the page at `page_path`. References to articles that
+/// no page inlines keep only their label.
+fn resolve_static_references(
+    html: &str, articles: &Articles, resolution_paths: &[String], locations: &HashMap<Rc<str>, String>, page_path: &str, extension: &str,
+) -> String {
+    let mut resolved = String::with_capacity(html.len());
+    let mut offset = 0;
+    for reference in find_references(html) {
+        resolved.push_str(&html[offset..reference.start]);
+        offset = reference.end;
+        let article = match referenced_article(articles, &reference.key, resolution_paths) {
+            Some(article) => article,
+            None => { // Dangling references are reported when validating, keep the label.
+                resolved.push_str(reference.label);
+                continue;
+            }
+        };
+        let article = article.borrow();
+        let location = locations.get(&article.key);
+        if let Some(location) = location {
+            let url = relative_url(page_path, location);
+            let url = format!("{}{}", url.trim_end_matches(".html"), extension);
+            resolved.push_str(&format!(r#"<a class="ref" href="{}#{}">"#, escape_html(&url), escape_html(&article_anchor(&article.key))));
+        }
+        if reference.label.is_empty() {
+            resolved.push_str(&reference_name(&article));
+        } else {
+            resolved.push_str(reference.label);
+        }
+        if location.is_some() {
+            resolved.push_str("</a>");
+        }
+    }
+    resolved.push_str(&html[offset..]);
+    resolved
+}
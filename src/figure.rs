@@ -0,0 +1,13 @@
+use std::path::PathBuf;
+use crate::markup::Markup;
+
+/// An image in article or document content.
+#[derive(Clone)]
+pub struct Figure {
+    /// Path of the image file in the project.
+    pub(crate) source: PathBuf,
+    /// Content-hashed file name of the image in the website assets.
+    pub(crate) file_name: String,
+    pub(crate) alt: String,
+    pub(crate) caption: Option<Markup>,
+}
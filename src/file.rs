@@ -2,6 +2,7 @@ use std::fs;
 use std::fs::File;
 use std::io::Read;
 use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
 use khi::parse::parse::{parse_dictionary_str, parse_list_str};
 use khi::parse::parse::parser::{error_to_string};
 use khi::parse::pdm::{ParsedDictionary, ParsedList};
@@ -61,6 +62,38 @@ pub fn read_file_content_to_list(path: &Path, file_type: &str, content: &str) ->
     }
 }
 
+/// Get the modification time of a file.
+pub fn modification_time(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path).ok()?.modified().ok()
+}
+
+/// Get the creation time of a file, if the file system records it.
+pub fn creation_time(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path).ok()?.created().ok()
+}
+
+/// Format a time as an RFC 3339 timestamp in UTC, as in 2024-07-31T12:00:00Z.
+pub fn format_timestamp(time: SystemTime) -> String {
+    let seconds = time.duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0);
+    let (days, seconds) = (seconds / 86400, seconds % 86400);
+    // Convert days since the epoch to a civil date.
+    let z = days as i64 + 719468;
+    let era = z.div_euclid(146097);
+    let doe = z - era * 146097;
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+    format!("{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z", year, month, day, seconds / 3600, seconds / 60 % 60, seconds % 60)
+}
+
+/// Format a time as a date in UTC, as in 2024-07-31.
+pub fn format_date(time: SystemTime) -> String {
+    format_timestamp(time)[..10].to_string()
+}
+
 /// Update the modification times of the files.
 ///
 /// If a file is identical to a previous version, set the modification time to
@@ -84,3 +117,44 @@ fn is_file_identical(path: &Path, content: &str) -> Result<bool, String> {
         Ok(false)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn at(seconds: u64) -> SystemTime {
+        UNIX_EPOCH + Duration::from_secs(seconds)
+    }
+
+    #[test]
+    fn epoch_and_times_of_day() {
+        assert_eq!(format_timestamp(UNIX_EPOCH), "1970-01-01T00:00:00Z");
+        assert_eq!(format_timestamp(at(86399)), "1970-01-01T23:59:59Z");
+        assert_eq!(format_timestamp(at(86400)), "1970-01-02T00:00:00Z");
+        assert_eq!(format_timestamp(at(1722436200)), "2024-07-31T14:30:00Z");
+    }
+
+    #[test]
+    fn leap_years() {
+        assert_eq!(format_date(at(951782400)), "2000-02-29");
+        assert_eq!(format_date(at(951868800)), "2000-03-01");
+        assert_eq!(format_date(at(1709164800)), "2024-02-29");
+        assert_eq!(format_date(at(1709251200)), "2024-03-01");
+        assert_eq!(format_date(at(4107542400)), "2100-03-01"); // 2100 is not a leap year.
+        assert_eq!(format_date(at(4107456000)), "2100-02-28");
+    }
+
+    #[test]
+    fn year_boundaries() {
+        assert_eq!(format_timestamp(at(946684799)), "1999-12-31T23:59:59Z");
+        assert_eq!(format_timestamp(at(946684800)), "2000-01-01T00:00:00Z");
+        assert_eq!(format_date(at(1735603200)), "2024-12-31");
+    }
+
+    #[test]
+    fn times_before_the_epoch_are_clamped() {
+        assert_eq!(format_timestamp(UNIX_EPOCH - Duration::from_secs(1)), "1970-01-01T00:00:00Z");
+    }
+
+}
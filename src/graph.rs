@@ -0,0 +1,250 @@
+//! Graph of the classes, articles and documents of a project.
+
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+use crate::article::{Article, Articles};
+use crate::colour::parse_css_colour;
+use crate::document::{Document, DocumentElement, PanelElement};
+use crate::markup::strip_markup;
+use crate::name::NameElement;
+use crate::relation::RelationClass;
+use crate::style::Styles;
+
+pub struct Graph {
+    pub(crate) nodes: Vec<Node>,
+    pub(crate) edges: Vec<Edge>,
+}
+
+pub struct Node {
+    /// Identifier prefixed by the node type, as in `class:group`.
+    pub(crate) id: String,
+    pub(crate) kind: NodeKind,
+    pub(crate) key: String,
+    pub(crate) label: String,
+    /// Key of the class style.
+    pub(crate) style: Option<String>,
+    /// Style colour in hexadecimal notation, if it is an sRGB colour.
+    pub(crate) colour: Option<String>,
+}
+
+#[derive(Copy, Clone, Eq, PartialEq)]
+pub enum NodeKind {
+    Class,
+    Article,
+    Document,
+}
+
+impl NodeKind {
+    pub fn name(&self) -> &'static str {
+        match self {
+            NodeKind::Class => "class",
+            NodeKind::Article => "article",
+            NodeKind::Document => "document",
+        }
+    }
+}
+
+pub struct Edge {
+    pub(crate) source: String,
+    pub(crate) target: String,
+    pub(crate) kind: EdgeKind,
+}
+
+/// Type of an edge.
+#[derive(Copy, Clone, Eq, PartialEq, Hash)]
+pub enum EdgeKind {
+    /// From an article to its class.
+    Member,
+    /// From a class to a class it is, by a relation of the class.
+    Relation,
+    /// From an article to a class that one of its names takes as parameter.
+    Parameter,
+    /// From a document to an article or class in one of its panels.
+    Panel,
+    /// From a document or article to a class that it refers to.
+    Backlink,
+}
+
+impl EdgeKind {
+
+    pub const ALL: [EdgeKind; 5] = [EdgeKind::Member, EdgeKind::Relation, EdgeKind::Parameter, EdgeKind::Panel, EdgeKind::Backlink];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            EdgeKind::Member => "member",
+            EdgeKind::Relation => "relation",
+            EdgeKind::Parameter => "parameter",
+            EdgeKind::Panel => "panel",
+            EdgeKind::Backlink => "backlink",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Result<Self, String> {
+        match EdgeKind::ALL.iter().find(|kind| kind.name() == name) {
+            Some(kind) => Ok(*kind),
+            None => {
+                let names: Vec<&str> = EdgeKind::ALL.iter().map(|kind| kind.name()).collect();
+                Err(format!("Unknown relation type {}. Expected one of {}.", name, names.join(", ")))
+            }
+        }
+    }
+
+}
+
+/// Restriction of a graph.
+#[derive(Default, Eq, PartialEq)]
+pub struct GraphFilter {
+    /// Keep only the documents in this directory of `src`, and the articles and
+    /// classes in their panels.
+    pub(crate) dir: Option<PathBuf>,
+    /// Keep only edges of these types, and the nodes they connect.
+    pub(crate) kinds: Option<Vec<EdgeKind>>,
+}
+
+impl GraphFilter {
+    pub fn is_empty(&self) -> bool {
+        self.dir.is_none() && self.kinds.is_none()
+    }
+}
+
+/// Build the graph of a project.
+pub fn build_graph(styles: &Styles, articles: &Articles, documents: &[Rc<Document>], resolution_paths: &[String], filter: &GraphFilter) -> Graph {
+    let mut graph = Graph { nodes: vec![], edges: vec![] };
+    let mut edges = HashSet::new();
+    let mut add_edge = |graph: &mut Graph, source: String, target: String, kind: EdgeKind| {
+        if edges.insert((source.clone(), target.clone(), kind)) {
+            graph.edges.push(Edge { source, target, kind });
+        }
+    };
+    let mut class_keys: Vec<_> = articles.get_classes().keys().collect();
+    class_keys.sort();
+    for key in class_keys {
+        let class = articles.get_class(key).unwrap().borrow();
+        let (style, colour) = node_style(styles, class.style.as_deref());
+        let label = if class.articles.is_empty() {
+            key.to_string()
+        } else {
+            article_name(&class.resolve(resolution_paths).borrow())
+        };
+        graph.nodes.push(Node { id: class_id(key), kind: NodeKind::Class, key: key.to_string(), label, style, colour });
+        for relation in class.relations.iter() {
+            let (left, right) = (relation_class_key(&relation.left), relation_class_key(&relation.right));
+            if articles.get_class(left).is_some() && articles.get_class(right).is_some() {
+                add_edge(&mut graph, class_id(left), class_id(right), EdgeKind::Relation);
+            }
+        }
+        for document in class.backlinks.documents.iter().filter_map(|document| document.upgrade()) {
+            add_edge(&mut graph, document_id(&document.key), class_id(key), EdgeKind::Backlink);
+        }
+        for article in class.backlinks.articles.iter() {
+            add_edge(&mut graph, article_id(article), class_id(key), EdgeKind::Backlink);
+        }
+    }
+    let mut article_keys: Vec<_> = articles.get_articles().keys().collect();
+    article_keys.sort();
+    for key in article_keys {
+        let article = articles.get_article(key).unwrap().borrow();
+        let class = article.get_class();
+        let class = class.borrow();
+        let (style, colour) = node_style(styles, class.style.as_deref());
+        graph.nodes.push(Node { id: article_id(key), kind: NodeKind::Article, key: key.to_string(), label: article_name(&article), style, colour });
+        add_edge(&mut graph, article_id(key), class_id(&class.key), EdgeKind::Member);
+        for name in article.names.iter() {
+            for element in name {
+                if let NameElement::Parameter { class, .. } = element {
+                    add_edge(&mut graph, article_id(key), class_id(class), EdgeKind::Parameter);
+                }
+            }
+        }
+    }
+    for document in documents {
+        graph.nodes.push(Node {
+            id: document_id(&document.key), kind: NodeKind::Document, key: document.key.clone(), label: document.title.clone(), style: None, colour: None,
+        });
+        for element in &document.structure {
+            if let DocumentElement::Panel(elements) = element {
+                for element in elements {
+                    match element {
+                        PanelElement::ArticleLink { key, .. } => add_edge(&mut graph, document_id(&document.key), article_id(key), EdgeKind::Panel),
+                        PanelElement::ClassLink { key, .. } => add_edge(&mut graph, document_id(&document.key), class_id(key), EdgeKind::Panel),
+                        PanelElement::Heading { .. } => {}
+                    }
+                }
+            }
+        }
+    }
+    let ids: HashSet<String> = graph.nodes.iter().map(|node| node.id.clone()).collect();
+    graph.edges.retain(|edge| ids.contains(&edge.source) && ids.contains(&edge.target));
+    if let Some(dir) = &filter.dir {
+        let dir = Path::new("src").join(dir);
+        let mut kept = HashSet::new();
+        for document in documents.iter().filter(|document| document.source_path().starts_with(&dir)) {
+            kept.insert(document_id(&document.key));
+            for element in &document.structure {
+                if let DocumentElement::Panel(elements) = element {
+                    for element in elements {
+                        match element {
+                            PanelElement::ArticleLink { key, .. } => {
+                                kept.insert(article_id(key));
+                                kept.insert(class_id(&articles.get_article(key).unwrap().borrow().get_class().borrow().key));
+                            }
+                            PanelElement::ClassLink { key, .. } => {
+                                kept.insert(class_id(key));
+                            }
+                            PanelElement::Heading { .. } => {}
+                        }
+                    }
+                }
+            }
+        }
+        graph.nodes.retain(|node| kept.contains(&node.id));
+        graph.edges.retain(|edge| kept.contains(&edge.source) && kept.contains(&edge.target));
+    }
+    if let Some(kinds) = &filter.kinds {
+        graph.edges.retain(|edge| kinds.contains(&edge.kind));
+        let connected: HashSet<String> = graph.edges.iter().flat_map(|edge| [edge.source.clone(), edge.target.clone()]).collect();
+        graph.nodes.retain(|node| connected.contains(&node.id));
+    }
+    graph
+}
+
+/// Style key and hexadecimal colour of a node.
+fn node_style(styles: &Styles, style: Option<&str>) -> (Option<String>, Option<String>) {
+    let colour = style
+        .and_then(|style| styles.get(style))
+        .and_then(|style| style.colour.as_ref())
+        .and_then(|colour| parse_css_colour(colour).ok().flatten())
+        .map(|rgb| rgb.hex());
+    (style.map(|style| style.to_string()), colour)
+}
+
+/// Primary name of an article as plain text.
+pub(crate) fn article_name(article: &Article) -> String {
+    let mut name = String::new();
+    for element in article.names.first().unwrap().iter() {
+        if let NameElement::Name(markup) = element {
+            name.push_str(&strip_markup(&markup.0));
+        }
+    }
+    name.trim().to_string()
+}
+
+fn relation_class_key(class: &RelationClass) -> &str {
+    match class {
+        RelationClass::Name(name) => name,
+        RelationClass::Qual { name, .. } => name,
+    }
+}
+
+pub(crate) fn class_id(key: &str) -> String {
+    format!("class:{}", key)
+}
+
+pub(crate) fn article_id(key: &str) -> String {
+    format!("article:{}", key)
+}
+
+pub(crate) fn document_id(key: &str) -> String {
+    format!("document:{}", key)
+}
@@ -0,0 +1,257 @@
+//! Build-time syntax highlighting of code blocks.
+//!
+//! Code is split into tokens which are wrapped in classed `<span>` elements. The
+//! class names follow the highlight.js names used by the bundled
+//! `highlight-style/tomorrow-night.css`: keyword, built_in, literal, string,
+//! number, comment and function.
+
+use crate::markup::escape_html;
+
+/// Lexical description of a language.
+pub struct Language {
+    names: &'static [&'static str],
+    keywords: &'static [&'static str],
+    built_ins: &'static [&'static str],
+    literals: &'static [&'static str],
+    line_comment: Option<&'static str>,
+    block_comment: Option<(&'static str, &'static str)>,
+    string_delimiters: &'static [char],
+}
+
+static LANGUAGES: [Language; 6] = [
+    Language {
+        names: &["rust", "rs"],
+        keywords: &[
+            "as", "async", "await", "break", "const", "continue", "crate", "dyn", "else", "enum", "extern",
+            "fn", "for", "if", "impl", "in", "let", "loop", "match", "mod", "move", "mut", "pub", "ref",
+            "return", "self", "Self", "static", "struct", "super", "trait", "type", "unsafe", "use",
+            "where", "while",
+        ],
+        built_ins: &[
+            "bool", "char", "str", "u8", "u16", "u32", "u64", "u128", "usize", "i8", "i16", "i32", "i64",
+            "i128", "isize", "f32", "f64", "String", "Vec", "Box", "Rc", "Option", "Result", "Some",
+            "None", "Ok", "Err",
+        ],
+        literals: &["true", "false"],
+        line_comment: Some("//"),
+        block_comment: Some(("/*", "*/")),
+        string_delimiters: &['"'],
+    },
+    Language {
+        names: &["python", "py"],
+        keywords: &[
+            "and", "as", "assert", "async", "await", "break", "class", "continue", "def", "del", "elif",
+            "else", "except", "finally", "for", "from", "global", "if", "import", "in", "is", "lambda",
+            "nonlocal", "not", "or", "pass", "raise", "return", "try", "while", "with", "yield",
+        ],
+        built_ins: &[
+            "print", "len", "range", "int", "float", "str", "list", "dict", "set", "tuple", "bool",
+            "enumerate", "zip", "map", "filter", "open", "self",
+        ],
+        literals: &["True", "False", "None"],
+        line_comment: Some("#"),
+        block_comment: None,
+        string_delimiters: &['"', '\''],
+    },
+    Language {
+        names: &["javascript", "js"],
+        keywords: &[
+            "async", "await", "break", "case", "catch", "class", "const", "continue", "default",
+            "delete", "do", "else", "export", "extends", "finally", "for", "function", "if", "import",
+            "in", "instanceof", "let", "new", "of", "return", "static", "switch", "this", "throw", "try",
+            "typeof", "var", "void", "while", "yield",
+        ],
+        built_ins: &[
+            "Array", "Object", "String", "Number", "Boolean", "Map", "Set", "Promise", "JSON", "Math",
+            "console", "document", "window",
+        ],
+        literals: &["true", "false", "null", "undefined", "NaN"],
+        line_comment: Some("//"),
+        block_comment: Some(("/*", "*/")),
+        string_delimiters: &['"', '\'', '`'],
+    },
+    Language {
+        names: &["c", "cpp", "c++", "h"],
+        keywords: &[
+            "auto", "break", "case", "class", "const", "continue", "default", "delete", "do", "else",
+            "enum", "extern", "for", "goto", "if", "inline", "namespace", "new", "private", "protected",
+            "public", "return", "sizeof", "static", "struct", "switch", "template", "this", "typedef",
+            "union", "using", "virtual", "volatile", "while",
+        ],
+        built_ins: &[
+            "char", "double", "float", "int", "long", "short", "signed", "unsigned", "void", "bool",
+            "size_t", "std",
+        ],
+        literals: &["true", "false", "NULL", "nullptr"],
+        line_comment: Some("//"),
+        block_comment: Some(("/*", "*/")),
+        string_delimiters: &['"', '\''],
+    },
+    Language {
+        names: &["java"],
+        keywords: &[
+            "abstract", "break", "case", "catch", "class", "continue", "default", "do", "else", "enum",
+            "extends", "final", "finally", "for", "if", "implements", "import", "instanceof",
+            "interface", "new", "package", "private", "protected", "public", "return", "static",
+            "super", "switch", "synchronized", "this", "throw", "throws", "try", "var", "while",
+        ],
+        built_ins: &[
+            "boolean", "byte", "char", "double", "float", "int", "long", "short", "void", "String",
+            "Object", "Integer", "List", "Map", "System",
+        ],
+        literals: &["true", "false", "null"],
+        line_comment: Some("//"),
+        block_comment: Some(("/*", "*/")),
+        string_delimiters: &['"', '\''],
+    },
+    Language {
+        names: &["bash", "sh", "shell"],
+        keywords: &[
+            "if", "then", "else", "elif", "fi", "for", "while", "until", "do", "done", "case", "esac",
+            "in", "function", "return", "local", "export",
+        ],
+        built_ins: &["echo", "cd", "pwd", "read", "set", "unset", "source", "exit", "test", "printf"],
+        literals: &["true", "false"],
+        line_comment: Some("#"),
+        block_comment: None,
+        string_delimiters: &['"', '\''],
+    },
+];
+
+/// Find a supported language by one of its names.
+pub fn find_language(name: &str) -> Option<&'static Language> {
+    let name = name.to_ascii_lowercase();
+    LANGUAGES.iter().find(|language| language.names.contains(&name.as_str()))
+}
+
+/// Names of all supported languages.
+pub fn language_names() -> impl Iterator<Item = &'static str> {
+    LANGUAGES.iter().flat_map(|language| language.names.iter().copied())
+}
+
+/// Highlight code into escaped HTML with classed spans.
+pub fn highlight(language: &Language, code: &str) -> String {
+    let mut html = String::new();
+    let mut rest = code;
+    while let Some(c) = rest.chars().next() {
+        if let Some(line_comment) = language.line_comment {
+            if rest.starts_with(line_comment) {
+                let end = rest.find('\n').unwrap_or(rest.len());
+                push_token(&mut html, "comment", &rest[..end]);
+                rest = &rest[end..];
+                continue;
+            }
+        }
+        if let Some((open, close)) = language.block_comment {
+            if rest.starts_with(open) {
+                let end = match rest[open.len()..].find(close) {
+                    Some(i) => open.len() + i + close.len(),
+                    None => rest.len(),
+                };
+                push_token(&mut html, "comment", &rest[..end]);
+                rest = &rest[end..];
+                continue;
+            }
+        }
+        if language.string_delimiters.contains(&c) {
+            let end = string_end(rest, c);
+            push_token(&mut html, "string", &rest[..end]);
+            rest = &rest[end..];
+        } else if c.is_ascii_digit() {
+            let end = rest.find(|c: char| !(c.is_ascii_alphanumeric() || c == '.' || c == '_')).unwrap_or(rest.len());
+            push_token(&mut html, "number", &rest[..end]);
+            rest = &rest[end..];
+        } else if c.is_alphabetic() || c == '_' {
+            let end = rest.find(|c: char| !(c.is_alphanumeric() || c == '_')).unwrap_or(rest.len());
+            let word = &rest[..end];
+            rest = &rest[end..];
+            if language.keywords.contains(&word) {
+                push_token(&mut html, "keyword", word);
+            } else if language.literals.contains(&word) {
+                push_token(&mut html, "literal", word);
+            } else if language.built_ins.contains(&word) {
+                push_token(&mut html, "built_in", word);
+            } else if rest.starts_with('(') {
+                push_token(&mut html, "function", word);
+            } else {
+                html.push_str(&escape_html(word));
+            }
+        } else {
+            html.push_str(&escape_html(&rest[..c.len_utf8()]));
+            rest = &rest[c.len_utf8()..];
+        }
+    }
+    html
+}
+
+/// Find the byte length of a string literal starting at the start of `code`.
+fn string_end(code: &str, delimiter: char) -> usize {
+    let mut escaped = false;
+    for (i, c) in code.char_indices().skip(1) {
+        if escaped {
+            escaped = false;
+        } else if c == '\\' {
+            escaped = true;
+        } else if c == delimiter {
+            return i + c.len_utf8();
+        } else if c == '\n' && delimiter != '`' {
+            return i;
+        }
+    }
+    code.len()
+}
+
+fn push_token(html: &mut String, class: &str, token: &str) {
+    html.push_str(&format!(r#"<span class="{class}">{}</span>"#, escape_html(token)));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn highlight_as(name: &str, code: &str) -> String {
+        highlight(find_language(name).unwrap(), code)
+    }
+
+    #[test]
+    fn finds_languages_by_any_name() {
+        assert!(find_language("Rust").is_some());
+        assert!(find_language("rs").is_some());
+        assert!(find_language("sh").is_some());
+        assert!(find_language("cobol").is_none());
+        assert!(language_names().any(|name| name == "bash"));
+    }
+
+    #[test]
+    fn classifies_words() {
+        assert_eq!(
+            highlight_as("rust", "let x: Option<u8> = parse(true);"),
+            concat!(
+                r#"<span class="keyword">let</span> x: <span class="built_in">Option</span>&lt;<span class="built_in">u8</span>&gt; = "#,
+                r#"<span class="function">parse</span>(<span class="literal">true</span>);"#,
+            ),
+        );
+    }
+
+    #[test]
+    fn numbers_strings_and_comments() {
+        assert_eq!(highlight_as("rust", "1_000u32"), r#"<span class="number">1_000u32</span>"#);
+        assert_eq!(highlight_as("rust", r#""a \" b" x"#), r#"<span class="string">&quot;a \&quot; b&quot;</span> x"#);
+        assert_eq!(highlight_as("rust", "x // y\nz"), "x <span class=\"comment\">// y</span>\nz");
+        assert_eq!(highlight_as("rust", "/* a\nb */c"), "<span class=\"comment\">/* a\nb */</span>c");
+        assert_eq!(highlight_as("bash", "echo 'hi' # done"), r#"<span class="built_in">echo</span> <span class="string">'hi'</span> <span class="comment"># done</span>"#);
+    }
+
+    #[test]
+    fn unterminated_tokens_end_at_line_or_input_end() {
+        assert_eq!(highlight_as("rust", "\"open\nx"), "<span class=\"string\">&quot;open</span>\nx");
+        assert_eq!(highlight_as("rust", "/* open"), r#"<span class="comment">/* open</span>"#);
+    }
+
+    #[test]
+    fn escapes_html() {
+        assert_eq!(highlight_as("rust", "a<b && c>d"), "a&lt;b &amp;&amp; c&gt;d");
+        assert_eq!(highlight_as("rust", "\"<&>\""), r#"<span class="string">&quot;&lt;&amp;&gt;&quot;</span>"#);
+    }
+
+}
@@ -102,3 +102,33 @@ impl<'a> KeyReader<'a> {
     }
 
 }
+
+/// A key that links to a class or to an article.
+pub(crate) enum LinkKey {
+    Class(String), Article(String)
+}
+
+/// Read the key in an include element. This key can be a class key or an
+/// article key.
+pub(crate) fn read_include_key(document_key: &str, key: &str) -> Result<LinkKey, String> {
+    let mut reader = KeyReader::new(key);
+    if reader.is_plain_key() {
+        let (key, article) = reader.parse_plain()?;
+        if !reader.is_at_end() {
+            return Err(format!("Expected end in key."));
+        }
+        if article {
+            Ok(LinkKey::Article(key))
+        } else {
+            Ok(LinkKey::Class(key))
+        }
+    } else if reader.is_parenthesized() {
+        let key = reader.parse_parenthesized()?;
+        if !reader.is_at_end() {
+            return Err(format!("Expected end in key."));
+        }
+        Ok(LinkKey::Article(format!("{}@{}", &key, document_key)))
+    } else {
+        return Err(format!("Invalid declaration key."));
+    }
+}
@@ -0,0 +1,20 @@
+use std::collections::{BTreeMap, HashMap};
+
+/// Locales by language code.
+pub type Locales = HashMap<String, Locale>;
+
+/// User interface strings of the website in one language.
+#[derive(Clone)]
+pub struct Locale {
+    pub(crate) language: String,
+    pub(crate) strings: BTreeMap<String, String>,
+}
+
+impl Locale {
+
+    /// Get a string, or the key itself if the locale does not define it.
+    pub fn get<'a>(&'a self, key: &'a str) -> &'a str {
+        self.strings.get(key).map(|s| s.as_str()).unwrap_or(key)
+    }
+
+}
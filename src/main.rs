@@ -14,6 +14,15 @@ pub mod relation;
 mod types;
 mod preprocess_markup;
 pub mod dir;
+mod highlight;
+mod table;
+mod figure;
+mod bibliography;
+mod colour;
+mod locale;
+mod export;
+mod graph;
+mod mathml;
 
 use std::{env, fs};
 use std::ffi::OsString;
@@ -24,21 +33,59 @@ use khi::parse::pdm::{ParsedDictionary, ParsedTaggedTuple, ParsedValue};
 use zeroarg::{parse_arguments, Argument};
 use crate::article::Articles;
 use crate::compile::config::read_configuration_files;
+use crate::compile::locale::read_default_locales;
 use crate::compile::document::read_source_dir;
 use crate::compile::project::{read_project_file, ProjectSettings};
-use crate::compile::style::read_style_file;
+use crate::compile::backlink::link_backlinks;
+use crate::compile::bibliography::read_bibliography_files;
+use crate::compile::reference::validate_references;
+use crate::compile::style::{read_style_file, validate_style_references};
 use crate::compile::template::Templates;
 use crate::document::Documents;
+use crate::graph::{build_graph, EdgeKind, GraphFilter};
+use crate::export::anki::write_anki_decks;
+use crate::export::graph::write_graph_files;
+use crate::export::latex::{write_latex_book, LatexBook};
+use crate::export::markdown::write_markdown_pages;
+use crate::export::html::write_html_pages;
+use crate::export::epub::write_epub_book;
+use crate::locale::Locales;
 use crate::makro::Macros;
 use crate::style::Styles;
-use crate::web::asset::{include_assets, include_static_assets};
+use crate::web::asset::{include_assets, include_static_assets, include_style_symbols, include_vendored_assets};
 use crate::web::class::write_class_directory;
+use crate::web::citation::write_bibliography_page;
+use crate::web::sitemap::write_sitemap;
+use crate::web::feed::write_feed;
+use crate::web::graph::write_graph_page;
+use crate::web::search::write_search_index;
 use crate::web::class_style::{write_class_style_css_file, write_class_style_json_file};
 use crate::web::dirpage::write_dir_indexes;
 use crate::web::document::write_documents;
+use crate::web::{base_path, include_index_and_icon, SiteSettings};
+use crate::web::index::write_index;
+use crate::web::template::read_page_templates;
 
 type Html = String;
 
+/// What to produce from a project.
+#[derive(Eq, PartialEq)]
+pub enum Target {
+    Website,
+    /// A LaTeX book in the latex directory.
+    Latex,
+    /// Anki decks in the anki directory.
+    Anki,
+    /// Markdown files of the documents in the markdown directory.
+    Markdown,
+    /// Static HTML files of the documents in the html directory.
+    Html,
+    /// An EPUB book of the documents in the epub directory.
+    Epub,
+    /// DOT and GraphML files of the project graph in the graph directory.
+    Graph(GraphFilter),
+}
+
 fn main() {
     let mut args = env::args();
     args.next();
@@ -50,15 +97,50 @@ fn main() {
         }
     };
     let mut help = false;
+    let mut target = None;
+    let mut graph_filter = GraphFilter::default();
     for argument in arguments {
         match argument {
             Argument::Operand(operand) => {
-                eprintln!("Error: Operand {} not supported", &operand);
-                return;
+                if target.is_some() {
+                    eprintln!("Error: Only one command is supported");
+                    return;
+                } else if operand == "latex" {
+                    target = Some(Target::Latex);
+                } else if operand == "anki" {
+                    target = Some(Target::Anki);
+                } else if operand == "markdown" {
+                    target = Some(Target::Markdown);
+                } else if operand == "html" {
+                    target = Some(Target::Html);
+                } else if operand == "epub" {
+                    target = Some(Target::Epub);
+                } else if operand == "graph" {
+                    target = Some(Target::Graph(GraphFilter::default()));
+                } else {
+                    eprintln!("Error: Operand {} not supported", &operand);
+                    return;
+                }
             }
-            Argument::Attribute(k, _) => {
-                eprintln!("Error: Attribute {} not supported", &k);
-                return;
+            Argument::Attribute(k, v) => {
+                if k == "dir" {
+                    graph_filter.dir = Some(PathBuf::from(v));
+                } else if k == "relation" {
+                    let mut kinds = vec![];
+                    for name in v.split(',') {
+                        match EdgeKind::from_name(name.trim()) {
+                            Ok(kind) => kinds.push(kind),
+                            Err(e) => {
+                                eprintln!("Error: {}", e);
+                                return;
+                            }
+                        }
+                    }
+                    graph_filter.kinds = Some(kinds);
+                } else {
+                    eprintln!("Error: Attribute {} not supported", &k);
+                    return;
+                }
             }
             Argument::Flag(flag) => {
                 if flag == "h" || flag == "help" || flag == "?" {
@@ -71,12 +153,21 @@ fn main() {
         }
     }
     if help {
-        eprintln!("lexikon [-h]\n\nProcess project in current directory with command \"lexikon\".");
+        eprintln!("lexikon [-h] [latex|anki|markdown|html|epub|graph [--dir=<dir>] [--relation=<types>]]\n\nProcess project in current directory with command \"lexikon\".\nExport it as a LaTeX book with command \"lexikon latex\".\nExport its articles as Anki decks with command \"lexikon anki\".\nExport its documents as Markdown with command \"lexikon markdown\".\nExport its documents as static HTML pages with command \"lexikon html\".\nExport its documents as an EPUB book with command \"lexikon epub\".\nExport its graph as DOT and GraphML with command \"lexikon graph\".");
         return;
     }
     let root_dir_path = env::current_dir().unwrap();
     eprintln!("Processing project at {}.", root_dir_path.to_str().unwrap());
-    match compile() {
+    let target = match target {
+        Some(Target::Graph(_)) => Target::Graph(graph_filter),
+        Some(target) if graph_filter.is_empty() => target,
+        None if graph_filter.is_empty() => Target::Website,
+        _ => {
+            eprintln!("Error: The attributes dir and relation are only supported by the graph command");
+            return;
+        }
+    };
+    match compile(target) {
         Ok(_) => {
             eprintln!("Project compiled successfully.")
         }
@@ -87,23 +178,60 @@ fn main() {
 }
 
 /// Compile the project.
-pub fn compile() -> Result<(), String> {
+pub fn compile(target: Target) -> Result<(), String> {
     // Read project file.
-    let ProjectSettings { resolution_paths, style_path, config_paths, dependencies } = read_project_file("project.khi".as_ref())?;
+    let ProjectSettings { resolution_paths, style_path, config_paths, dependencies, vendor_path, margin_notes, bibliography_paths, citation_style, title, description, front_page, base_url, language, theme, stylesheet_paths } = read_project_file("project.khi".as_ref())?;
     // Read configuration files and class style file.
     let mut macros = Macros::new();
     let mut templates = Templates::new();
-    read_configuration_files(&mut templates, &mut macros, &config_paths)?;
+    let mut locales = Locales::new();
+    read_default_locales(&mut locales)?;
+    read_configuration_files(&mut templates, &mut macros, &mut locales, &config_paths)?;
+    let locale = match locales.remove(&language) {
+        Some(locale) => locale,
+        None => return Err(format!("No locale for language {}. Add a .locale.khi file with Language: {} to the configuration.", language, language)),
+    };
     let styles = if let Some(style_path) = style_path {
         read_style_file(style_path.as_ref())?
     } else {
         Styles::new()
     };
+    let bibliography = read_bibliography_files(&bibliography_paths, citation_style)?;
     // Read document source directory.
     let mut articles = Articles::new();
     let mut documents = Documents::new();
-    let tree = read_source_dir(&templates, &resolution_paths, &macros, &mut articles, &mut documents, Path::new("src"), OsString::from("src"))?;
+    let tree = read_source_dir(&templates, &resolution_paths, &macros, &bibliography, &mut articles, &mut documents, Path::new("src"), OsString::from("src"))?;
     eprintln!("Complete. Articles: {} Classes: {} Documents: {}", articles.article_map.len(), articles.class_map.len(), documents.len()); ////////////////////////////////////////////
+    validate_references(&articles, &documents)?;
+    validate_style_references(&styles, &templates, &articles);
+    link_backlinks(&articles, &documents);
+    let page_templates = read_page_templates(Path::new("templates"), &locale, base_path(base_url.as_deref()))?;
+    let site = SiteSettings {
+        vendored: vendor_path.is_some(),
+        margin_notes,
+        title: title.unwrap_or_else(|| locale.get("Home").to_string()),
+        description,
+        base_url,
+        locale,
+        templates: page_templates,
+        theme,
+        stylesheets: stylesheet_paths.iter().map(|path| path.file_name().unwrap().to_str().unwrap().to_string()).collect(),
+    };
+    match target {
+        Target::Website => {}
+        Target::Latex => {
+            let book = LatexBook { title: &site.title, description: site.description.as_deref(), locale: &site.locale };
+            return write_latex_book(Path::new("latex"), &book, &styles, &macros, &articles, &documents, &resolution_paths);
+        }
+        Target::Anki => return write_anki_decks(Path::new("anki"), &site, &articles, &documents, &resolution_paths),
+        Target::Markdown => return write_markdown_pages(Path::new("markdown"), &site, &styles, &articles, &documents, &resolution_paths),
+        Target::Html => return write_html_pages(Path::new("html"), &site, &styles, &articles, &documents, &resolution_paths),
+        Target::Epub => return write_epub_book(Path::new("epub"), &site, &styles, &articles, &documents, &tree, &resolution_paths),
+        Target::Graph(filter) => {
+            let graph = build_graph(&styles, &articles, &documents, &resolution_paths, &filter);
+            return write_graph_files(Path::new("graph"), &graph);
+        }
+    }
     // Read dependencies.
 //    dependency::read_dependencies(&mut articles, &mut documents, read_project.dependencies.as_slice())?;//todo
     // Write website.
@@ -117,13 +245,25 @@ pub fn compile() -> Result<(), String> {
     // Write website files.
     write_class_style_json_file(temp_web_path, &styles)?;
     write_class_style_css_file(temp_web_path, &styles)?;
-    write_class_directory(temp_web_path, &articles)?;
-    write_dir_indexes(&styles, &resolution_paths, &articles, temp_web_path, &PathBuf::from("/"), &documents, &tree)?;
+    write_class_directory(&site, &styles, temp_web_path, &articles, &resolution_paths)?;
+    write_dir_indexes(&site, &styles, &resolution_paths, &articles, temp_web_path, &PathBuf::from("/"), &documents, &tree)?;
+    write_search_index(temp_web_path, &styles, &articles, &documents)?;
+    if !bibliography.is_empty() {
+        write_bibliography_page(&site, temp_web_path, &bibliography)?;
+    }
     //write_documents(&styles, &resolution_paths, &articles, temp_web_path, &documents)?; // Todo: merge write docs and dirs into write_tree
-    //write_index(Path::new(""), temp_path);
+    write_index(&site, front_page.as_ref(), &articles, &documents, &tree, Path::new(""), temp_web_path)?;
+    let graph = build_graph(&styles, &articles, &documents, &resolution_paths, &GraphFilter::default());
+    write_graph_page(&site, temp_web_path, &articles, &graph)?;
+    write_sitemap(&site, temp_web_path, &articles, &tree, !bibliography.is_empty())?;
+    write_feed(&site, temp_web_path, &articles, &documents)?;
     include_static_assets(temp_web_path)?;
-    include_assets(temp_web_path)?;
-//    include_index_and_icon(temp_path)?;
+    if let Some(vendor_path) = &vendor_path {
+        include_vendored_assets(temp_web_path, vendor_path)?;
+    }
+    include_assets(temp_web_path, &articles, &documents, &stylesheet_paths)?;
+    include_style_symbols(temp_web_path, &styles)?;
+    include_index_and_icon(Path::new(""), temp_web_path)?;
 //    carry_modification_dates(target_path, temp_path)?;
     // Replace the old target directory with the newly generated files.
      
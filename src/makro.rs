@@ -1,5 +1,6 @@
 use std::collections::HashMap;
 use khi::parse::pdm::{ParsedValue};
+use crate::bibliography::Bibliography;
 
 /// Todo: Currently only support for Math type macros, but should be expanded to several types.
 pub type Macros = HashMap<String, MathMacro>;
@@ -12,16 +13,27 @@ pub struct MathMacro {
 pub struct LocalMacroRegistry<'a> {
     project_macros: &'a Macros,
     document_macros: &'a Macros,
+    document_key: &'a str,
+    bibliography: &'a Bibliography,
 }
 
 impl<'a> LocalMacroRegistry<'a> {
-    pub fn new(project_macros: &'a HashMap<String, MathMacro>, document_macros: &'a HashMap<String, MathMacro>) -> Self {
-        Self { project_macros, document_macros }
+    pub fn new(project_macros: &'a HashMap<String, MathMacro>, document_macros: &'a HashMap<String, MathMacro>, document_key: &'a str, bibliography: &'a Bibliography) -> Self {
+        Self { project_macros, document_macros, document_key, bibliography }
     }
 }
 
 pub trait MacroMap {
     fn get(&self, key: &str) -> Option<&MathMacro>;
+    /// Key of the document whose markup is processed, if any. Local keys in
+    /// markup are relative to this document.
+    fn document_key(&self) -> Option<&str> {
+        None
+    }
+    /// Bibliography that citations in markup refer to, if any.
+    fn bibliography(&self) -> Option<&Bibliography> {
+        None
+    }
 }
 
 impl<'a> MacroMap for LocalMacroRegistry<'a> {
@@ -34,6 +46,12 @@ impl<'a> MacroMap for LocalMacroRegistry<'a> {
             None
         }
     }
+    fn document_key(&self) -> Option<&str> {
+        Some(self.document_key)
+    }
+    fn bibliography(&self) -> Option<&Bibliography> {
+        Some(self.bibliography)
+    }
 }
 
 impl MacroMap for HashMap<String, MathMacro> {
@@ -5,6 +5,8 @@ use crate::makro::{MacroMap};
 use crate::tex::{write_tex_with, BreakMode};
 use crate::{tex_error_to_text, tuple_split};
 use crate::preprocess_markup::{process_markup_level, process_unexpanded_markup};
+use crate::highlight::{find_language, highlight, language_names};
+use crate::key::{read_include_key, LinkKey};
 // TODO: Processing markup produces LaTeX/HTML, which should maybe be done in the web package. But this works for now since this is the only option.
 
 #[derive(Clone)]
@@ -20,10 +22,146 @@ impl Markup {
         Self(str.to_string())
     }
 
+    /// The markup as HTML, with its inline styles written as HTML elements.
+    pub fn to_html(&self) -> String {
+        render_inline_styles(&self.0)
+    }
+
+}
+
+/// Style of inline text. The style is independent of the output format: markup
+/// holds it as an element named by [InlineStyle::markup_element], which each
+/// backend renders in its own way.
+#[derive(Copy, Clone, Eq, PartialEq)]
+pub enum InlineStyle {
+    Emphasis,
+    Strong,
+    Subscript,
+    Superscript,
+    Strikethrough,
+    SmallCaps,
+    Quotation,
+    Keyboard,
+}
+
+impl InlineStyle {
+
+    /// Find the style of a markup command.
+    pub fn from_command(name: &str) -> Option<Self> {
+        match name {
+            "e" => Some(InlineStyle::Emphasis),
+            "b" => Some(InlineStyle::Strong),
+            "sub" => Some(InlineStyle::Subscript),
+            "sup" => Some(InlineStyle::Superscript),
+            "s" => Some(InlineStyle::Strikethrough),
+            "sc" => Some(InlineStyle::SmallCaps),
+            "q" => Some(InlineStyle::Quotation),
+            "kbd" => Some(InlineStyle::Keyboard),
+            _ => None,
+        }
+    }
+
+    /// Name of the element that holds text of this style in markup.
+    pub fn markup_element(&self) -> &'static str {
+        match self {
+            InlineStyle::Emphasis => "inline-emphasis",
+            InlineStyle::Strong => "inline-strong",
+            InlineStyle::Subscript => "inline-subscript",
+            InlineStyle::Superscript => "inline-superscript",
+            InlineStyle::Strikethrough => "inline-strikethrough",
+            InlineStyle::SmallCaps => "inline-small-caps",
+            InlineStyle::Quotation => "inline-quotation",
+            InlineStyle::Keyboard => "inline-keyboard",
+        }
+    }
+
+    /// Find the style of an element in markup.
+    pub fn from_markup_element(name: &str) -> Option<Self> {
+        [
+            InlineStyle::Emphasis, InlineStyle::Strong, InlineStyle::Subscript, InlineStyle::Superscript,
+            InlineStyle::Strikethrough, InlineStyle::SmallCaps, InlineStyle::Quotation, InlineStyle::Keyboard,
+        ].into_iter().find(|style| style.markup_element() == name)
+    }
+
+    /// The HTML element and class that render this style.
+    pub fn html_element(&self) -> (&'static str, Option<&'static str>) {
+        match self {
+            InlineStyle::Emphasis => ("em", None),
+            InlineStyle::Strong => ("strong", None),
+            InlineStyle::Subscript => ("sub", None),
+            InlineStyle::Superscript => ("sup", None),
+            InlineStyle::Strikethrough => ("s", None),
+            InlineStyle::SmallCaps => ("span", Some("small-caps")),
+            InlineStyle::Quotation => ("q", None),
+            InlineStyle::Keyboard => ("kbd", None),
+        }
+    }
+
+    /// Keyboard input is literal text, the other styles contain markup.
+    pub fn is_literal(&self) -> bool {
+        matches!(self, InlineStyle::Keyboard)
+    }
+
 }
 
 pub(crate) fn process_article_markup_text(output: &mut String, macros: &impl MacroMap, text: &ParsedText) -> Result<(), String> {
-    output.push_str(text.as_str());
+    write_text(output, text.as_str());
+    Ok(())
+}
+
+/// Write plain text into markup. Text is escaped, so `<` and `&` are never read
+/// as HTML. Raw HTML is written with `<raw!>`.
+fn write_text(output: &mut String, text: &str) {
+    output.push_str(&escape_html(text));
+}
+
+/// Write the inline style elements of markup as HTML elements.
+fn render_inline_styles(markup: &str) -> String {
+    let mut html = String::with_capacity(markup.len());
+    let mut rest = markup;
+    while let Some(i) = rest.find('<') {
+        html.push_str(&rest[..i]);
+        rest = &rest[i..];
+        let closing = rest.starts_with("</");
+        let name_start = if closing { 2 } else { 1 };
+        let style = rest.find('>').and_then(|end| InlineStyle::from_markup_element(&rest[name_start..end]).map(|style| (style, end)));
+        match style {
+            Some((style, end)) => {
+                let (element, class) = style.html_element();
+                match class {
+                    _ if closing => html.push_str(&format!("</{element}>")),
+                    Some(class) => html.push_str(&format!(r#"<{element} class="{class}">"#)),
+                    None => html.push_str(&format!("<{element}>")),
+                }
+                rest = &rest[end + 1..];
+            }
+            None => {
+                html.push('<');
+                rest = &rest[1..];
+            }
+        }
+    }
+    html.push_str(rest);
+    html
+}
+
+/// Write a styled inline command.
+fn process_inline_style(output: &mut String, macros: &impl MacroMap, style: InlineStyle, name: &str, arguments: &[&ParsedValue], from: Position) -> Result<(), String> {
+    if arguments.len() != 1 {
+        return Err(format!("<{name}> command at {}:{} takes 1 argument.", from.line, from.column));
+    }
+    let argument = arguments[0];
+    let element = style.markup_element();
+    output.push_str(&format!("<{element}>"));
+    if style.is_literal() {
+        if !argument.is_text() {
+            return Err(format!("<{name}> command at {}:{} takes 1 text argument.", argument.from().line, argument.from().column));
+        }
+        output.push_str(&escape_html(argument.as_text().unwrap().as_str()));
+    } else {
+        process_markup_level(output, macros, argument)?;
+    }
+    output.push_str(&format!("</{element}>"));
     Ok(())
 }
 
@@ -83,33 +221,52 @@ pub(crate) fn process_article_markup_tag(output: &mut String, macros: &impl Macr
         output.push_str(&format!("<a href=\"{href}\">{}</a>", &label.0));
         Ok(())
     } else if name == "code" {
-        if poss.len() != 1 {
-            return Err(format!("<code> takes 1 text argument."));
-        }
-        let code = poss.get(0).unwrap();
+        let (language, code) = if poss.len() == 1 {
+            (find_code_attribute_language(tag), poss.get(0).unwrap())
+        } else if poss.len() == 2 {
+            let language = poss.get(0).unwrap();
+            if !language.is_text() {
+                return Err(format!("Code language at {}:{} must be text.", language.from().line, language.from().column))
+            }
+            (Some(language.as_text().unwrap().as_str()), poss.get(1).unwrap())
+        } else {
+            return Err(format!("<code> takes 1 text argument, optionally preceded by a language."));
+        };
         if !code.is_text() {
-            return Err(format!("Code at {}:{} must be text.", poss.get(0).unwrap().from().line, poss.get(0).unwrap().from().column))
+            return Err(format!("Code at {}:{} must be text.", code.from().line, code.from().column))
+        }
+        let code = code.as_text().unwrap().as_str();
+        if let Some(language) = language {
+            // Highlighted blocks are marked so that highlight.js does not process them again.
+            let code = if let Some(highlighting) = find_language(language) {
+                highlight(highlighting, code)
+            } else {
+                escape_html(code)
+            };
+            output.push_str(&format!(r#"<pre><code class="language-{}" data-highlighted="yes">{}</code></pre>"#, escape_html(language), code));
+        } else {
+            output.push_str(&format!("<pre><code>{}</code></pre>", escape_html(code)));
         }
-        let mut code = String::from(code.as_text().unwrap().as_str());
-        code = code.replace("&", "&amp;");
-        code = code.replace("<", "&lt;");
-        code = code.replace(">", "&gt;");
-        output.push_str(&format!("<pre><code>{}</code></pre>", code));
         Ok(())
     } else if name == "icode" {
         if poss.len() != 1 {
-            return Err(format!("<code> takes 1 text argument."));
+            return Err(format!("<icode> takes 1 text argument."));
         }
         let code = poss.get(0).unwrap();
         if !code.is_text() {
             return Err(format!("Code at {}:{} must be text.", poss.get(0).unwrap().from().line, poss.get(0).unwrap().from().column))
         }
-        let mut code = String::from(code.as_text().unwrap().as_str());
-        code = code.replace("&", "&amp;");
-        code = code.replace("<", "&lt;");
-        code = code.replace(">", "&gt;");
+        let code = escape_html(code.as_text().unwrap().as_str());
         output.push_str(&format!("<code>{}</code>", code));
         Ok(())
+    } else if let Some(style) = InlineStyle::from_command(name) {
+        process_inline_style(output, macros, style, name, poss.as_slice(), from)
+    } else if name == "ref" {
+        process_reference(output, macros, poss.as_slice(), from)
+    } else if name == "fn" {
+        process_footnote(output, macros, poss.as_slice(), from)
+    } else if name == "cite" {
+        process_citation(output, macros, poss.as_slice(), from)
     } else if name == "raw!" {
         if poss.len() != 1 {
             return Err(format!("<raw!> takes 1 text argument."));
@@ -125,8 +282,294 @@ pub(crate) fn process_article_markup_tag(output: &mut String, macros: &impl Macr
     }
 }
 
-// fn escape_html() { // TODO
-//     code = code.replace("&", "&amp;");
-//     code = code.replace("<", "&lt;");
-//     code = code.replace(">", "&gt;");
-// }
+/// Start of the anchor written for a reference. The anchor is a placeholder
+/// until the website is written, when the referenced class is resolved.
+pub(crate) const REFERENCE_START: &str = r#"<a class="ref" data-ref=""#;
+
+/// Write a reference to a class or an article.
+///
+/// The key is a class key, an article key or a local key `(key)` in the current
+/// document. An optional second argument replaces the name of the article as label.
+fn process_reference(output: &mut String, macros: &impl MacroMap, arguments: &[&ParsedValue], from: Position) -> Result<(), String> {
+    if arguments.len() != 1 && arguments.len() != 2 {
+        return Err(format!("<ref> command at {}:{} takes a key and an optional label.", from.line, from.column));
+    }
+    let key = arguments[0];
+    if !key.is_text() {
+        return Err(format!("Key of <ref> command at {}:{} must be text.", key.from().line, key.from().column));
+    }
+    let document_key = match macros.document_key() {
+        Some(document_key) => document_key,
+        None => return Err(format!("<ref> command at {}:{} is only allowed in documents.", from.line, from.column)),
+    };
+    let key = match read_include_key(document_key, key.as_text().unwrap().as_str()) {
+        Ok(LinkKey::Class(key)) => key,
+        Ok(LinkKey::Article(key)) => key,
+        Err(e) => return Err(format!("Invalid key in <ref> command at {}:{}: {}", from.line, from.column, e)),
+    };
+    output.push_str(REFERENCE_START);
+    output.push_str(&escape_html(&key));
+    output.push_str("\">");
+    if let Some(label) = arguments.get(1) {
+        process_markup_level(output, macros, label)?;
+    }
+    output.push_str("</a>");
+    Ok(())
+}
+
+/// Start and end of the element written for a footnote. The element is a
+/// placeholder until the article is written, when footnotes are numbered.
+pub(crate) const FOOTNOTE_START: &str = "<fn-note>";
+pub(crate) const FOOTNOTE_END: &str = "</fn-note>";
+
+/// Write a footnote. Footnotes cannot be nested.
+fn process_footnote(output: &mut String, macros: &impl MacroMap, arguments: &[&ParsedValue], from: Position) -> Result<(), String> {
+    if arguments.len() != 1 {
+        return Err(format!("<fn> command at {}:{} takes 1 markup argument.", from.line, from.column));
+    }
+    let mut note = String::new();
+    process_markup_level(&mut note, macros, arguments[0])?;
+    if note.contains(FOOTNOTE_START) {
+        return Err(format!("<fn> command at {}:{} cannot contain another footnote.", from.line, from.column));
+    }
+    output.push_str(FOOTNOTE_START);
+    output.push_str(&note);
+    output.push_str(FOOTNOTE_END);
+    Ok(())
+}
+
+/// Start of the anchor written for a citation.
+pub(crate) const CITATION_START: &str = r#"<a class="cite" data-cite=""#;
+
+/// Write a citation of a bibliography entry, with an optional locator such as a page.
+///
+/// The anchor carries the formatted reference, so that reference lists can be
+/// generated from content alone. It is linked to the bibliography page when the
+/// website is written.
+fn process_citation(output: &mut String, macros: &impl MacroMap, arguments: &[&ParsedValue], from: Position) -> Result<(), String> {
+    if arguments.len() != 1 && arguments.len() != 2 {
+        return Err(format!("<cite> command at {}:{} takes a key and an optional locator.", from.line, from.column));
+    }
+    let bibliography = match macros.bibliography() {
+        Some(bibliography) => bibliography,
+        None => return Err(format!("<cite> command at {}:{} is only allowed in documents.", from.line, from.column)),
+    };
+    let key = arguments[0];
+    if !key.is_text() {
+        return Err(format!("Key of <cite> command at {}:{} must be text.", key.from().line, key.from().column));
+    }
+    let key = key.as_text().unwrap().as_str();
+    let locator = if let Some(locator) = arguments.get(1) {
+        let mut markup = String::new();
+        process_markup_level(&mut markup, macros, locator)?;
+        Some(markup)
+    } else {
+        None
+    };
+    let label = match bibliography.citation_label(key, locator.as_deref()) {
+        Some(label) => label,
+        None => return Err(format!("Unknown bibliography entry {} in <cite> command at {}:{}.", key, from.line, from.column)),
+    };
+    let reference = bibliography.reference_html(key).unwrap();
+    output.push_str(CITATION_START);
+    output.push_str(&escape_html(key));
+    output.push_str(&format!(r#"" data-reference="{}">{}</a>"#, escape_html(&reference), label));
+    Ok(())
+}
+
+/// A citation found in markup.
+pub(crate) struct CitationPlaceholder {
+    pub(crate) key: String,
+    /// Formatted reference in HTML.
+    pub(crate) reference: String,
+    /// Byte index after the attributes of the anchor, where a link can be added.
+    pub(crate) end: usize,
+}
+
+/// Find the citations in markup.
+pub(crate) fn find_citations(markup: &str) -> Vec<CitationPlaceholder> {
+    let mut citations = vec![];
+    let mut offset = 0;
+    while let Some(i) = markup[offset..].find(CITATION_START) {
+        let key_start = offset + i + CITATION_START.len();
+        let key_end = match markup[key_start..].find('"') {
+            Some(i) => key_start + i,
+            None => break,
+        };
+        let reference_start = key_end + r#"" data-reference=""#.len();
+        let reference_end = match markup.get(reference_start..).and_then(|rest| rest.find('"')) {
+            Some(i) => reference_start + i,
+            None => break,
+        };
+        citations.push(CitationPlaceholder {
+            key: unescape_html(&markup[key_start..key_end]),
+            reference: unescape_html(&markup[reference_start..reference_end]),
+            end: reference_end + 1,
+        });
+        offset = reference_end;
+    }
+    citations
+}
+
+/// A reference placeholder found in markup.
+pub(crate) struct ReferencePlaceholder<'a> {
+    /// Byte range of the whole anchor.
+    pub(crate) start: usize,
+    pub(crate) end: usize,
+    /// Class key or article key.
+    pub(crate) key: String,
+    /// Label given by the author. Empty if the name of the article should be used.
+    pub(crate) label: &'a str,
+}
+
+/// Find the reference placeholders in markup.
+pub(crate) fn find_references(markup: &str) -> Vec<ReferencePlaceholder> {
+    let mut references = vec![];
+    let mut offset = 0;
+    while let Some(i) = markup[offset..].find(REFERENCE_START) {
+        let start = offset + i;
+        let key_start = start + REFERENCE_START.len();
+        let key_end = match markup[key_start..].find('"') {
+            Some(i) => key_start + i,
+            None => break,
+        };
+        let label_start = key_end + 2;
+        let label_end = match find_anchor_end(&markup[label_start..]) {
+            Some(i) => label_start + i,
+            None => break,
+        };
+        let end = label_end + 4;
+        let key = unescape_html(&markup[key_start..key_end]);
+        references.push(ReferencePlaceholder { start, end, key, label: &markup[label_start..label_end] });
+        offset = end;
+    }
+    references
+}
+
+/// Find the `</a>` that closes an anchor whose content starts `html`, skipping
+/// the anchors nested in the content.
+fn find_anchor_end(html: &str) -> Option<usize> {
+    let mut depth = 0;
+    let mut offset = 0;
+    while let Some(i) = html[offset..].find('<') {
+        let tag = &html[offset + i..];
+        if tag.starts_with("</a>") {
+            if depth == 0 {
+                return Some(offset + i);
+            }
+            depth -= 1;
+        } else if tag.starts_with("<a ") || tag.starts_with("<a>") {
+            depth += 1;
+        }
+        offset += i + 1;
+    }
+    None
+}
+
+/// Find the language of a code command given as an attribute, as in `<code rust>`.
+fn find_code_attribute_language(tag: &ParsedTaggedTuple) -> Option<&'static str> {
+    language_names().find(|language| tag.get_attribute_by(*language).is_some())
+}
+
+/// Remove tags from markup and unescape the remaining text.
+pub(crate) fn strip_markup(markup: &str) -> String {
+    let mut text = String::new();
+    let mut in_tag = false;
+    for c in markup.chars() {
+        match c {
+            '<' => in_tag = true,
+            '>' if in_tag => {
+                in_tag = false;
+                text.push(' ');
+            }
+            _ if !in_tag => text.push(c),
+            _ => {}
+        }
+    }
+    unescape_html(&text)
+}
+
+/// Reverse [escape_html], and replace decimal character references such as
+/// `&#8599;`, which generated HTML uses for arrows. References are decoded in a
+/// single pass, so `&amp;lt;` becomes `&lt;` and not `<`.
+pub(crate) fn unescape_html(text: &str) -> String {
+    let mut unescaped = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(i) = rest.find('&') {
+        unescaped.push_str(&rest[..i]);
+        rest = &rest[i..];
+        let reference = rest.find(';').and_then(|end| {
+            let c = match &rest[1..end] {
+                "amp" => Some('&'),
+                "lt" => Some('<'),
+                "gt" => Some('>'),
+                "quot" => Some('"'),
+                name => name.strip_prefix('#').and_then(|code| code.parse().ok()).and_then(char::from_u32),
+            };
+            c.map(|c| (c, end))
+        });
+        match reference {
+            Some((c, end)) => {
+                unescaped.push(c);
+                rest = &rest[end + 1..];
+            }
+            None => {
+                unescaped.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    unescaped.push_str(rest);
+    unescaped
+}
+
+/// Escape the characters of text that are reserved in HTML.
+pub(crate) fn escape_html(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unescape_reverses_escape() {
+        for text in ["plain", "a & b < c > d \"e\"", "&lt;", "&amp;lt;", "&#38;", "x;y&z", "æ∑ ↗"] {
+            assert_eq!(unescape_html(&escape_html(text)), text);
+        }
+    }
+
+    #[test]
+    fn text_is_escaped() {
+        let mut output = String::new();
+        write_text(&mut output, "AT&T: a < b and c > d");
+        assert_eq!(output, "AT&amp;T: a &lt; b and c &gt; d");
+        assert_eq!(Markup(output.clone()).to_html(), output);
+        assert_eq!(strip_markup(&output), "AT&T: a < b and c > d");
+    }
+
+    #[test]
+    fn unescape_decodes_once() {
+        assert_eq!(unescape_html("&#38;lt;"), "&lt;");
+        assert_eq!(unescape_html("&amp;lt;"), "&lt;");
+        assert_eq!(unescape_html("&amp;#8599;"), "&#8599;");
+        assert_eq!(unescape_html("&#8599; &#60;"), "↗ <");
+    }
+
+    #[test]
+    fn unescape_keeps_unknown_references() {
+        assert_eq!(unescape_html("&nbsp; & &#; &#x41; &#99999999;"), "&nbsp; & &#; &#x41; &#99999999;");
+        assert_eq!(unescape_html("a &b; c &lt"), "a &b; c &lt");
+    }
+
+}
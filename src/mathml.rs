@@ -0,0 +1,614 @@
+//! Conversion of TeX math to MathML.
+//!
+//! This covers the math that content commonly uses: symbols, scripts,
+//! fractions, roots, accents, fonts, text, delimiters and matrix environments.
+//! The TeX is kept as an annotation, and commands that are not supported are
+//! written as errors and returned to the caller to report.
+
+use std::collections::HashSet;
+use crate::markup::escape_html;
+
+/// Identifiers, by command name.
+const IDENTIFIERS: &[(&str, &str)] = &[
+    ("alpha", "α"), ("beta", "β"), ("gamma", "γ"), ("delta", "δ"), ("epsilon", "ϵ"), ("varepsilon", "ε"),
+    ("zeta", "ζ"), ("eta", "η"), ("theta", "θ"), ("vartheta", "ϑ"), ("iota", "ι"), ("kappa", "κ"),
+    ("lambda", "λ"), ("mu", "μ"), ("nu", "ν"), ("xi", "ξ"), ("pi", "π"), ("varpi", "ϖ"), ("rho", "ρ"),
+    ("varrho", "ϱ"), ("sigma", "σ"), ("varsigma", "ς"), ("tau", "τ"), ("upsilon", "υ"), ("phi", "ϕ"),
+    ("varphi", "φ"), ("chi", "χ"), ("psi", "ψ"), ("omega", "ω"),
+    ("Gamma", "Γ"), ("Delta", "Δ"), ("Theta", "Θ"), ("Lambda", "Λ"), ("Xi", "Ξ"), ("Pi", "Π"),
+    ("Sigma", "Σ"), ("Upsilon", "Υ"), ("Phi", "Φ"), ("Psi", "Ψ"), ("Omega", "Ω"),
+    ("infty", "∞"), ("partial", "∂"), ("nabla", "∇"), ("emptyset", "∅"), ("varnothing", "∅"),
+    ("ell", "ℓ"), ("hbar", "ℏ"), ("aleph", "ℵ"), ("Re", "ℜ"), ("Im", "ℑ"), ("wp", "℘"),
+    ("top", "⊤"), ("bot", "⊥"), ("angle", "∠"), ("triangle", "△"), ("prime", "′"),
+];
+
+/// Operators, by command name.
+const OPERATORS: &[(&str, &str)] = &[
+    ("pm", "±"), ("mp", "∓"), ("times", "×"), ("div", "÷"), ("cdot", "⋅"), ("ast", "∗"), ("star", "⋆"),
+    ("circ", "∘"), ("bullet", "∙"), ("oplus", "⊕"), ("ominus", "⊖"), ("otimes", "⊗"), ("odot", "⊙"),
+    ("cup", "∪"), ("cap", "∩"), ("setminus", "∖"), ("wedge", "∧"), ("land", "∧"), ("vee", "∨"), ("lor", "∨"),
+    ("neg", "¬"), ("lnot", "¬"), ("leq", "≤"), ("le", "≤"), ("geq", "≥"), ("ge", "≥"), ("neq", "≠"), ("ne", "≠"),
+    ("ll", "≪"), ("gg", "≫"), ("approx", "≈"), ("sim", "∼"), ("simeq", "≃"), ("cong", "≅"), ("equiv", "≡"),
+    ("propto", "∝"), ("prec", "≺"), ("succ", "≻"), ("preceq", "⪯"), ("succeq", "⪰"), ("mid", "∣"),
+    ("parallel", "∥"), ("perp", "⊥"), ("in", "∈"), ("notin", "∉"), ("ni", "∋"), ("subset", "⊂"),
+    ("supset", "⊃"), ("subseteq", "⊆"), ("supseteq", "⊇"), ("subsetneq", "⊊"), ("supsetneq", "⊋"),
+    ("forall", "∀"), ("exists", "∃"), ("nexists", "∄"), ("to", "→"), ("rightarrow", "→"),
+    ("leftarrow", "←"), ("gets", "←"), ("leftrightarrow", "↔"), ("Rightarrow", "⇒"), ("Leftarrow", "⇐"),
+    ("Leftrightarrow", "⇔"), ("implies", "⟹"), ("impliedby", "⟸"), ("iff", "⟺"), ("mapsto", "↦"),
+    ("longrightarrow", "⟶"), ("longmapsto", "⟼"), ("hookrightarrow", "↪"), ("uparrow", "↑"),
+    ("downarrow", "↓"), ("vdash", "⊢"), ("models", "⊨"), ("ldots", "…"), ("dots", "…"), ("cdots", "⋯"),
+    ("vdots", "⋮"), ("ddots", "⋱"), ("colon", ":"), ("langle", "⟨"), ("rangle", "⟩"), ("lceil", "⌈"),
+    ("rceil", "⌉"), ("lfloor", "⌊"), ("rfloor", "⌋"), ("vert", "|"), ("Vert", "‖"), ("lvert", "|"),
+    ("rvert", "|"), ("lVert", "‖"), ("rVert", "‖"), ("backslash", "∖"), ("{", "{"), ("}", "}"), ("|", "‖"),
+];
+
+/// Operators with limits above and below them in display math.
+const LARGE_OPERATORS: &[(&str, &str)] = &[
+    ("sum", "∑"), ("prod", "∏"), ("coprod", "∐"), ("bigcup", "⋃"), ("bigcap", "⋂"), ("bigoplus", "⨁"),
+    ("bigotimes", "⨂"), ("bigvee", "⋁"), ("bigwedge", "⋀"), ("int", "∫"), ("iint", "∬"), ("iiint", "∭"),
+    ("oint", "∮"),
+];
+
+/// Function names, which are written upright.
+const FUNCTIONS: &[&str] = &[
+    "sin", "cos", "tan", "cot", "sec", "csc", "arcsin", "arccos", "arctan", "sinh", "cosh", "tanh", "log",
+    "ln", "lg", "exp", "lim", "liminf", "limsup", "max", "min", "sup", "inf", "det", "dim", "ker", "deg",
+    "gcd", "arg", "hom", "Pr",
+];
+
+/// Functions with limits below them in display math.
+const LIMIT_FUNCTIONS: &[&str] = &["lim", "liminf", "limsup", "max", "min", "sup", "inf", "det", "gcd", "Pr"];
+
+/// Accents written over or under their argument.
+const ACCENTS: &[(&str, &str, bool)] = &[
+    ("hat", "^", true), ("widehat", "^", true), ("bar", "¯", true), ("overline", "¯", true),
+    ("tilde", "~", true), ("widetilde", "~", true), ("vec", "→", true), ("overrightarrow", "→", true),
+    ("dot", "˙", true), ("ddot", "¨", true), ("check", "ˇ", true), ("breve", "˘", true),
+    ("underline", "_", false), ("overbrace", "⏞", true), ("underbrace", "⏟", false),
+];
+
+/// Font commands and their math variants.
+const FONTS: &[(&str, &str)] = &[
+    ("mathbb", "double-struck"), ("mathbf", "bold"), ("boldsymbol", "bold-italic"), ("mathcal", "script"),
+    ("mathscr", "script"), ("mathfrak", "fraktur"), ("mathrm", "normal"), ("mathit", "italic"),
+    ("mathsf", "sans-serif"), ("mathtt", "monospace"),
+];
+
+/// Spaces and their widths in em.
+const SPACES: &[(&str, &str)] = &[
+    (",", "0.167em"), (":", "0.222em"), (">", "0.222em"), (";", "0.278em"), (" ", "0.333em"), ("~", "0.333em"),
+    ("quad", "1em"), ("qquad", "2em"), ("!", "-0.167em"),
+];
+
+/// Commands that do not change the MathML.
+const IGNORED: &[&str] = &[
+    "displaystyle", "textstyle", "scriptstyle", "limits", "nolimits", "big", "Big", "bigg", "Bigg",
+    "bigl", "bigr", "Bigl", "Bigr", "biggl", "biggr", "Biggl", "Biggr", "nonumber", "notag",
+];
+
+/// Convert TeX math to MathML. The names of unsupported commands are added to
+/// `unsupported`.
+pub(crate) fn tex_to_mathml(tex: &str, display: bool, unsupported: &mut HashSet<String>) -> String {
+    let mut parser = Parser { tokens: lex(tex), i: 0, display, unsupported };
+    let row = parser.parse_all();
+    format!(
+        r#"<math xmlns="http://www.w3.org/1998/Math/MathML"{}><semantics><mrow>{}</mrow><annotation encoding="application/x-tex">{}</annotation></semantics></math>"#,
+        if display { r#" display="block""# } else { "" }, row.concat(), escape_html(tex),
+    )
+}
+
+#[derive(Clone, PartialEq)]
+enum Token {
+    Command(String),
+    Char(char),
+    Space,
+    Open,
+    Close,
+    Superscript,
+    Subscript,
+    Align,
+}
+
+fn lex(tex: &str) -> Vec<Token> {
+    let mut tokens = vec![];
+    let mut chars = tex.chars().peekable();
+    while let Some(c) = chars.next() {
+        let token = match c {
+            '\\' => {
+                let mut name = String::new();
+                while let Some(c) = chars.peek().filter(|c| c.is_ascii_alphabetic()) {
+                    name.push(*c);
+                    chars.next();
+                }
+                if name.is_empty() {
+                    if let Some(c) = chars.next() {
+                        name.push(c);
+                    }
+                }
+                Token::Command(name)
+            }
+            '{' => Token::Open,
+            '}' => Token::Close,
+            '^' => Token::Superscript,
+            '_' => Token::Subscript,
+            '&' => Token::Align,
+            '~' => Token::Command(String::from("~")),
+            c if c.is_whitespace() => Token::Space,
+            c => Token::Char(c),
+        };
+        tokens.push(token);
+    }
+    tokens
+}
+
+struct Parser<'a> {
+    tokens: Vec<Token>,
+    i: usize,
+    display: bool,
+    unsupported: &'a mut HashSet<String>,
+}
+
+impl Parser<'_> {
+
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.i)
+    }
+
+    fn skip_spaces(&mut self) {
+        while self.peek() == Some(&Token::Space) {
+            self.i += 1;
+        }
+    }
+
+    /// Whether the next token ends a row.
+    fn at_row_end(&self) -> bool {
+        match self.peek() {
+            None | Some(Token::Close) | Some(Token::Align) => true,
+            Some(Token::Command(name)) => name == "\\" || name == "right" || name == "end" || name == "cr",
+            _ => false,
+        }
+    }
+
+    /// Parse all tokens. Row ends outside of groups and environments, such as
+    /// a stray `}`, are skipped.
+    fn parse_all(&mut self) -> Vec<String> {
+        let mut row = self.parse_row();
+        while self.i < self.tokens.len() {
+            self.i += 1;
+            row.extend(self.parse_row());
+        }
+        row
+    }
+
+    /// Parse elements with their scripts until the end of a row.
+    fn parse_row(&mut self) -> Vec<String> {
+        let mut row = vec![];
+        loop {
+            self.skip_spaces();
+            if self.at_row_end() {
+                return row;
+            }
+            let (base, limits) = match self.parse_atom() {
+                Some(atom) => atom,
+                None => continue,
+            };
+            row.push(self.parse_scripts(base, limits));
+        }
+    }
+
+    /// Attach the subscript and superscript that follow a base.
+    fn parse_scripts(&mut self, base: String, limits: bool) -> String {
+        let mut subscript = None;
+        let mut superscript = None;
+        loop {
+            self.skip_spaces();
+            match self.peek() {
+                Some(Token::Subscript) if subscript.is_none() => {
+                    self.i += 1;
+                    subscript = Some(self.parse_argument());
+                }
+                Some(Token::Superscript) if superscript.is_none() => {
+                    self.i += 1;
+                    superscript = Some(self.parse_argument());
+                }
+                Some(Token::Char('\'')) if superscript.is_none() => {
+                    let mut primes = String::new();
+                    while self.peek() == Some(&Token::Char('\'')) {
+                        primes.push('′');
+                        self.i += 1;
+                    }
+                    superscript = Some(format!("<mo>{}</mo>", primes));
+                }
+                _ => break,
+            }
+        }
+        let (under, over, both) = if limits && self.display {
+            ("munder", "mover", "munderover")
+        } else {
+            ("msub", "msup", "msubsup")
+        };
+        match (subscript, superscript) {
+            (None, None) => base,
+            (Some(sub), None) => format!("<{under}>{base}{sub}</{under}>"),
+            (None, Some(sup)) => format!("<{over}>{base}{sup}</{over}>"),
+            (Some(sub), Some(sup)) => format!("<{both}>{base}{sub}{sup}</{both}>"),
+        }
+    }
+
+    /// Parse a group or a single element as one element.
+    fn parse_argument(&mut self) -> String {
+        self.skip_spaces();
+        if self.peek() == Some(&Token::Open) {
+            return self.parse_group();
+        }
+        if self.at_row_end() {
+            return String::from("<mrow></mrow>");
+        }
+        match self.parse_atom() {
+            Some((atom, _)) => atom,
+            None => String::from("<mrow></mrow>"),
+        }
+    }
+
+    /// Parse a group from its opening brace.
+    fn parse_group(&mut self) -> String {
+        self.i += 1;
+        let mut row = self.parse_row();
+        // Alignments and line breaks only separate cells in environments.
+        while matches!(self.peek(), Some(Token::Align)) || self.peek() == Some(&Token::Command(String::from("\\"))) {
+            self.i += 1;
+            row.extend(self.parse_row());
+        }
+        if self.peek() == Some(&Token::Close) {
+            self.i += 1;
+        }
+        format!("<mrow>{}</mrow>", row.concat())
+    }
+
+    /// Parse an optional argument in brackets.
+    fn parse_optional_argument(&mut self) -> Option<String> {
+        self.skip_spaces();
+        if self.peek() != Some(&Token::Char('[')) {
+            return None;
+        }
+        self.i += 1;
+        let mut row = vec![];
+        loop {
+            self.skip_spaces();
+            if self.at_row_end() || self.peek() == Some(&Token::Char(']')) {
+                break;
+            }
+            if let Some((atom, limits)) = self.parse_atom() {
+                row.push(self.parse_scripts(atom, limits));
+            }
+        }
+        if self.peek() == Some(&Token::Char(']')) {
+            self.i += 1;
+        }
+        Some(format!("<mrow>{}</mrow>", row.concat()))
+    }
+
+    /// Read the text of a group, as in the argument of `\text`.
+    fn read_text(&mut self) -> String {
+        self.skip_spaces();
+        if self.peek() != Some(&Token::Open) {
+            return match self.tokens.get(self.i).cloned() {
+                Some(Token::Char(c)) => {
+                    self.i += 1;
+                    c.to_string()
+                }
+                _ => String::new(),
+            };
+        }
+        self.i += 1;
+        let mut text = String::new();
+        let mut depth = 1;
+        while let Some(token) = self.tokens.get(self.i).cloned() {
+            self.i += 1;
+            match token {
+                Token::Open => depth += 1,
+                Token::Close => {
+                    depth -= 1;
+                    if depth == 0 {
+                        break;
+                    }
+                }
+                Token::Char(c) => text.push(c),
+                Token::Space => text.push(' '),
+                Token::Superscript => text.push('^'),
+                Token::Subscript => text.push('_'),
+                Token::Align => text.push('&'),
+                Token::Command(name) if name.chars().all(|c| !c.is_ascii_alphabetic()) && name != "\\" => text.push_str(&name),
+                Token::Command(_) => {}
+            }
+        }
+        text
+    }
+
+    /// Parse an element without scripts. Returns the element and whether it
+    /// takes limits in display math, or `None` if the token writes nothing.
+    fn parse_atom(&mut self) -> Option<(String, bool)> {
+        let token = self.tokens.get(self.i)?.clone();
+        self.i += 1;
+        let name = match token {
+            Token::Open => {
+                self.i -= 1;
+                return Some((self.parse_group(), false));
+            }
+            Token::Char(c) if c.is_ascii_digit() || (c == '.' && matches!(self.peek(), Some(Token::Char(d)) if d.is_ascii_digit())) => {
+                let mut number = c.to_string();
+                while let Some(Token::Char(d)) = self.peek() {
+                    if d.is_ascii_digit() || (*d == '.' && matches!(self.tokens.get(self.i + 1), Some(Token::Char(e)) if e.is_ascii_digit())) {
+                        number.push(*d);
+                        self.i += 1;
+                    } else {
+                        break;
+                    }
+                }
+                return Some((format!("<mn>{}</mn>", number), false));
+            }
+            Token::Char(c) if c.is_alphabetic() => return Some((format!("<mi>{}</mi>", c), false)),
+            Token::Char(c) => {
+                let operator = match c {
+                    '-' => "−".to_string(),
+                    '*' => "∗".to_string(),
+                    '\'' => "′".to_string(),
+                    c => escape_html(&c.to_string()),
+                };
+                let stretchy = if "()[]|/".contains(c) { r#" stretchy="false""# } else { "" };
+                return Some((format!("<mo{}>{}</mo>", stretchy, operator), false));
+            }
+            Token::Superscript | Token::Subscript => { // A script without a base.
+                self.i -= 1;
+                return Some((self.parse_scripts(String::from("<mrow></mrow>"), false), false));
+            }
+            Token::Space | Token::Close | Token::Align => return None,
+            Token::Command(name) => name,
+        };
+        let name = name.as_str();
+        if let Some((_, identifier)) = IDENTIFIERS.iter().find(|(command, _)| *command == name) {
+            let variant = if identifier.chars().next().is_some_and(|c| c.is_uppercase()) { r#" mathvariant="normal""# } else { "" };
+            return Some((format!("<mi{}>{}</mi>", variant, identifier), false));
+        }
+        if let Some((_, operator)) = OPERATORS.iter().find(|(command, _)| *command == name) {
+            return Some((format!("<mo>{}</mo>", escape_html(operator)), false));
+        }
+        if let Some((_, operator)) = LARGE_OPERATORS.iter().find(|(command, _)| *command == name) {
+            let limits = !name.contains("int");
+            return Some((format!(r#"<mo largeop="true" movablelimits="true">{}</mo>"#, operator), limits));
+        }
+        if FUNCTIONS.contains(&name) {
+            return Some((format!("<mi>{}</mi>", name), LIMIT_FUNCTIONS.contains(&name)));
+        }
+        if let Some((_, width)) = SPACES.iter().find(|(command, _)| *command == name) {
+            return Some((format!(r#"<mspace width="{}"></mspace>"#, width), false));
+        }
+        if IGNORED.contains(&name) {
+            return None;
+        }
+        if let Some((_, accent, over)) = ACCENTS.iter().find(|(command, _, _)| *command == name) {
+            let argument = self.parse_argument();
+            return Some(if *over {
+                (format!(r#"<mover accent="true">{}<mo>{}</mo></mover>"#, argument, accent), false)
+            } else {
+                (format!(r#"<munder accentunder="true">{}<mo>{}</mo></munder>"#, argument, accent), false)
+            });
+        }
+        if let Some((_, variant)) = FONTS.iter().find(|(command, _)| *command == name) {
+            let argument = self.parse_argument();
+            let argument = argument.replace("<mi>", &format!(r#"<mi mathvariant="{}">"#, variant));
+            return Some((argument, false));
+        }
+        let element = match name {
+            "frac" | "dfrac" | "tfrac" | "cfrac" => {
+                let numerator = self.parse_argument();
+                let denominator = self.parse_argument();
+                format!("<mfrac>{}{}</mfrac>", numerator, denominator)
+            }
+            "binom" | "dbinom" | "tbinom" => {
+                let n = self.parse_argument();
+                let k = self.parse_argument();
+                format!(r#"<mrow><mo>(</mo><mfrac linethickness="0">{}{}</mfrac><mo>)</mo></mrow>"#, n, k)
+            }
+            "sqrt" => match self.parse_optional_argument() {
+                Some(index) => {
+                    let radicand = self.parse_argument();
+                    format!("<mroot>{}{}</mroot>", radicand, index)
+                }
+                None => format!("<msqrt>{}</msqrt>", self.parse_argument()),
+            },
+            "overset" | "stackrel" => {
+                let over = self.parse_argument();
+                let base = self.parse_argument();
+                format!("<mover>{}{}</mover>", base, over)
+            }
+            "underset" => {
+                let under = self.parse_argument();
+                let base = self.parse_argument();
+                format!("<munder>{}{}</munder>", base, under)
+            }
+            "text" | "textrm" | "textup" | "mbox" | "textit" | "textbf" => {
+                let text = self.read_text();
+                let variant = match name {
+                    "textit" => r#" mathvariant="italic""#,
+                    "textbf" => r#" mathvariant="bold""#,
+                    _ => "",
+                };
+                format!("<mtext{}>{}</mtext>", variant, escape_html(&text).replace(' ', "\u{a0}"))
+            }
+            "operatorname" => {
+                let text = self.read_text();
+                format!("<mi>{}</mi>", escape_html(&text))
+            }
+            "left" => {
+                let open = self.parse_delimiter();
+                let row = self.parse_row();
+                let close = if self.peek() == Some(&Token::Command(String::from("right"))) {
+                    self.i += 1;
+                    self.parse_delimiter()
+                } else {
+                    String::new()
+                };
+                format!("<mrow>{}{}{}</mrow>", open, row.concat(), close)
+            }
+            "begin" => self.parse_environment(),
+            "not" => {
+                let (atom, _) = self.parse_atom().unwrap_or_default();
+                match atom.strip_suffix("</mo>") {
+                    Some(atom) => format!("{}\u{338}</mo>", atom),
+                    None => atom,
+                }
+            }
+            "%" | "$" | "#" | "&" | "_" => format!("<mo>{}</mo>", escape_html(name)),
+            _ => {
+                self.unsupported.insert(name.to_string());
+                format!("<merror><mtext>\\{}</mtext></merror>", escape_html(name))
+            }
+        };
+        Some((element, false))
+    }
+
+    /// Parse the delimiter after `\left` or `\right`.
+    fn parse_delimiter(&mut self) -> String {
+        self.skip_spaces();
+        let token = match self.tokens.get(self.i).cloned() {
+            Some(token) => token,
+            None => return String::new(),
+        };
+        self.i += 1;
+        let delimiter = match token {
+            Token::Char('.') => return String::new(),
+            Token::Char(c) => escape_html(&c.to_string()),
+            Token::Command(name) => match OPERATORS.iter().find(|(command, _)| *command == name) {
+                Some((_, operator)) => operator.to_string(),
+                None => return String::new(),
+            },
+            _ => return String::new(),
+        };
+        format!(r#"<mo fence="true" stretchy="true">{}</mo>"#, delimiter)
+    }
+
+    /// Parse an environment from after `\begin`, as a table of rows and
+    /// columns.
+    fn parse_environment(&mut self) -> String {
+        let environment = self.read_text();
+        if environment == "array" {
+            self.read_text(); // Column specification.
+        }
+        let mut rows = vec![];
+        let mut row = vec![];
+        loop {
+            row.push(format!("<mtd>{}</mtd>", self.parse_row().concat()));
+            match self.tokens.get(self.i).cloned() {
+                Some(Token::Align) => self.i += 1,
+                Some(Token::Command(name)) if name == "\\" || name == "cr" => {
+                    self.i += 1;
+                    self.parse_optional_argument(); // Row spacing.
+                    rows.push(format!("<mtr>{}</mtr>", row.concat()));
+                    row = vec![];
+                }
+                Some(Token::Command(name)) if name == "end" => {
+                    self.i += 1;
+                    self.read_text();
+                    break;
+                }
+                Some(Token::Close) | Some(Token::Command(_)) => self.i += 1, // Unbalanced.
+                _ => break,
+            }
+        }
+        if row.iter().any(|cell| cell != "<mtd></mtd>") {
+            rows.push(format!("<mtr>{}</mtr>", row.concat()));
+        }
+        let environment = environment.trim_end_matches('*');
+        let align = match environment {
+            "aligned" | "align" | "alignat" | "split" | "eqnarray" => r#" columnalign="right left""#,
+            "cases" => r#" columnalign="left left""#,
+            _ => "",
+        };
+        let table = format!("<mtable{}>{}</mtable>", align, rows.concat());
+        let (open, close) = match environment {
+            "pmatrix" => ("(", ")"),
+            "bmatrix" => ("[", "]"),
+            "Bmatrix" => ("{", "}"),
+            "vmatrix" => ("|", "|"),
+            "Vmatrix" => ("‖", "‖"),
+            "cases" => ("{", ""),
+            _ => return table,
+        };
+        let fence = |delimiter: &str| if delimiter.is_empty() { String::new() } else { format!(r#"<mo fence="true">{}</mo>"#, delimiter) };
+        format!("<mrow>{}{}{}</mrow>", fence(open), table, fence(close))
+    }
+
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The MathML of TeX without the math element and its annotation.
+    fn convert(tex: &str, display: bool) -> String {
+        let mut unsupported = HashSet::new();
+        let mathml = tex_to_mathml(tex, display, &mut unsupported);
+        assert!(unsupported.is_empty(), "unsupported commands in {}", tex);
+        let start = mathml.find("<semantics><mrow>").unwrap() + "<semantics><mrow>".len();
+        let end = mathml.rfind("</mrow><annotation").unwrap();
+        mathml[start..end].to_string()
+    }
+
+    #[test]
+    fn identifiers_operators_and_numbers() {
+        assert_eq!(convert(r"\alpha + \beta", false), "<mi>α</mi><mo>+</mo><mi>β</mi>");
+        assert_eq!(convert("12.5", false), "<mn>12.5</mn>");
+        assert_eq!(convert(r"\sin x", false), "<mi>sin</mi><mi>x</mi>");
+    }
+
+    #[test]
+    fn scripts() {
+        assert_eq!(convert("x^2", false), "<msup><mi>x</mi><mn>2</mn></msup>");
+        assert_eq!(convert("a_n", false), "<msub><mi>a</mi><mi>n</mi></msub>");
+        assert_eq!(
+            convert(r"\sum_{i=1}^n i", true),
+            r#"<munderover><mo largeop="true" movablelimits="true">∑</mo><mrow><mi>i</mi><mo>=</mo><mn>1</mn></mrow><mi>n</mi></munderover><mi>i</mi>"#,
+        );
+        assert_eq!(convert(r"\lim_{n} a_n", true), "<munder><mi>lim</mi><mrow><mi>n</mi></mrow></munder><msub><mi>a</mi><mi>n</mi></msub>");
+    }
+
+    #[test]
+    fn fractions_roots_and_accents() {
+        assert_eq!(convert(r"\frac{a}{b}", false), "<mfrac><mrow><mi>a</mi></mrow><mrow><mi>b</mi></mrow></mfrac>");
+        assert_eq!(convert(r"\sqrt[3]{x}", false), "<mroot><mrow><mi>x</mi></mrow><mrow><mn>3</mn></mrow></mroot>");
+        assert_eq!(convert(r"\hat{x}", false), r#"<mover accent="true"><mrow><mi>x</mi></mrow><mo>^</mo></mover>"#);
+        assert_eq!(convert(r"\mathbb{R}", false), r#"<mrow><mi mathvariant="double-struck">R</mi></mrow>"#);
+    }
+
+    #[test]
+    fn delimiters_text_and_environments() {
+        assert_eq!(
+            convert(r"\left( x \right)", false),
+            r#"<mrow><mo fence="true" stretchy="true">(</mo><mi>x</mi><mo fence="true" stretchy="true">)</mo></mrow>"#,
+        );
+        // Spaces at the ends of text are kept as no-break spaces.
+        assert_eq!(convert(r"\text{if } x < y", false), "<mtext>if\u{a0}</mtext><mi>x</mi><mo>&lt;</mo><mi>y</mi>");
+        assert_eq!(
+            convert(r"\begin{pmatrix} a & b \\ c & d \end{pmatrix}", false),
+            r#"<mrow><mo fence="true">(</mo><mtable><mtr><mtd><mi>a</mi></mtd><mtd><mi>b</mi></mtd></mtr><mtr><mtd><mi>c</mi></mtd><mtd><mi>d</mi></mtd></mtr></mtable><mo fence="true">)</mo></mrow>"#,
+        );
+    }
+
+    #[test]
+    fn display_and_annotation() {
+        let mut unsupported = HashSet::new();
+        let mathml = tex_to_mathml("a < b", true, &mut unsupported);
+        assert!(mathml.starts_with(r#"<math xmlns="http://www.w3.org/1998/Math/MathML" display="block">"#));
+        assert!(mathml.ends_with(r#"<annotation encoding="application/x-tex">a &lt; b</annotation></semantics></math>"#));
+    }
+
+    #[test]
+    fn unsupported_commands() {
+        let mut unsupported = HashSet::new();
+        let mathml = tex_to_mathml(r"\foo x", false, &mut unsupported);
+        assert!(mathml.contains(r"<merror><mtext>\foo</mtext></merror><mi>x</mi>"));
+        assert!(unsupported.contains("foo"));
+    }
+
+}
@@ -2,7 +2,7 @@ use std::rc::Rc;
 use khi::{Catenation, Element, Text, TextType, Value};
 use khi::parse::pdm::{ParsedCatenation, ParsedTag, ParsedTaggedTuple, ParsedText, ParsedTupleElement, ParsedValue, Position};
 use crate::makro::MacroMap;
-use crate::markup::Markup;
+use crate::markup::{Markup, FOOTNOTE_START};
 
 pub fn process_unexpanded_markup(macros: &impl MacroMap, input: &ParsedValue) -> Result<Markup, String> {
     let mut output = String::new();
@@ -10,6 +10,16 @@ pub fn process_unexpanded_markup(macros: &impl MacroMap, input: &ParsedValue) ->
     Ok(Markup::raw(&output))
 }
 
+/// Process markup outside body content, such as a name, heading or caption.
+/// Footnotes are only numbered in body content, so they are not allowed.
+pub fn process_inline_markup(macros: &impl MacroMap, input: &ParsedValue, what: &str) -> Result<Markup, String> {
+    let markup = process_unexpanded_markup(macros, input)?;
+    if markup.0.contains(FOOTNOTE_START) {
+        return Err(format!("<fn> command is not allowed in {} at {}:{}.", what, input.from().line, input.from().column));
+    }
+    Ok(markup)
+}
+
 pub fn process_markup_level(output: &mut String, macros: &impl MacroMap, input: &ParsedValue) -> Result<(), String> {
     let input = preprocess_markup_level(input)?;
     process_article_markup(output, macros, &input)
@@ -1,13 +1,14 @@
 use std::rc::{Rc};
 
 /// A relation: <left> is <right>
+#[derive(PartialEq, Eq, Hash)]
 pub struct Relation {
     pub(crate) left: RelationClass,
     pub(crate) right: RelationClass,
 }
 
 /// A class partially (or fully) applied to some of its parameters.
-#[derive(Clone)]
+#[derive(Clone, PartialEq, Eq, Hash)]
 pub enum RelationClass {
     Name(Rc<str>),
     Qual {
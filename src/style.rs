@@ -1,12 +1,40 @@
 use std::collections::HashMap;
+use std::path::PathBuf;
+use crate::markup::escape_html;
 
 pub type Styles = HashMap<String, Style>;
 
 /// A class style.
 pub struct Style {
+    /// Key of the style in the style file, which templates refer to.
+    pub key: String,
+    /// Name of the style shown to readers.
     pub name: String,
     pub description: Option<String>,
     pub colour: Option<String>,
     pub abbreviation: Option<String>,
-    pub symbol_path: Option<String>, // TODO: Enable symbols
+    pub symbol: Option<Symbol>,
+}
+
+/// Symbol of a class style, shown in article links and article headers.
+pub enum Symbol {
+    /// An SVG or PNG image, copied to /assets/symbols/{file_name}.
+    Image { source: PathBuf, file_name: String },
+    /// A Unicode glyph.
+    Glyph(String),
+}
+
+impl Style {
+
+    /// HTML of the style symbol, if the style has one. Website paths are
+    /// prefixed with `base`.
+    pub fn symbol_html(&self, base: &str) -> Option<String> {
+        match self.symbol.as_ref()? {
+            Symbol::Image { file_name, .. } => Some(format!(
+                r#"<img class="label-symbol" src="{}/assets/symbols/{}" alt="{}">"#, escape_html(base), escape_html(file_name), escape_html(&self.name),
+            )),
+            Symbol::Glyph(glyph) => Some(format!(r#"<span class="label-symbol">{}</span>"#, escape_html(glyph))),
+        }
+    }
+
 }
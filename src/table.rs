@@ -0,0 +1,28 @@
+use crate::markup::Markup;
+
+/// A table of markup cells.
+#[derive(Clone)]
+pub struct Table {
+    /// Rows shown as column headings.
+    pub(crate) header: Vec<Vec<Markup>>,
+    pub(crate) rows: Vec<Vec<Markup>>,
+    /// Alignment of each column. Columns without an alignment are left aligned.
+    pub(crate) alignments: Vec<Alignment>,
+    pub(crate) caption: Option<Markup>,
+}
+
+#[derive(Copy, Clone, Eq, PartialEq)]
+pub enum Alignment {
+    Left,
+    Center,
+    Right,
+}
+
+impl Table {
+
+    /// Alignment of a column.
+    pub fn alignment(&self, column: usize) -> Alignment {
+        self.alignments.get(column).copied().unwrap_or(Alignment::Left)
+    }
+
+}
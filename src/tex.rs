@@ -1,7 +1,7 @@
 use std::fmt::Write;
 use khi::{Catenation, Dictionary, Element, List, TaggedTuple, Text, TextType, Value};
 use khi::parse::pdm::{ParsedList, ParsedTaggedTuple, ParsedText, ParsedValue, Position};
-use crate::makro::{MacroMap};
+use crate::makro::{MacroMap, Macros};
 use crate::{tuple_split};
 
 pub struct Writer<'a, M: MacroMap> {
@@ -85,8 +85,8 @@ impl<M: MacroMap> Writer<'_, M> {
     /// - '_' must be inserted as "\_". _ is the subscript operator in math mode, and reserved in text mode.
     /// - '&' must be inserted as "\&". & is the tabulation operator.
     /// - '#' must be inserted as "\#". # is the argument substitution operator.
-    /// - '\' must be inserted as "\textbackslash" in text and "\backslash" or "\setminus" in math. "\\" indicates a line break.
-    /// - '~' -> "\texttildelow"
+    /// - '\' must be inserted as "\textbackslash{}" in text and "\backslash" or "\setminus" in math. "\\" indicates a line break.
+    /// - '~' -> "\texttildelow{}"
     /// - '{' -> "\{"
     /// - '}' -> "\}"
     pub(crate) fn normalize_and_push_char(&mut self, c: char) {
@@ -120,13 +120,13 @@ impl<M: MacroMap> Writer<'_, M> {
             self.last_type = LastType::Glyph;
             self.column += 2;
         } else if c == '\\' {
-            self.output.push_str("\\textbackslash");
+            self.output.push_str("\\textbackslash{}");
             self.last_type = LastType::Glyph;
-            self.column += 14;
+            self.column += 16;
         } else if c == '~' {
-            self.output.push_str("\\texttildelow");
+            self.output.push_str("\\texttildelow{}");
             self.last_type = LastType::Glyph;
-            self.column += 13;
+            self.column += 15;
         } else if c == '{' {
             self.output.push('\\');
             self.output.push('{');
@@ -208,6 +208,15 @@ pub fn write_tex_with(structure: &ParsedValue, macros: &impl MacroMap, mode: Bre
     Ok(output)
 }
 
+/// Escape text for text mode. Runs of whitespace become a single space.
+pub fn escape_tex_text(text: &str) -> String {
+    let mut output = String::new();
+    let macros = Macros::new();
+    let mut writer = Writer { output: &mut output, column: 1, last_type: LastType::Glyph, line: 1, break_mode: BreakMode::Never, macros: &macros };
+    writer.normalize_and_push_str(text);
+    output
+}
+
 pub struct Preprocessor<'a, M: MacroMap> {
     pub(crate) writer: Writer<'a, M>,
     pub(crate) break_mode: BreakMode,
@@ -1,14 +1,31 @@
+use std::collections::HashMap;
 use std::fs;
 use std::fs::File;
 use std::io::Write;
-use std::path::Path;
-use include_dir::include_dir;
+use std::path::{Path, PathBuf};
+use include_dir::{include_dir, Dir as IncludeDir};
+use crate::article::{ArticleElement, Articles};
+use crate::document::{DocumentElement, Documents};
+use crate::style::{Styles, Symbol};
+use crate::web::SiteSettings;
+
+/// Files that must be present in a vendor directory, relative to its root.
+const VENDORED_FILES: [&str; 3] = [
+    "mathjax/tex-mml-chtml.js",
+    "highlight/highlight.min.js",
+    "highlight/styles/dark.css",
+];
 
 /// Write out all assets that should be included.
 pub fn include_static_assets(root_path: &Path) -> Result<(), String> {
     let static_assets_dir_path = root_path.join("assets/static");
     let assets = include_dir!("assets-include");
-    fs::create_dir_all(&static_assets_dir_path);
+    write_static_asset_dir(&static_assets_dir_path, &assets)
+}
+
+fn write_static_asset_dir(static_assets_dir_path: &Path, assets: &IncludeDir) -> Result<(), String> {
+    let dir_path = static_assets_dir_path.join(assets.path());
+    fs::create_dir_all(&dir_path);
     for asset in assets.files() {
         let asset_path = static_assets_dir_path.join(asset.path());
         let mut file = File::create(&asset_path).unwrap();
@@ -16,9 +33,140 @@ pub fn include_static_assets(root_path: &Path) -> Result<(), String> {
             return Err(format!("Error writing asset file {}.", asset_path.to_str().unwrap()));
         }
     }
+    for subdir in assets.dirs() {
+        write_static_asset_dir(static_assets_dir_path, subdir)?;
+    }
+    Ok(())
+}
+
+/// Copy the vendored front-end libraries to /assets/static/vendor.
+///
+/// The whole vendor directory is copied, since MathJax loads its components and
+/// fonts relative to its own location.
+pub fn include_vendored_assets(root_path: &Path, vendor_path: &Path) -> Result<(), String> {
+    for file in VENDORED_FILES {
+        if !vendor_path.join(file).is_file() {
+            return Err(format!("Vendor directory {} is missing {}.", vendor_path.to_str().unwrap(), file));
+        }
+    }
+    let vendor_out_path = root_path.join("assets/static/vendor");
+    copy_dir(vendor_path, &vendor_out_path)
+}
+
+fn copy_dir(from: &Path, to: &Path) -> Result<(), String> {
+    if let Err(_) = fs::create_dir_all(to) {
+        return Err(format!("Error creating directory {}.", to.to_str().unwrap()));
+    }
+    let entries = match fs::read_dir(from) {
+        Ok(entries) => entries,
+        Err(_) => return Err(format!("Error reading directory {}.", from.to_str().unwrap())),
+    };
+    for entry in entries {
+        let entry = match entry {
+            Ok(entry) => entry,
+            Err(_) => return Err(format!("Error reading directory {}.", from.to_str().unwrap())),
+        };
+        let entry_type = match entry.file_type() {
+            Ok(entry_type) => entry_type,
+            Err(_) => return Err(format!("Error reading directory {}.", from.to_str().unwrap())),
+        };
+        let from_path = entry.path();
+        let to_path = to.join(entry.file_name());
+        if entry_type.is_dir() {
+            copy_dir(&from_path, &to_path)?;
+        } else if entry_type.is_file() {
+            if let Err(_) = fs::copy(&from_path, &to_path) {
+                return Err(format!("Error copying {} to {}.", from_path.to_str().unwrap(), to_path.to_str().unwrap()));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Copy the symbol images of the class styles to /assets/symbols.
+pub fn include_style_symbols(root_path: &Path, styles: &Styles) -> Result<(), String> {
+    let symbol_dir_path = root_path.join("assets/symbols");
+    for style in styles.values() {
+        if let Some(Symbol::Image { source, file_name }) = &style.symbol {
+            if let Err(_) = fs::create_dir_all(&symbol_dir_path) {
+                return Err(format!("Error creating directory {}.", symbol_dir_path.to_str().unwrap()));
+            }
+            let asset_path = symbol_dir_path.join(file_name);
+            if let Err(_) = fs::copy(source, &asset_path) {
+                return Err(format!("Error copying symbol {} to {}.", source.to_str().unwrap(), asset_path.to_str().unwrap()));
+            }
+        }
+    }
     Ok(())
 }
 
-pub fn include_assets(root_path: &Path) -> Result<(), String> {
-    Ok(()) // TODO
+/// Generate the script and stylesheet tags of the MathJax and highlight.js
+/// libraries.
+pub fn generate_library_tags(site: &SiteSettings) -> String {
+    let (mathjax, highlight, highlight_style) = if site.vendored {
+        (
+            site.link("/assets/static/vendor/mathjax/tex-mml-chtml.js"),
+            site.link("/assets/static/vendor/highlight/highlight.min.js"),
+            site.link("/assets/static/vendor/highlight/styles/dark.css"),
+        )
+    } else {
+        (
+            "https://cdn.jsdelivr.net/npm/mathjax@3/es5/tex-mml-chtml.js".to_string(),
+            "https://cdnjs.cloudflare.com/ajax/libs/highlight.js/11.10.0/highlight.min.js".to_string(),
+            "https://cdnjs.cloudflare.com/ajax/libs/highlight.js/11.10.0/styles/dark.css".to_string(),
+        )
+    };
+    let mut html = String::new();
+    html.push_str(&format!(r#"<script id="MathJax-script" async src="{mathjax}"></script>"#));
+    html.push('\n');
+    html.push_str(&format!(r#"<link rel="stylesheet" href="{highlight_style}">"#));
+    html.push('\n');
+    html.push_str(&format!(r#"<script src="{highlight}"></script>"#));
+    html.push('\n');
+    html.push_str("<script>hljs.highlightAll();</script>");
+    html
+}
+
+/// Copy the images used in article and document content to /assets/content, and
+/// the project stylesheets to /assets/user.
+pub fn include_assets(root_path: &Path, articles: &Articles, documents: &Documents, stylesheets: &[PathBuf]) -> Result<(), String> {
+    let content_dir_path = root_path.join("assets/content");
+    if let Err(_) = fs::create_dir_all(&content_dir_path) {
+        return Err(format!("Error creating directory {}.", content_dir_path.to_str().unwrap()));
+    }
+    let mut figures = HashMap::new();
+    for article in articles.get_articles().values() {
+        let article = article.borrow();
+        for element in article.content.iter() {
+            if let ArticleElement::Figure(figure) = element {
+                figures.insert(figure.file_name.clone(), figure.source.clone());
+            }
+        }
+    }
+    for document in documents {
+        for element in document.structure.iter() {
+            if let DocumentElement::Figure(figure) = element {
+                figures.insert(figure.file_name.clone(), figure.source.clone());
+            }
+        }
+    }
+    for (file_name, source) in figures {
+        let asset_path = content_dir_path.join(&file_name);
+        if let Err(_) = fs::copy(&source, &asset_path) {
+            return Err(format!("Error copying image {} to {}.", source.to_str().unwrap(), asset_path.to_str().unwrap()));
+        }
+    }
+    if !stylesheets.is_empty() {
+        let user_dir_path = root_path.join("assets/user");
+        if let Err(_) = fs::create_dir_all(&user_dir_path) {
+            return Err(format!("Error creating directory {}.", user_dir_path.to_str().unwrap()));
+        }
+        for stylesheet in stylesheets {
+            let asset_path = user_dir_path.join(stylesheet.file_name().unwrap());
+            if let Err(_) = fs::copy(stylesheet, &asset_path) {
+                return Err(format!("Error copying stylesheet {} to {}.", stylesheet.to_str().unwrap(), asset_path.to_str().unwrap()));
+            }
+        }
+    }
+    Ok(())
 }
@@ -0,0 +1,25 @@
+use crate::article::Backlinks;
+use crate::locale::Locale;
+use crate::markup::{escape_html, REFERENCE_START};
+
+/// Generate the "Referenced by" section of a class.
+///
+/// Articles are written as reference placeholders, so the HTML must be passed
+/// through `resolve_references` afterwards. Website paths are prefixed with `base`.
+pub(crate) fn generate_backlinks(html: &mut Vec<u8>, backlinks: &Backlinks, locale: &Locale, base: &str) {
+    if backlinks.is_empty() {
+        return;
+    }
+    html.extend_from_slice(format!(r#"<section class="backlinks"><h2>{}</h2><ul>"#, escape_html(locale.get("ReferencedBy"))).as_bytes());
+    for document in backlinks.documents.iter() {
+        let document = document.upgrade().unwrap();
+        html.extend_from_slice(format!(
+            r#"<li><a class="document-link" href="{}{}">{}</a></li>"#,
+            escape_html(base), escape_html(&document.web_path()), escape_html(&document.title),
+        ).as_bytes());
+    }
+    for article_key in backlinks.articles.iter() {
+        html.extend_from_slice(format!("<li>{}{}\"></a></li>", REFERENCE_START, escape_html(article_key)).as_bytes());
+    }
+    html.extend_from_slice(b"</ul></section>");
+}
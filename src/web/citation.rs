@@ -0,0 +1,78 @@
+use std::fs::File;
+use std::io::Write;
+use std::path::Path;
+use crate::bibliography::Bibliography;
+use crate::locale::Locale;
+use crate::markup::{escape_html, find_citations};
+use crate::web::asset::generate_library_tags;
+use crate::web::template::TemplateContext;
+use crate::web::SiteSettings;
+
+/// Generate the list of references cited in content, in order of first citation.
+/// Website paths are prefixed with `base`.
+pub(crate) fn generate_references(html: &mut Vec<u8>, content: &str, locale: &Locale, base: &str) {
+    let mut citations = find_citations(content);
+    if citations.is_empty() {
+        return;
+    }
+    let mut keys = vec![];
+    citations.retain(|citation| {
+        if keys.contains(&citation.key) {
+            false
+        } else {
+            keys.push(citation.key.clone());
+            true
+        }
+    });
+    html.extend_from_slice(format!(r#"<section class="references"><h2>{}</h2><ul>"#, escape_html(locale.get("References"))).as_bytes());
+    for citation in citations {
+        html.extend_from_slice(format!(
+            r#"<li>{} <a class="bib-link" href="{}/bibliography.html#bib-{}">&#8599;</a></li>"#, citation.reference, escape_html(base), escape_html(&citation.key),
+        ).as_bytes());
+    }
+    html.extend_from_slice(b"</ul></section>");
+}
+
+/// Link the citations in HTML to their entries on the bibliography page, whose
+/// path is prefixed with `base`.
+pub(crate) fn link_citations(html: &str, base: &str) -> String {
+    let citations = find_citations(html);
+    if citations.is_empty() {
+        return html.to_string();
+    }
+    let mut linked = String::with_capacity(html.len());
+    let mut offset = 0;
+    for citation in citations {
+        linked.push_str(&html[offset..citation.end]);
+        linked.push_str(&format!(r#" href="{}/bibliography.html#bib-{}""#, escape_html(base), escape_html(&citation.key)));
+        offset = citation.end;
+    }
+    linked.push_str(&html[offset..]);
+    linked
+}
+
+/// Write the project bibliography page.
+pub fn write_bibliography_page(site: &SiteSettings, root_path: &Path, bibliography: &Bibliography) -> Result<(), String> {
+    let path = root_path.join("bibliography.html");
+    let page = generate_bibliography_page(site, bibliography);
+    let mut file = File::create(&path).unwrap();
+    if let Err(_) = file.write_all(page.as_bytes()) {
+        return Err(format!("Error writing to bibliography page {}.", path.to_str().unwrap()));
+    }
+    Ok(())
+}
+
+pub fn generate_bibliography_page(site: &SiteSettings, bibliography: &Bibliography) -> String {
+    let mut context = TemplateContext::new();
+    context.html("HEAD", site.page_head("/bibliography.html"));
+    context.html("LIBRARIES", generate_library_tags(site));
+    let mut entries = vec![];
+    for entry in bibliography.entries.iter() {
+        let mut item = TemplateContext::new();
+        item.text("KEY", &entry.key);
+        item.html("REFERENCE", bibliography.reference_html(&entry.key).unwrap());
+        entries.push(item);
+    }
+    context.list("ENTRIES", entries);
+    site.templates.render("bibliography.html", &context)
+}
@@ -4,10 +4,23 @@ use std::io::Write;
 use std::path::Path;
 use crate::article::{Article, ArticleElement, Articles, Class};
 use serde_json::{Value as JsonValue, Map as JsonMap};
+use crate::locale::Locale;
 use crate::name::NameElement;
+use crate::style::Styles;
+use crate::web::table::generate_table;
+use crate::web::figure::generate_figure;
+use crate::web::reference::resolve_references;
+use crate::web::backlink::generate_backlinks;
+use crate::web::footnote::generate_footnotes;
+use crate::web::citation::generate_references;
+use crate::web::structured::generate_class_json_ld;
+use crate::web::document::generate_prerendered_article;
+use crate::web::asset::generate_library_tags;
+use crate::web::template::TemplateContext;
+use crate::web::SiteSettings;
 
 /// Write class files to the class directory.
-pub(crate) fn write_class_directory(root_path: &Path, classes: &Articles) -> Result<(), String> {
+pub(crate) fn write_class_directory(site: &SiteSettings, styles: &Styles, root_path: &Path, classes: &Articles, resolve_paths: &[String]) -> Result<(), String> {
     let class_dir_path = root_path.join("classes");
     if let Err(_) = fs::create_dir(&class_dir_path) {
         return Err(format!("Error creating class directory {}.", class_dir_path.to_str().unwrap())); // Create the temporary class directory.
@@ -15,18 +28,18 @@ pub(crate) fn write_class_directory(root_path: &Path, classes: &Articles) -> Res
     // Write articles.
     for (_, class) in classes.get_classes().iter() {
         let class = class.borrow();
-        write_class_data_file(&class_dir_path, &class)?;
-        write_class_page_file(&class_dir_path, &class)?;
+        write_class_data_file(site, styles, &class_dir_path, classes, resolve_paths, &class)?;
+        write_class_page_file(site, styles, &class_dir_path, classes, resolve_paths, &class)?;
     }
     Ok(())
 }
 
 /// Write or update a class file.
-fn write_class_data_file(class_dir_path: &Path, class: &Class) -> Result<(), String> {
+fn write_class_data_file(site: &SiteSettings, styles: &Styles, class_dir_path: &Path, articles: &Articles, resolve_paths: &[String], class: &Class) -> Result<(), String> {
     let class_key = class.key.as_ref();
     let class_file_name = format!("{}.json", class_key);
     let class_path = class_dir_path.join(&class_file_name);
-    let class_data = generate_class_json(class)?;
+    let class_data = generate_class_json(site, styles, articles, resolve_paths, class)?;
     let mut file = File::create(&class_path).unwrap();
     if let Err(_) = file.write_all(class_data.as_bytes()) {
         return Err(format!("Error writing to class file {}.", class_path.to_str().unwrap()));
@@ -34,12 +47,20 @@ fn write_class_data_file(class_dir_path: &Path, class: &Class) -> Result<(), Str
     Ok(())
 }
 
-fn write_class_page_file(class_dir_path: &Path, class: &Class) -> Result<(), String> {
-    Ok(()) // TODO
+/// Write the class page, which shows all articles of the class.
+fn write_class_page_file(site: &SiteSettings, styles: &Styles, class_dir_path: &Path, articles: &Articles, resolve_paths: &[String], class: &Class) -> Result<(), String> {
+    let class_path = class_dir_path.join(format!("{}.html", class.key));
+    let class_page = generate_class_page(site, styles, resolve_paths, class)?;
+    let class_page = resolve_references(&class_page, articles, resolve_paths, site.base_path());
+    let mut file = File::create(&class_path).unwrap();
+    if let Err(_) = file.write_all(class_page.as_bytes()) {
+        return Err(format!("Error writing to class page {}.", class_path.to_str().unwrap()));
+    }
+    Ok(())
 }
 
-/// Generate class json, which contains entries "parameters", "style", "articles" and "relations".
-pub fn generate_class_json(class: &Class) -> Result<String, String> {
+/// Generate class json, which contains entries "parameters", "style", "symbol", "articles", "backlinks" and "relations".
+pub fn generate_class_json(site: &SiteSettings, styles: &Styles, articles: &Articles, resolve_paths: &[String], class: &Class) -> Result<String, String> {
     let mut class_json = JsonMap::new();
     // Write parameters.
     if !class.parameters.is_empty() {
@@ -52,6 +73,10 @@ pub fn generate_class_json(class: &Class) -> Result<String, String> {
     // Write style.
     if let Some(style) = &class.style {
         class_json.insert("style".into(), JsonValue::String(style.to_string()));
+        // Write symbol.
+        if let Some(symbol) = styles.get(style.as_ref()).and_then(|style| style.symbol_html(site.base_path())) {
+            class_json.insert("symbol".into(), JsonValue::String(symbol));
+        }
     }
     // Write articles.
     let mut articles_json = JsonMap::new();
@@ -59,10 +84,18 @@ pub fn generate_class_json(class: &Class) -> Result<String, String> {
         let article = article.upgrade().unwrap();
         let article = article.borrow();
         let article_key = article.key.clone();
-        let article_json = generate_article_json(&article);
+        let article_json = generate_article_json(site, articles, resolve_paths, &article);
         articles_json.insert(article_key.to_string(), JsonValue::Object(article_json));
     }
     class_json.insert("articles".into(), JsonValue::Object(articles_json));
+    // Write backlinks.
+    if !class.backlinks.is_empty() {
+        let mut backlinks = vec![];
+        generate_backlinks(&mut backlinks, &class.backlinks, &site.locale, site.base_path());
+        let backlinks = String::from_utf8(backlinks).unwrap();
+        let backlinks = resolve_references(&backlinks, articles, resolve_paths, site.base_path());
+        class_json.insert("backlinks".into(), JsonValue::String(backlinks));
+    }
     // Write relations.
 //    generate_class_relations(class);
     //
@@ -74,7 +107,7 @@ pub fn generate_class_json(class: &Class) -> Result<String, String> {
 }
 
 /// Generate article json, which contains entries "names" and "content".
-fn generate_article_json(article: &Article) -> JsonMap<String, JsonValue> {
+fn generate_article_json(site: &SiteSettings, articles: &Articles, resolve_paths: &[String], article: &Article) -> JsonMap<String, JsonValue> {
     let mut article_json = JsonMap::new();
     // Names
     let mut names_json = vec![];
@@ -83,13 +116,13 @@ fn generate_article_json(article: &Article) -> JsonMap<String, JsonValue> {
         for ne in name {
             let element = match ne {
                 NameElement::Name(name) => {
-                    JsonValue::Array(vec![JsonValue::String(name.0.clone()), JsonValue::String(String::from(""))])
+                    JsonValue::Array(vec![JsonValue::String(name.to_html()), JsonValue::String(String::from(""))])
                 }
                 NameElement::Preposition(markup) => {
-                    JsonValue::String(markup.0.clone())
+                    JsonValue::String(markup.to_html())
                 }
                 NameElement::Parameter { markup, class } => {
-                    JsonValue::Array(vec![JsonValue::String(markup.0.clone()), JsonValue::String(class.to_string())])
+                    JsonValue::Array(vec![JsonValue::String(markup.to_html()), JsonValue::String(class.to_string())])
                 }
             };
             name_json.push(element);
@@ -100,8 +133,9 @@ fn generate_article_json(article: &Article) -> JsonMap<String, JsonValue> {
     // Content
     let article_elements = article.content.as_slice();
     let mut content = vec![];
-    generate_article_content(&mut content, article_elements);
+    generate_article_content(&mut content, article_elements, &article.key, &site.locale, site.base_path());
     let content = String::from_utf8(content).unwrap();
+    let content = resolve_references(&content, articles, resolve_paths, site.base_path());
     article_json.insert("content".into(), JsonValue::String(content)); // TODO: Allow content entry to be Array?
     article_json
 }
@@ -110,18 +144,49 @@ fn generate_article_json(article: &Article) -> JsonMap<String, JsonValue> {
 //
 //}
 
-pub fn generate_class_page(class: &Class) -> Result<String, String> {
-    Ok(String::new()) // TODO
+/// Generate the class page. Reference placeholders are left unresolved.
+pub fn generate_class_page(site: &SiteSettings, styles: &Styles, resolve_paths: &[String], class: &Class) -> Result<String, String> {
+    let mut context = TemplateContext::new();
+    context.text("TITLE", class.key.as_ref());
+    context.html("HEAD", site.page_head(&format!("/classes/{}.html", class.key)));
+    context.html("LIBRARIES", generate_library_tags(site));
+    context.list("NAV", vec![]);
+    context.html("STRUCTURED-DATA", generate_class_json_ld(site, resolve_paths, class));
+    let mut html = vec![];
+    for article in class.articles.iter() {
+        let article = article.upgrade().unwrap();
+        let article = article.borrow();
+        generate_prerendered_article(site, styles, &mut html, &article, resolve_paths, false);
+    }
+    generate_backlinks(&mut html, &class.backlinks, &site.locale, site.base_path());
+    context.html("ARTICLES", String::from_utf8(html).unwrap());
+    Ok(site.templates.render("class.html", &context))
+}
+
+/// Generate article content followed by its footnotes and references. Footnote identifiers
+/// are prefixed with `id_prefix`, and website paths with `base`.
+pub(crate) fn generate_article_content(html: &mut Vec<u8>, content: &[ArticleElement], id_prefix: &str, locale: &Locale, base: &str) {
+    let mut body = vec![];
+    generate_article_elements(&mut body, content, base);
+    let body = String::from_utf8(body).unwrap();
+    generate_footnotes(html, &body, id_prefix);
+    generate_references(html, &body, locale, base);
 }
 
-pub(crate) fn generate_article_content(html: &mut Vec<u8>, content: &[ArticleElement]) {
+fn generate_article_elements(html: &mut Vec<u8>, content: &[ArticleElement], base: &str) {
     for element in content {
         match element {
             ArticleElement::Heading { level, markup } => {
-                html.extend_from_slice(&format!("<h{level}>{}</h{level}>", markup.0.as_str()).as_bytes());
+                html.extend_from_slice(&format!("<h{level}>{}</h{level}>", markup.to_html()).as_bytes());
             }
             ArticleElement::Markup(text) => {
-                html.extend_from_slice(&format!(r#"{}"#, text.0.as_str()).as_bytes());
+                html.extend_from_slice(&format!(r#"{}"#, text.to_html()).as_bytes());
+            }
+            ArticleElement::Table(table) => {
+                generate_table(html, table);
+            }
+            ArticleElement::Figure(figure) => {
+                generate_figure(html, figure, base);
             }
             ArticleElement::LocalSeparator => {
                 html.extend_from_slice("<hr>".as_bytes());
@@ -11,7 +11,6 @@ pub fn generate_class_style_json(styles: &Styles) -> Result<String, String> {
     let mut styles_json = JsonMap::new();
     for (style_key, style) in styles {
         let mut style_json = JsonMap::new();
-        let name = style.name.as_str();
         json_map_set_string(&mut style_json, "name", style.name.as_str());
         if let Some(description) = &style.description {
             json_map_set_string(&mut style_json, "description", description.as_str());
@@ -22,7 +21,7 @@ pub fn generate_class_style_json(styles: &Styles) -> Result<String, String> {
         if let Some(colour) = &style.colour {
             json_map_set_string(&mut style_json, "colour", colour.as_str());
         }
-        styles_json.insert(name.to_string(), JsonValue::Object(style_json));
+        styles_json.insert(style_key.to_string(), JsonValue::Object(style_json));
     }
     match serde_json::to_string(&styles_json) {
         Ok(j) => Ok(j),
@@ -33,8 +32,7 @@ pub fn generate_class_style_json(styles: &Styles) -> Result<String, String> {
 pub fn generate_class_style_css(styles: &Styles) -> Result<String, String> {
     let mut css = String::new();
     for (style_key, style) in styles {
-        let name = style.name.as_str();
-        css.push_str(&format!(".{}-style{{", name));
+        css.push_str(&format!(".{}-style{{", style_key));
         if let Some(colour) = &style.colour {
             css.push_str(&format!("--type-colour:{};", colour.as_str()));
         } else {
@@ -8,10 +8,13 @@ use crate::compile::project::ResolutionPaths;
 use crate::dir::Dir;
 use crate::document::{Documents};
 use crate::style::Styles;
+use crate::web::asset::generate_library_tags;
 use crate::web::document::{write_document, write_documents};
+use crate::web::template::TemplateContext;
+use crate::web::SiteSettings;
 
 pub fn write_dir_indexes(
-    styles: &Styles, resolve_paths: &ResolutionPaths, articles: &Articles,
+    site: &SiteSettings, styles: &Styles, resolve_paths: &ResolutionPaths, articles: &Articles,
     parent_path: &Path, web_parent_path: &Path,
     documents: &Documents, tree: &Rc<Dir>
 ) -> Result<(), String> {
@@ -21,14 +24,14 @@ pub fn write_dir_indexes(
     let dir_path = parent_path.join(file_name);
     let web_dir_path = web_parent_path.join(file_name);
 
-    write_dir_index(dir_path.as_path(), web_dir_path.as_path(), &tree)?;
+    write_dir_index(site, dir_path.as_path(), web_dir_path.as_path(), &tree)?;
 
     for subtree in &tree.subdirs {
-        write_dir_indexes(styles, resolve_paths, articles, dir_path.as_path(), web_dir_path.as_path(), documents, subtree)?
+        write_dir_indexes(site, styles, resolve_paths, articles, dir_path.as_path(), web_dir_path.as_path(), documents, subtree)?
     }
 
     for document in &tree.subdocs {
-        write_document(styles, resolve_paths, articles, dir_path.as_path(), document)?;
+        write_document(site, styles, resolve_paths, articles, dir_path.as_path(), document)?;
     }
 
     //let doc_out_dir = dir_path.join(tree.file_name.as_os_str());
@@ -65,7 +68,7 @@ pub fn write_dir_indexes(
     Ok(())
 }
 
-fn write_dir_index(path: &Path, web_path: &Path, dir: &Rc<Dir>) -> Result<(), String> {
+fn write_dir_index(site: &SiteSettings, path: &Path, web_path: &Path, dir: &Rc<Dir>) -> Result<(), String> {
 
     let mut html = String::new();
 
@@ -73,6 +76,7 @@ fn write_dir_index(path: &Path, web_path: &Path, dir: &Rc<Dir>) -> Result<(), St
         fs::create_dir(&path).unwrap();
     }
 
+    let base = site.base_path();
     let name = dir.name.as_str();
     html.push_str(format!("<h1><span>{name}</span></h1>").as_str());
     html.push_str("<ul>");
@@ -87,17 +91,17 @@ fn write_dir_index(path: &Path, web_path: &Path, dir: &Rc<Dir>) -> Result<(), St
         };
         let file_name = format!("{}.html", file_name);
         let web_file_path = web_path.join(file_name);
-        html.push_str(format!(r#"<li><a href="{}">{}</a></li>"#, web_file_path.to_str().unwrap(), document.title).as_str());
+        html.push_str(format!(r#"<li><a href="{}{}">{}</a></li>"#, base, web_file_path.to_str().unwrap(), document.title).as_str());
     }
     html.push_str("</ul>");
 
     for subdir in &dir.subdirs {
         let path = path.join(subdir.file_name.as_os_str());
         let web_path = web_path.join(subdir.file_name.as_os_str());
-        write_dir_index_inner(&mut html, path.as_path(), web_path.as_path(), subdir, 2)?;
+        write_dir_index_inner(&mut html, base, path.as_path(), web_path.as_path(), subdir, 2)?;
     }
 
-    let html = generate_dir_page(dir.clone(), html.as_str())?;
+    let html = generate_dir_page(site, dir.clone(), &format!("{}/", web_path.to_str().unwrap()), html.as_str())?;
 
     let index_path = path.join("index.html");
 
@@ -112,7 +116,7 @@ fn write_dir_index(path: &Path, web_path: &Path, dir: &Rc<Dir>) -> Result<(), St
     Ok(())
 }
 
-fn write_dir_index_inner(html: &mut String, path: &Path, web_path: &Path, subtree: &Rc<Dir>, level: usize) -> Result<(), String> {
+fn write_dir_index_inner(html: &mut String, base: &str, path: &Path, web_path: &Path, subtree: &Rc<Dir>, level: usize) -> Result<(), String> {
     if level > 6 {
         return Err(format!("Nesting too deep."));
     }
@@ -135,47 +139,39 @@ fn write_dir_index_inner(html: &mut String, path: &Path, web_path: &Path, subtre
         };
         let file_name = format!("{}.html", file_name);
         let web_file_path = web_path.join(file_name);
-        html.push_str(format!(r#"<li><a href="{}">{}</a></li>"#, web_file_path.to_str().unwrap(), document.title).as_str());
+        html.push_str(format!(r#"<li><a href="{}{}">{}</a></li>"#, base, web_file_path.to_str().unwrap(), document.title).as_str());
     }
     html.push_str("</ul>");
 
     for subdir in &subtree.subdirs {
         let path = path.join(subdir.file_name.as_os_str());
         let web_path = web_path.join(subdir.file_name.as_os_str());
-        write_dir_index_inner(html, path.as_path(), web_path.as_path(), subdir, level + 1)?;
+        write_dir_index_inner(html, base, path.as_path(), web_path.as_path(), subdir, level + 1)?;
     }
 
     Ok(())
 }
 
-fn generate_dir_page(dir: Rc<Dir>, content: &str) -> Result<String, String> {
-    let mut html = vec![];
-    let mut template = include_str!("../../templates/dirpage.html").as_bytes();
-    while template.len() > 0 {
-        if template.starts_with(b"{TITLE}") {
-            let title = dir.name.as_str();
-            html.extend_from_slice(title.as_bytes());
-            template = &template[7..];
-        } else if template.starts_with(b"{NAV}") { // TODO: Replace w Nav path
-            //let mut path = String::from("/documents");
-            let mut path = String::from("");
-            let mut dirtrail = dir.dirtrail(); // TODO Do not include start node in dir trail.
-            dirtrail.pop();
-            for dir in dirtrail {
-                path.push('/');
-                path.push_str(&dir.file_name.to_str().unwrap());
-                let crumb = dir.name.as_str();
-                let link = format!(r#"<a href="{}">{}</a>"#, &path, crumb);
-                html.extend_from_slice(link.as_bytes());
-            }
-            template = &template[5..];
-        } else if template.starts_with(b"{CONTENT}") {
-            html.extend_from_slice(content.as_bytes());
-            template = &template[9..];
-        } else {
-            html.push(template[0]);
-            template = &template[1..];
-        }
+/// Generate the page of a directory at the website path `page_path`.
+fn generate_dir_page(site: &SiteSettings, dir: Rc<Dir>, page_path: &str, content: &str) -> Result<String, String> {
+    let mut context = TemplateContext::new();
+    context.text("TITLE", dir.name.as_str());
+    context.html("HEAD", site.page_head(page_path));
+    context.html("LIBRARIES", generate_library_tags(site));
+    let mut nav = vec![];
+    //let mut path = String::from("/documents");
+    let mut path = site.link("");
+    let mut dirtrail = dir.dirtrail(); // TODO Do not include start node in dir trail.
+    dirtrail.pop();
+    for dir in dirtrail {
+        path.push('/');
+        path.push_str(&dir.file_name.to_str().unwrap());
+        let mut crumb = TemplateContext::new();
+        crumb.text("HREF", &path);
+        crumb.text("NAME", dir.name.as_str());
+        nav.push(crumb);
     }
-    Ok(String::from_utf8(html).unwrap())
+    context.list("NAV", nav);
+    context.html("CONTENT", content);
+    Ok(site.templates.render("dirpage.html", &context))
 }
@@ -10,18 +10,28 @@ use crate::document::{Document, DocumentElement, PanelElement};
 use crate::markup::Markup;
 use crate::name::{Name, NameElement};
 use crate::style::Styles;
+use crate::web::asset::generate_library_tags;
 use crate::web::class::generate_article_content;
+use crate::web::table::generate_table;
+use crate::web::figure::generate_figure;
+use crate::web::reference::resolve_references;
+use crate::web::backlink::generate_backlinks;
+use crate::web::footnote::generate_footnotes;
+use crate::web::citation::generate_references;
+use crate::web::structured::generate_document_json_ld;
+use crate::web::template::TemplateContext;
+use crate::web::SiteSettings;
 
-pub fn write_documents(styles: &Styles, resolve_paths: &ResolutionPaths, articles: &Articles, web_path: &Path, documents: &[Rc<Document>]) -> Result<(), String> {
+pub fn write_documents(site: &SiteSettings, styles: &Styles, resolve_paths: &ResolutionPaths, articles: &Articles, web_path: &Path, documents: &[Rc<Document>]) -> Result<(), String> {
     let document_dir_path = web_path.join("documents");
     fs::create_dir(&document_dir_path); // Create the documents directory.
     for document in documents {
-        write_document(styles, resolve_paths, articles, &document_dir_path, document)?;
+        write_document(site, styles, resolve_paths, articles, &document_dir_path, document)?;
     }
     Ok(())
 }
 
-pub fn write_document(styles: &Styles, resolve_paths: &ResolutionPaths, articles: &Articles, document_dir_path: &Path, document: &Document) -> Result<(), String> {
+pub fn write_document(site: &SiteSettings, styles: &Styles, resolve_paths: &ResolutionPaths, articles: &Articles, document_dir_path: &Path, document: &Document) -> Result<(), String> {
     let mut document_path = document_dir_path.to_path_buf();
     let file_name = document.file_name.to_str().unwrap();
 
@@ -34,7 +44,8 @@ pub fn write_document(styles: &Styles, resolve_paths: &ResolutionPaths, articles
     };
     let file_name = format!("{}.html", file_name);
     document_path.push(file_name);
-    let document_page = generate_document_page(styles, resolve_paths, articles, document)?;
+    let document_page = generate_document_page(site, styles, resolve_paths, articles, document)?;
+    let document_page = resolve_references(&document_page, articles, resolve_paths, site.base_path());
     let mut file = File::create(&document_path).unwrap();
     file.write_all(document_page.as_bytes()).unwrap();
     Ok(())
@@ -42,79 +53,81 @@ pub fn write_document(styles: &Styles, resolve_paths: &ResolutionPaths, articles
 
 
 
-pub fn generate_document_page(styles: &Styles, resolve_paths: &ResolutionPaths, articles: &Articles, document: &Document) -> Result<String, String> {
-    let mut html = vec![];
-    let mut template = include_str!("../../templates/template.html").as_bytes();
-    while template.len() > 0 {
-        if template.starts_with(b"{TITLE}") {
-            let title = &document.title.as_bytes();
-            html.extend_from_slice(title);
-            template = &template[7..];
-        } else if template.starts_with(b"{NAV}") { // TODO: Replace w Nav path
-            let mut path = String::from("/documents");
-            let mut dirtrail = document.dirtrail();
-            dirtrail.remove(0); // TODO Because doc root repeats
-            for dir in dirtrail {
-                path.push('/');
-                path.push_str(&dir.file_name.to_str().unwrap());
-                let crumb = dir.name.as_str();
-                let link = format!(r#"<a href="{}">{}</a>"#, &path, crumb);
-                html.extend_from_slice(link.as_bytes());
-            }
-            template = &template[5..];
-        } else if template.starts_with(b"{RESOLUTION-PATHS}") {
-            let mut paths = Vec::new();
-            for path in &document.resolution_paths {
-                paths.push(serde_json::Value::String(path.to_string()));
-            }
-            let json = serde_json::to_string(&paths).unwrap();
-            html.extend_from_slice(json.as_bytes());
-            template = &template[18..];
-        } else if template.starts_with(b"{OVERVIEW}") {
-            generate_overview_tab_content(styles, resolve_paths, articles, &mut html, document.structure.as_slice());
-            template = &template[10..];
-        } else if template.starts_with(b"{DETAILS}") {
-            generate_details_tab(resolve_paths, &articles, &mut html, document.structure.as_slice(), document.resolution_paths.as_slice());
-            template = &template[9..];
-        } else {
-            html.push(template[0]);
-            template = &template[1..];
-        }
+pub fn generate_document_page(site: &SiteSettings, styles: &Styles, resolve_paths: &ResolutionPaths, articles: &Articles, document: &Document) -> Result<String, String> {
+    let mut context = TemplateContext::new();
+    context.text("TITLE", &document.title);
+    context.html("HEAD", site.page_head(&document.web_path()));
+    context.html("LIBRARIES", generate_library_tags(site));
+    let mut nav = vec![];
+    let mut path = site.link("/documents");
+    let mut dirtrail = document.dirtrail();
+    dirtrail.remove(0); // TODO Because doc root repeats
+    for dir in dirtrail {
+        path.push('/');
+        path.push_str(&dir.file_name.to_str().unwrap());
+        let mut crumb = TemplateContext::new();
+        crumb.text("HREF", &path);
+        crumb.text("NAME", &dir.name);
+        nav.push(crumb);
     }
-    Ok(String::from_utf8(html).unwrap())
+    context.list("NAV", nav);
+    context.html("STRUCTURED-DATA", generate_document_json_ld(site, articles, resolve_paths, document));
+    context.text("BODY-CLASS", if site.margin_notes { "margin-notes" } else { "" });
+    let mut paths = Vec::new();
+    for path in &document.resolution_paths {
+        paths.push(serde_json::Value::String(path.to_string()));
+    }
+    context.html("RESOLUTION-PATHS", serde_json::to_string(&paths).unwrap());
+    let mut overview = vec![];
+    generate_overview_tab_content(site, styles, resolve_paths, articles, &mut overview, document.structure.as_slice());
+    let overview = String::from_utf8(overview).unwrap();
+    let mut overview_html = vec![];
+    generate_footnotes(&mut overview_html, &overview, &document.key);
+    generate_references(&mut overview_html, &overview, &site.locale, site.base_path());
+    context.html("OVERVIEW", String::from_utf8(overview_html).unwrap());
+    let mut details = vec![];
+    generate_details_tab(site, styles, resolve_paths, &articles, &mut details, document.structure.as_slice(), document.resolution_paths.as_slice());
+    context.html("DETAILS", String::from_utf8(details).unwrap());
+    Ok(site.templates.render("template.html", &context))
 }
 
 /// Generate overview tab content.
-fn generate_overview_tab_content(styles: &Styles, resolve_paths: &ResolutionPaths, articles: &Articles, html: &mut Vec<u8>, document_elements: &[DocumentElement]) {
+fn generate_overview_tab_content(site: &SiteSettings, styles: &Styles, resolve_paths: &ResolutionPaths, articles: &Articles, html: &mut Vec<u8>, document_elements: &[DocumentElement]) {
     for element in document_elements {
         match element {
             DocumentElement::Heading { level, heading, index } => {
                 if let Some(index) = index {
-                    html.extend_from_slice(&format!(r#"<h{level}><span>{index}</span> <span>{}</span></h{level}>"#, &heading.0).as_bytes());
+                    html.extend_from_slice(&format!(r#"<h{level}><span>{index}</span> <span>{}</span></h{level}>"#, heading.to_html()).as_bytes());
                 } else {
-                    html.extend_from_slice(&format!(r#"<h{level}><span>{}</span></h{level}>"#, &heading.0).as_bytes());
+                    html.extend_from_slice(&format!(r#"<h{level}><span>{}</span></h{level}>"#, heading.to_html()).as_bytes());
                 }
             }
             DocumentElement::Paragraph(text) => {
-                html.extend_from_slice(&format!(r#"<p>{}</p>"#, &text.0).as_bytes());
+                html.extend_from_slice(&format!(r#"<p>{}</p>"#, text.to_html()).as_bytes());
+            }
+            DocumentElement::Table(table) => {
+                generate_table(html, table);
+            }
+            DocumentElement::Figure(figure) => {
+                generate_figure(html, figure, site.base_path());
             }
             DocumentElement::Panel(article_elements) => {
-                generate_links_panel(styles, resolve_paths, articles, html, article_elements);
+                generate_links_panel(site, styles, resolve_paths, articles, html, article_elements);
             }
         }
     }
 }
 
 /// Generate a label panel.
-fn generate_links_panel(styles: &Styles, resolve_paths: &ResolutionPaths, articles: &Articles, html: &mut Vec<u8>, elements: &[PanelElement]) {
+fn generate_links_panel(site: &SiteSettings, styles: &Styles, resolve_paths: &ResolutionPaths, articles: &Articles, html: &mut Vec<u8>, elements: &[PanelElement]) {
     html.extend_from_slice(br#"<div class="links">"#);
     for element in elements {
         match element {
             PanelElement::Heading { level, heading, index } => {
                 if let Some(index) = index {
-                    html.extend_from_slice(format!(r#"<hr><h{level}><span>{index}</span> <span>{}</span></h{level}>"#, heading.0).as_bytes());
+                    html.extend_from_slice(format!(r#"<hr><h{level}><span>{index}</span> <span>{}</span></h{level}>"#, heading.to_html()).as_bytes());
                 } else {
-                    html.extend_from_slice(format!(r#"<hr><h{level}><span>{}</span></h{level}>"#, heading.0).as_bytes());
+                    html.extend_from_slice(format!(r#"<hr><h{level}><span>{}</span></h{level}>"#, heading.to_html()).as_bytes());
                 }
             }
             PanelElement::ArticleLink { key, index } => {
@@ -124,7 +137,7 @@ fn generate_links_panel(styles: &Styles, resolve_paths: &ResolutionPaths, articl
                     eprintln!("Could not find article with key {}", key);
                     panic!();
                 };
-                generate_article_link(styles, html, &article.borrow(), index.as_ref());
+                generate_article_link(site, styles, html, &article.borrow(), index.as_ref());
             }
             PanelElement::ClassLink { key, index } => {
                 let class = match articles.class_map.get(key) {
@@ -136,7 +149,7 @@ fn generate_links_panel(styles: &Styles, resolve_paths: &ResolutionPaths, articl
                 };
                 let class = class.borrow();
                 let article = class.resolve(resolve_paths);
-                generate_article_link(styles, html, &article.borrow(), index.as_ref()); // TODO Send class key or resolve to article key?
+                generate_article_link(site, styles, html, &article.borrow(), index.as_ref()); // TODO Send class key or resolve to article key?
             }
         }
     }
@@ -144,9 +157,7 @@ fn generate_links_panel(styles: &Styles, resolve_paths: &ResolutionPaths, articl
 }
 
 /// Generate an article label. // TODO: Get rid of progress box, generate in JS
-/// // TODO: Allow symbol instead of abbr.
-fn generate_article_link(styles: &Styles, html: &mut Vec<u8>, article: &Article, index: Option<&String>) {
-    let mut template = include_str!("../../templates/article-link.html").as_bytes();
+fn generate_article_link(site: &SiteSettings, styles: &Styles, html: &mut Vec<u8>, article: &Article, index: Option<&String>) {
     let class = article.class.upgrade().unwrap();
     let class = class.borrow();
     let style = if let Some(style) = &class.style {
@@ -158,80 +169,50 @@ fn generate_article_link(styles: &Styles, html: &mut Vec<u8>, article: &Article,
     } else {
         None
     };
-    while template.len() > 0 {
-        if template.starts_with(b"{STYLE}") {
-            if let Some(style) = style {
-                let name = style.name.as_bytes();
-                html.extend_from_slice(name);
-                html.extend_from_slice(b"-style");
-            }
-            template = &template[7..];
-        } else if template.starts_with(b"{ARTICLE}") {
-            let article_key = article.key.as_bytes();
-            html.extend_from_slice(article_key);
-            template = &template[9..];
-        } else if template.starts_with(b"{ABBREVIATION}") {
-            if let Some(style) = style {
-                if let Some(abbreviation) = &style.abbreviation { // Generate abbreviation if it is specified.
-                    generate_article_link_abbreviation(html, abbreviation);
-                }
-            }
-            template = &template[14..];
-        } else if template.starts_with(b"{NAME}") {
-            let name = &article.names.first().unwrap(); // TODO: Check name exists
-            for ne in name.iter() {
-                match ne {
-                    NameElement::Name(m) => {
-                        write_markup(html ,m); // TODO WRITE NAME WITH <strong>
-                    }
-                    _ => {},
-                }
-            }
-            template = &template[6..];
-        } else if template.starts_with(b"{INDEX}") {
-            if let Some(index) = index {
-                generate_article_link_index(html, index.as_ref());
+    let mut context = TemplateContext::new();
+    context.text("STYLE", style.map(|style| format!("{}-style", style.key)).unwrap_or_default());
+    context.text("ARTICLE", article.key.as_ref());
+    let mut abbreviation = String::new();
+    if let Some(style) = style {
+        if let Some(text) = &style.abbreviation { // Generate abbreviation if it is specified.
+            abbreviation = generate_text_template(site, "article-link-abbreviation.html", text);
+        }
+    }
+    context.html("ABBREVIATION", abbreviation);
+    let mut symbol = String::new();
+    if let Some(symbol_html) = style.and_then(|style| style.symbol_html(site.base_path())) {
+        let mut symbol_context = TemplateContext::new();
+        symbol_context.html("SYMBOL", symbol_html);
+        symbol = site.templates.render("article-link-symbol.html", &symbol_context);
+    }
+    context.html("SYMBOL", symbol);
+    let mut name = vec![];
+    for ne in article.names.first().unwrap().iter() { // TODO: Check name exists
+        match ne {
+            NameElement::Name(m) => {
+                write_markup(&mut name, m); // TODO WRITE NAME WITH <strong>
             }
-            template = &template[7..];
-        } else {
-            html.push(template[0]);
-            template = &template[1..];
+            _ => {},
         }
     }
+    context.html("NAME", String::from_utf8(name).unwrap());
+    context.html("INDEX", index.map(|index| generate_text_template(site, "article-link-index.html", index)).unwrap_or_default());
+    html.extend_from_slice(site.templates.render("article-link.html", &context).as_bytes());
 }
 
 fn write_markup(html: &mut Vec<u8>, markup: &Markup) {
-    html.extend_from_slice(markup.0.as_bytes());
+    html.extend_from_slice(markup.to_html().as_bytes());
 }
 
-fn generate_article_link_abbreviation(html: &mut Vec<u8>, abbreviation: &str) {
-    let mut template = include_str!("../../templates/article-link-abbreviation.html").as_bytes();
-    while template.len() > 0 {
-        if template.starts_with(b"{TEXT}") {
-            html.extend_from_slice(abbreviation.as_bytes());
-            template = &template[6..];
-        } else {
-            html.push(template[0]);
-            template = &template[1..];
-        }
-    }
-}
-
-fn generate_article_link_index(html: &mut Vec<u8>, index: &str) {
-    let mut template = include_str!("../../templates/article-link-index.html").as_bytes();
-    while template.len() > 0 {
-        if template.starts_with(b"{TEXT}") {
-            html.extend_from_slice(index.as_bytes());
-            template = &template[6..];
-        } else {
-            html.push(template[0]);
-            template = &template[1..];
-        }
-    }
+/// Render a template whose only placeholder is `{TEXT}`.
+fn generate_text_template(site: &SiteSettings, template: &str, text: &str) -> String {
+    let mut context = TemplateContext::new();
+    context.text("TEXT", text);
+    site.templates.render(template, &context)
 }
 
 /// Generate the articles in the details tab.
-fn generate_details_tab(resolve_paths: &ResolutionPaths, articles: &Articles, html: &mut Vec<u8>, document_elements: &[DocumentElement], resolution_paths: &[String]) {
+fn generate_details_tab(site: &SiteSettings, styles: &Styles, resolve_paths: &ResolutionPaths, articles: &Articles, html: &mut Vec<u8>, document_elements: &[DocumentElement], resolution_paths: &[String]) {
     for element in document_elements {
         if let DocumentElement::Panel(elements) = element {
             for element in elements {
@@ -239,14 +220,14 @@ fn generate_details_tab(resolve_paths: &ResolutionPaths, articles: &Articles, ht
                     PanelElement::ArticleLink { key, index } => {
                         let article = articles.article_map.get(key).unwrap();
                         let article = article.borrow();
-                        generate_prerendered_article(html, &article, resolution_paths);
+                        generate_prerendered_article(site, styles, html, &article, resolution_paths, true);
                     }
                     PanelElement::ClassLink { key, index } => {
                         let class = articles.class_map.get(key).unwrap();
                         let class = class.borrow();
                         let article = class.resolve(resolve_paths);
                         let article = article.borrow();
-                        generate_prerendered_article(html, &article, resolution_paths);
+                        generate_prerendered_article(site, styles, html, &article, resolution_paths, true);
                     }
                     _ => {}
                 }
@@ -255,67 +236,40 @@ fn generate_details_tab(resolve_paths: &ResolutionPaths, articles: &Articles, ht
     }
 }
 
-/// Generate a prerendered article.
+/// Generate a prerendered article, optionally followed by the backlinks of its class.
 ///
 /// These are the articles embedded into the initial HTML article files and
 /// which are not generated by JavaScript.
-fn generate_prerendered_article(html: &mut Vec<u8>, article: &Article, resolution_paths: &[String]) {
-    let template = include_str!("../../templates/article-preload.html");
-    let mut template = template.as_bytes();
+pub(crate) fn generate_prerendered_article(site: &SiteSettings, styles: &Styles, html: &mut Vec<u8>, article: &Article, resolution_paths: &[String], backlinks: bool) {
     let class = &article.class.upgrade().unwrap();
     let class = class.borrow();
     let class= class.deref();
-    while template.len() != 0 {
-        if template.starts_with(b"{ARTICLE}") {
-            let article_key = &article.key;
-            let article_key = article_key.as_bytes();
-            html.extend_from_slice(article_key);
-            template = &template[9..];
-        } else if template.starts_with(b"{CLASS}") {
-            let class_key = class.key.as_bytes();
-            html.extend_from_slice(class_key);
-            template = &template[7..];
-        } else if template.starts_with(b"{STYLE}") {
-            if let Some(style) = &class.style {
-                let style = style.as_bytes();
-                html.extend_from_slice(style);
-                html.extend_from_slice(b"-style");
-            }
-            template = &template[7..];
-        } else if template.starts_with(b"{NAMES}") {
-
-            let primary_name = &article.names[0];
-            html.extend_from_slice(b"<h1>");
-            make_long_name(html, primary_name);
-            html.extend_from_slice(b"</h1>");
-            //let mut i = 1;
-            //while i < name.names.len() {
-            //    let name = name.names[i].get_full_html();
-            //    let name = name.as_str();
-            //    html.extend_from_slice(b"<p>");
-            //    html.extend_from_slice(name.as_bytes());
-            //    html.extend_from_slice(b"</p>");
-            //    i += 1;
-            //}
-            template = &template[7..];
-        } else if template.starts_with(b"{CONTENT}") {
-            generate_article_content(html, article.content.as_slice());
-            template = &template[9..];
-        } else if template.starts_with(b"{CLASS_LINK}") {
-            let class_key = class.key.as_ref();
-            html.extend_from_slice(format!("/classes/{class_key}.html").as_bytes());
-            template = &template[12..];
-        } else if template.starts_with(b"{LINKS}") {
-            //generate_article_links(html, class, resolution_paths);///////////////////////////////////////////////////
-            template = &template[7..];
-        } else {
-            html.push(template[0]);
-            template = &template[1..];
-        }
+    let mut context = TemplateContext::new();
+    context.text("ARTICLE", article.key.as_ref());
+    context.text("CLASS", class.key.as_ref());
+    context.text("STYLE", class.style.as_ref().map(|style| format!("{}-style", style)).unwrap_or_default());
+    context.text("TYPE", class.style.as_ref().map(|style| style.to_string()).unwrap_or_default());
+    let symbol = class.style.as_ref().and_then(|style| styles.get(style.as_ref())).and_then(|style| style.symbol_html(site.base_path()));
+    context.html("SYMBOL", symbol.unwrap_or_default());
+    let mut names = vec![];
+    let primary_name = &article.names[0];
+    names.extend_from_slice(b"<h1>");
+    make_long_name(&mut names, primary_name);
+    names.extend_from_slice(b"</h1>");
+    context.html("NAMES", String::from_utf8(names).unwrap());
+    let mut content = vec![];
+    generate_article_content(&mut content, article.content.as_slice(), &article.key, &site.locale, site.base_path());
+    context.html("CONTENT", String::from_utf8(content).unwrap());
+    let mut backlinks_html = vec![];
+    if backlinks {
+        generate_backlinks(&mut backlinks_html, &class.backlinks, &site.locale, site.base_path());
     }
+    context.html("BACKLINKS", String::from_utf8(backlinks_html).unwrap());
+    context.html("LINKS", ""); //generate_article_links(html, class, resolution_paths);
+    html.extend_from_slice(site.templates.render("article-preload.html", &context).as_bytes());
 }
 
-fn make_long_name(html: &mut Vec<u8>, name: &Name) {
+pub(crate) fn make_long_name(html: &mut Vec<u8>, name: &Name) {
     for ne in name {
         match ne {
             NameElement::Name(n) => {
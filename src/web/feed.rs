@@ -0,0 +1,87 @@
+use std::fs::File;
+use std::io::Write;
+use std::path::Path;
+use std::time::SystemTime;
+use crate::article::Articles;
+use crate::document::{Document, Documents};
+use crate::file::format_timestamp;
+use crate::markup::{escape_html, strip_markup};
+use crate::name::NameElement;
+use crate::web::SiteSettings;
+
+/// Write atom.xml with an entry for each document, most recently changed
+/// first. Requires the base URL, since feed entries need absolute URLs.
+///
+/// The change date of a document is the modification time of its file.
+pub fn write_feed(site: &SiteSettings, root_path: &Path, articles: &Articles, documents: &Documents) -> Result<(), String> {
+    let feed_url = match site.url("/atom.xml") {
+        Some(url) => url,
+        None => return Ok(()),
+    };
+    let mut entries: Vec<(SystemTime, &Document)> = documents.iter()
+        .map(|document| (document.modified().unwrap_or(SystemTime::UNIX_EPOCH), document.as_ref()))
+        .collect();
+    entries.sort_by(|a, b| b.0.cmp(&a.0).then_with(|| a.1.key.cmp(&b.1.key)));
+    let updated = entries.first().map(|(modified, _)| *modified).unwrap_or(SystemTime::UNIX_EPOCH);
+    let mut xml = String::new();
+    xml.push_str("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    xml.push_str("<feed xmlns=\"http://www.w3.org/2005/Atom\">\n");
+    xml.push_str(&format!("  <title>{}</title>\n", escape_html(&site.title)));
+    if let Some(description) = &site.description {
+        xml.push_str(&format!("  <subtitle>{}</subtitle>\n", escape_html(description)));
+    }
+    xml.push_str(&format!("  <id>{}</id>\n", escape_html(&feed_url)));
+    xml.push_str(&format!("  <link rel=\"self\" href=\"{}\"/>\n", escape_html(&feed_url)));
+    xml.push_str(&format!("  <link href=\"{}\"/>\n", escape_html(&site.url("/").unwrap())));
+    xml.push_str(&format!("  <updated>{}</updated>\n", format_timestamp(updated)));
+    for (modified, document) in entries {
+        let url = site.url(&document.web_path()).unwrap();
+        xml.push_str("  <entry>\n");
+        xml.push_str(&format!("    <title>{}</title>\n", escape_html(&document.title)));
+        xml.push_str(&format!("    <id>{}</id>\n", escape_html(&url)));
+        xml.push_str(&format!("    <link href=\"{}\"/>\n", escape_html(&url)));
+        xml.push_str(&format!("    <updated>{}</updated>\n", format_timestamp(modified)));
+        for author in document.authors.iter() {
+            xml.push_str(&format!("    <author><name>{}</name></author>\n", escape_html(author)));
+        }
+        let mut summary = String::new();
+        if let Some(description) = &document.description {
+            summary.push_str(description);
+        }
+        let defined = defined_article_names(articles, document);
+        if !defined.is_empty() {
+            if !summary.is_empty() {
+                summary.push(' ');
+            }
+            summary.push_str(&format!("Defines {}.", defined.join(", ")));
+        }
+        if !summary.is_empty() {
+            xml.push_str(&format!("    <summary>{}</summary>\n", escape_html(&summary)));
+        }
+        xml.push_str("  </entry>\n");
+    }
+    xml.push_str("</feed>\n");
+    let path = root_path.join("atom.xml");
+    let mut file = File::create(&path).unwrap();
+    if let Err(_) = file.write_all(xml.as_bytes()) {
+        return Err(format!("Error writing to feed {}.", path.to_str().unwrap()));
+    }
+    Ok(())
+}
+
+/// Names of the articles defined in a document, in key order.
+fn defined_article_names(articles: &Articles, document: &Document) -> Vec<String> {
+    let suffix = format!("@{}", document.key);
+    let mut keys: Vec<_> = articles.get_articles().keys().filter(|key| key.ends_with(&suffix)).collect();
+    keys.sort();
+    keys.iter().map(|key| {
+        let article = articles.get_article(key).unwrap().borrow();
+        let mut name = String::new();
+        for element in article.names.first().unwrap().iter() {
+            if let NameElement::Name(markup) = element {
+                name.push_str(&strip_markup(&markup.0));
+            }
+        }
+        name.trim().to_string()
+    }).collect()
+}
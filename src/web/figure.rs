@@ -0,0 +1,14 @@
+use crate::figure::Figure;
+use crate::markup::escape_html;
+
+/// Generate the HTML of an image, with a caption if it has one. Website paths
+/// are prefixed with `base`.
+pub(crate) fn generate_figure(html: &mut Vec<u8>, figure: &Figure, base: &str) {
+    let image = format!(r#"<img src="{}/assets/content/{}" alt="{}">"#, escape_html(base), figure.file_name, escape_html(&figure.alt));
+    html.extend_from_slice(b"<figure>");
+    html.extend_from_slice(image.as_bytes());
+    if let Some(caption) = &figure.caption {
+        html.extend_from_slice(format!("<figcaption>{}</figcaption>", caption.to_html()).as_bytes());
+    }
+    html.extend_from_slice(b"</figure>");
+}
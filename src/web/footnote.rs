@@ -0,0 +1,40 @@
+use crate::markup::{escape_html, FOOTNOTE_END, FOOTNOTE_START};
+
+/// Write content with its footnotes numbered, followed by the list of footnotes.
+///
+/// Each footnote is replaced by a numbered link to its entry in the list and a
+/// margin note, which is only shown when the page uses margin notes. The list
+/// entries link back to the footnote. Identifiers are prefixed with `id_prefix`
+/// to keep them unique within a page.
+pub(crate) fn generate_footnotes(html: &mut Vec<u8>, content: &str, id_prefix: &str) {
+    let id_prefix = escape_html(id_prefix);
+    let mut notes = vec![];
+    let mut rest = content;
+    while let Some(start) = rest.find(FOOTNOTE_START) {
+        let note_start = start + FOOTNOTE_START.len();
+        let note_end = match rest[note_start..].find(FOOTNOTE_END) {
+            Some(i) => note_start + i,
+            None => break,
+        };
+        html.extend_from_slice(rest[..start].as_bytes());
+        let note = &rest[note_start..note_end];
+        notes.push(note);
+        let n = notes.len();
+        html.extend_from_slice(format!(
+            r##"<sup class="fn-ref" id="fnref-{id_prefix}-{n}"><a href="#fn-{id_prefix}-{n}">{n}</a></sup><span class="margin-note"><sup>{n}</sup> {note}</span>"##,
+        ).as_bytes());
+        rest = &rest[note_end + FOOTNOTE_END.len()..];
+    }
+    html.extend_from_slice(rest.as_bytes());
+    if notes.is_empty() {
+        return;
+    }
+    html.extend_from_slice(b"<ol class=\"footnotes\">");
+    for (i, note) in notes.iter().enumerate() {
+        let n = i + 1;
+        html.extend_from_slice(format!(
+            r##"<li id="fn-{id_prefix}-{n}">{note} <a class="fn-back" href="#fnref-{id_prefix}-{n}">&#8617;</a></li>"##,
+        ).as_bytes());
+    }
+    html.extend_from_slice(b"</ol>");
+}
@@ -0,0 +1,65 @@
+use std::collections::{HashMap, HashSet};
+use std::fs::File;
+use std::io::Write;
+use std::path::Path;
+use serde_json::json;
+use crate::article::Articles;
+use crate::graph::{Graph, NodeKind};
+use crate::web::asset::generate_library_tags;
+use crate::web::template::TemplateContext;
+use crate::web::SiteSettings;
+
+/// Write the graph page, which draws the graph of the classes with script.js.
+pub fn write_graph_page(site: &SiteSettings, root_path: &Path, articles: &Articles, graph: &Graph) -> Result<(), String> {
+    let path = root_path.join("graph.html");
+    let page = generate_graph_page(site, articles, graph);
+    let mut file = File::create(&path).unwrap();
+    if let Err(_) = file.write_all(page.as_bytes()) {
+        return Err(format!("Error writing to graph page {}.", path.to_str().unwrap()));
+    }
+    Ok(())
+}
+
+pub fn generate_graph_page(site: &SiteSettings, articles: &Articles, graph: &Graph) -> String {
+    let mut context = TemplateContext::new();
+    context.html("HEAD", site.page_head("/graph.html"));
+    context.html("LIBRARIES", generate_library_tags(site));
+    // Escape < so that the graph cannot end the script element.
+    context.html("GRAPH", generate_class_graph_json(articles, graph).replace('<', "\\u003c"));
+    site.templates.render("graph.html", &context)
+}
+
+/// Generate the class graph as JSON with entries "nodes", "edges" and "articles".
+///
+/// The edges of articles are moved to their classes, and the documents are left
+/// out. "articles" maps each article to its class, for the neighbourhood of an
+/// article.
+fn generate_class_graph_json(articles: &Articles, graph: &Graph) -> String {
+    let mut classes = HashMap::new();
+    let mut nodes = vec![];
+    let mut article_classes = serde_json::Map::new();
+    for node in &graph.nodes {
+        match node.kind {
+            NodeKind::Class => {
+                classes.insert(node.id.as_str(), node.key.clone());
+                nodes.push(json!({ "key": node.key, "label": node.label, "style": node.style, "colour": node.colour }));
+            }
+            NodeKind::Article => {
+                let class = articles.get_article(&node.key).unwrap().borrow().get_class().borrow().key.to_string();
+                article_classes.insert(node.key.clone(), json!(class));
+                classes.insert(node.id.as_str(), class);
+            }
+            NodeKind::Document => {}
+        }
+    }
+    let mut added = HashSet::new();
+    let mut edges = vec![];
+    for edge in &graph.edges {
+        if let (Some(source), Some(target)) = (classes.get(edge.source.as_str()), classes.get(edge.target.as_str())) {
+            if source != target && added.insert((source, target, edge.kind)) {
+                edges.push(json!({ "source": source, "target": target, "kind": edge.kind.name() }));
+            }
+        }
+    }
+    json!({ "nodes": nodes, "edges": edges, "articles": article_classes }).to_string()
+}
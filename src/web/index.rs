@@ -1,41 +1,111 @@
+use std::collections::BTreeMap;
 use std::fs;
 use std::fs::File;
 use std::io::Write;
 use std::path::Path;
+use std::rc::Rc;
+use crate::article::Articles;
+use crate::dir::Dir;
+use crate::document::Documents;
+use crate::file::format_date;
+use crate::markup::{escape_html, Markup};
+use crate::web::asset::generate_library_tags;
+use crate::web::template::TemplateContext;
+use crate::web::SiteSettings;
+
+/// Number of recently updated documents listed on the front page.
+const RECENT_DOCUMENT_COUNT: usize = 10;
 
 /// Write the website index file.
 ///
 /// If the user has created an index.html in the root, copy this file. Otherwise,
 /// generate a new one.
-pub fn write_index(project_path: &Path, out_path: &Path) {
+pub fn write_index(site: &SiteSettings, front_page: Option<&Markup>, articles: &Articles, documents: &Documents, tree: &Rc<Dir>, project_path: &Path, out_path: &Path) -> Result<(), String> {
     let index_path = project_path.join("index.html");
     let index_out = out_path.join("index.html");
     if index_path.exists() {
         if index_path.is_file() {
-            fs::copy(&index_path, &index_out).unwrap();
+            if let Err(_) = fs::copy(&index_path, &index_out) {
+                return Err(format!("Error copying index file {}.", index_path.to_str().unwrap()));
+            }
         }
     } else {
-        let index = generate_index();
+        let index = generate_index(site, front_page, articles, documents, tree);
         let mut file = File::create(&index_out).unwrap();
-        file.write_all(index.as_bytes()).unwrap();
+        if let Err(_) = file.write_all(index.as_bytes()) {
+            return Err(format!("Error writing to index file {}.", index_out.to_str().unwrap()));
+        }
     }
+    Ok(())
 }
 
-pub fn generate_index() -> String {
+/// Generate the front page: the introduction, recently updated documents,
+/// top-level directories and the number of classes of each style.
+pub fn generate_index(site: &SiteSettings, front_page: Option<&Markup>, articles: &Articles, documents: &Documents, tree: &Rc<Dir>) -> String {
+    let mut context = TemplateContext::new();
+    context.text("TITLE", &site.title);
+    context.text("DESCRIPTION", site.description.clone().unwrap_or_default());
+    context.html("HEAD", site.page_head("/"));
+    context.html("LIBRARIES", generate_library_tags(site));
     let mut html = vec![];
-    let mut template = include_str!("../../templates/index.html").as_bytes();
-    while template.len() > 0 {
-        if template.starts_with(b"{TITLE}") {
-            let title = b"Title"; // TODO
-            html.extend_from_slice(title);
-            template = &template[7..];
-        } else if template.starts_with(b"{CONTENT}") {
-            html.extend_from_slice(b"Content");
-            template = &template[9..];
-        } else {
-            html.push(template[0]);
-            template = &template[1..];
+    generate_index_content(&mut html, site, front_page, articles, documents, tree);
+    context.html("CONTENT", String::from_utf8(html).unwrap());
+    site.templates.render("index.html", &context)
+}
+
+fn generate_index_content(html: &mut Vec<u8>, site: &SiteSettings, front_page: Option<&Markup>, articles: &Articles, documents: &Documents, tree: &Rc<Dir>) {
+    if let Some(description) = &site.description {
+        html.extend_from_slice(format!(r#"<p class="description">{}</p>"#, escape_html(description)).as_bytes());
+    }
+    if let Some(front_page) = front_page {
+        html.extend_from_slice(format!("<p>{}</p>", front_page.to_html()).as_bytes());
+    }
+    // Recently updated documents.
+    let mut recent: Vec<_> = documents.iter().filter_map(|document| Some((document.modified()?, document))).collect();
+    recent.sort_by(|a, b| b.0.cmp(&a.0));
+    if !recent.is_empty() {
+        html.extend_from_slice(format!(r#"<h2>{}</h2><ul class="recent-documents">"#, escape_html(site.locale.get("RecentlyUpdated"))).as_bytes());
+        for (modified, document) in recent.iter().take(RECENT_DOCUMENT_COUNT) {
+            html.extend_from_slice(format!(
+                r#"<li><a href="{}">{}</a> <time>{}</time></li>"#,
+                escape_html(&site.link(&document.web_path())), escape_html(&document.title), format_date(*modified),
+            ).as_bytes());
+        }
+        html.extend_from_slice(b"</ul>");
+    }
+    // Top-level directories.
+    if !tree.subdirs.is_empty() || !tree.subdocs.is_empty() {
+        html.extend_from_slice(format!(r#"<h2>{}</h2><ul class="top-directories">"#, escape_html(site.locale.get("Documents"))).as_bytes());
+        for dir in tree.subdirs.iter() {
+            html.extend_from_slice(format!(
+                r#"<li><a href="{}">{}</a></li>"#, escape_html(&site.link(&format!("/documents/{}", dir.file_name.to_str().unwrap()))), escape_html(&dir.name),
+            ).as_bytes());
+        }
+        for document in tree.subdocs.iter() {
+            html.extend_from_slice(format!(
+                r#"<li><a href="{}">{}</a></li>"#, escape_html(&site.link(&document.web_path())), escape_html(&document.title),
+            ).as_bytes());
+        }
+        html.extend_from_slice(b"</ul>");
+    }
+    // Class counts.
+    let mut style_counts: BTreeMap<String, usize> = BTreeMap::new();
+    for class in articles.get_classes().values() {
+        let class = class.borrow();
+        if let Some(style) = &class.style {
+            *style_counts.entry(style.to_string()).or_default() += 1;
+        }
+    }
+    html.extend_from_slice(format!(
+        r#"<h2>{}</h2><p class="class-counts">{} {}, {} {}</p>"#,
+        escape_html(site.locale.get("Contents")), articles.get_classes().len(), escape_html(site.locale.get("Classes")),
+        articles.get_articles().len(), escape_html(site.locale.get("Articles")),
+    ).as_bytes());
+    if !style_counts.is_empty() {
+        html.extend_from_slice(b"<ul class=\"class-counts\">");
+        for (style, count) in style_counts {
+            html.extend_from_slice(format!(r#"<li class="{}-style">{}: {}</li>"#, escape_html(&style), escape_html(&style), count).as_bytes());
         }
+        html.extend_from_slice(b"</ul>");
     }
-    String::from_utf8(html).unwrap()
 }
@@ -1,3 +1,7 @@
+use std::fs;
+use crate::locale::Locale;
+use crate::markup::escape_html;
+use crate::web::template::PageTemplates;
 use std::path::Path;
 use serde_json::{Map as JsonMap, Value as JsonValue};
 
@@ -7,10 +11,129 @@ pub mod class_style;
 pub mod document;
 pub mod dirpage;
 pub mod index;
+pub mod table;
+pub mod figure;
+pub mod reference;
+pub mod backlink;
+pub mod footnote;
+pub mod citation;
+pub mod search;
+pub mod sitemap;
+pub mod structured;
+pub mod feed;
+pub mod graph;
+pub mod template;
 //mod name;
 
-pub fn include_index_and_icon(root_path: &Path) -> Result<(), String> {
-    Ok(()) // TODO
+/// Settings that apply to every page of the generated website.
+pub struct SiteSettings {
+    /// Reference front-end libraries from /assets/static/vendor instead of a CDN.
+    pub vendored: bool,
+    /// Show footnotes as margin notes on document pages.
+    pub margin_notes: bool,
+    /// Title of the website.
+    pub title: String,
+    /// Description of the website, used as meta description of the front page.
+    pub description: Option<String>,
+    /// Absolute URL of the website root without a trailing slash, as in
+    /// https://host/team/notes.
+    pub base_url: Option<String>,
+    /// User interface strings of the website.
+    pub locale: Locale,
+    /// Page templates, with the overrides of the project.
+    pub templates: PageTemplates,
+    /// Default colour theme.
+    pub theme: Theme,
+    /// File names of the project stylesheets in /assets/user.
+    pub stylesheets: Vec<String>,
+}
+
+/// Colour theme of the website. Visitors can switch theme with the theme button.
+#[derive(Copy, Clone)]
+pub enum Theme {
+    /// Follow the colour scheme preferred by the system.
+    Auto,
+    Light,
+    Dark,
+}
+
+impl Theme {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Theme::Auto => "auto",
+            Theme::Light => "light",
+            Theme::Dark => "dark",
+        }
+    }
+}
+
+impl SiteSettings {
+
+    /// Path of the website root on the host without a trailing slash, as in
+    /// /team/notes. Empty if the website is hosted at the root.
+    pub fn base_path(&self) -> &str {
+        base_path(self.base_url.as_deref())
+    }
+
+    /// Link to a root-absolute website path, prefixed with the base path.
+    pub fn link(&self, path: &str) -> String {
+        format!("{}{}", self.base_path(), path)
+    }
+
+    /// Absolute URL of a root-absolute website path, if the base URL is known.
+    pub fn url(&self, path: &str) -> Option<String> {
+        self.base_url.as_ref().map(|base_url| format!("{}{}", base_url, path))
+    }
+
+    /// Tags for the `{HEAD}` of the page at `page_path`: the theme, base path and
+    /// locale strings for script.js, the project stylesheets, the canonical URL
+    /// and the feed link.
+    pub fn page_head(&self, page_path: &str) -> String {
+        let mut head = format!("<meta name=\"theme\" content=\"{}\">", self.theme.name());
+        head.push_str(&format!("\n<meta name=\"base-path\" content=\"{}\">", escape_html(self.base_path())));
+        for stylesheet in self.stylesheets.iter() {
+            let href = self.link(&format!("/assets/user/{}", stylesheet));
+            head.push_str(&format!("\n<link rel=\"stylesheet\" type=\"text/css\" href=\"{}\">", escape_html(&href)));
+        }
+        // Escape < so that the strings cannot end the script element.
+        let strings = serde_json::to_string(&self.locale.strings).unwrap().replace('<', "\\u003c");
+        head.push_str(&format!("\n<script id=\"locale\" type=\"application/json\">{}</script>", strings));
+        if let Some(url) = self.url(page_path) {
+            head.push_str(&format!("\n<link rel=\"canonical\" href=\"{}\">", escape_html(&url)));
+            let feed_url = self.url("/atom.xml").unwrap();
+            head.push_str(&format!("\n<link rel=\"alternate\" type=\"application/atom+xml\" href=\"{}\">", escape_html(&feed_url)));
+        }
+        head
+    }
+
+}
+
+/// Path of the website at `base_url` on its host without a trailing slash, as in
+/// /team/notes. Empty if there is no base URL or the website is hosted at the root.
+pub fn base_path(base_url: Option<&str>) -> &str {
+    match base_url {
+        Some(base_url) => {
+            let host_start = base_url.find("://").map(|i| i + 3).unwrap_or(0);
+            match base_url[host_start..].find('/') {
+                Some(i) => &base_url[host_start + i..],
+                None => "",
+            }
+        }
+        None => "",
+    }
+}
+
+/// Copy the favicon from the project directory, if there is one.
+///
+/// The index is written by [index::write_index].
+pub fn include_index_and_icon(project_path: &Path, root_path: &Path) -> Result<(), String> {
+    let icon_path = project_path.join("favicon.ico");
+    if icon_path.is_file() {
+        if let Err(_) = fs::copy(&icon_path, root_path.join("favicon.ico")) {
+            return Err(format!("Error copying icon {}.", icon_path.to_str().unwrap()));
+        }
+    }
+    Ok(())
 }
 
 pub(crate) fn json_map_set_string(map: &mut JsonMap<String, JsonValue>, key: impl Into<String>, value: impl Into<String>) {
@@ -0,0 +1,64 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+use crate::article::{Article, Articles};
+use crate::markup::{escape_html, find_references};
+use crate::web::citation::link_citations;
+use crate::web::document::make_long_name;
+
+/// The article that a reference to a class or an article refers to. Class
+/// references are resolved with the resolution paths.
+pub(crate) fn referenced_article(articles: &Articles, key: &str, resolve_paths: &[String]) -> Option<Rc<RefCell<Article>>> {
+    if key.contains('@') {
+        articles.get_article(key).cloned()
+    } else {
+        articles.get_class(key).map(|class| class.borrow().resolve(resolve_paths))
+    }
+}
+
+/// Label of a reference that has no label of its own: the long name of the
+/// article, with its parameters and prepositions.
+pub(crate) fn reference_name(article: &Article) -> String {
+    let mut html = vec![];
+    make_long_name(&mut html, &article.names[0]);
+    String::from_utf8(html).unwrap()
+}
+
+/// Replace the reference placeholders in HTML with links to the referenced
+/// articles, and link the citations to the bibliography. Class references are
+/// resolved with the resolution paths. Website paths are prefixed with `base`.
+pub(crate) fn resolve_references(html: &str, articles: &Articles, resolve_paths: &[String], base: &str) -> String {
+    let references = find_references(html);
+    if references.is_empty() {
+        return link_citations(html, base);
+    }
+    let mut resolved = String::with_capacity(html.len());
+    let mut offset = 0;
+    for reference in references {
+        resolved.push_str(&html[offset..reference.start]);
+        if let Some(article) = referenced_article(articles, &reference.key, resolve_paths) {
+            let article = article.borrow();
+            let class = article.get_class();
+            let class = class.borrow();
+            let style = if let Some(style) = &class.style {
+                format!(" {}-style", style)
+            } else {
+                String::new()
+            };
+            resolved.push_str(&format!(
+                r#"<a class="ref{}" href="{}/classes/{}.html" data-class="{}" data-article="{}">"#,
+                style, escape_html(base), escape_html(&class.key), escape_html(&class.key), escape_html(&article.key),
+            ));
+            if reference.label.is_empty() {
+                resolved.push_str(&reference_name(&article));
+            } else {
+                resolved.push_str(reference.label);
+            }
+            resolved.push_str("</a>");
+        } else { // Dangling references are reported when validating, keep the label.
+            resolved.push_str(reference.label);
+        }
+        offset = reference.end;
+    }
+    resolved.push_str(&html[offset..]);
+    link_citations(&resolved, base)
+}
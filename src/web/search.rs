@@ -0,0 +1,133 @@
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::fs::File;
+use std::io::Write;
+use std::path::Path;
+use serde_json::{Map as JsonMap, Value as JsonValue};
+use crate::article::Articles;
+use crate::compile::reference::article_markups;
+use crate::document::Documents;
+use crate::markup::strip_markup;
+use crate::name::NameElement;
+use crate::style::Styles;
+
+/// Score of a term found in a name, title or abbreviation.
+const TITLE_SCORE: u64 = 10;
+/// Score of a term found in content.
+const CONTENT_SCORE: u64 = 1;
+
+/// Write the search index to the search directory.
+///
+/// `items.json` lists the searchable items as `[title, href, type]`. Terms are
+/// split into shards by their first character, written to `{shard}.json` as a
+/// map from term to a flat array of item indexes and scores.
+pub fn write_search_index(root_path: &Path, styles: &Styles, articles: &Articles, documents: &Documents) -> Result<(), String> {
+    let search_dir_path = root_path.join("search");
+    if let Err(_) = fs::create_dir(&search_dir_path) {
+        return Err(format!("Error creating search directory {}.", search_dir_path.to_str().unwrap()));
+    }
+    let mut items = vec![];
+    let mut index: HashMap<String, BTreeMap<usize, u64>> = HashMap::new();
+    // Index articles.
+    let mut article_keys: Vec<_> = articles.get_articles().keys().collect();
+    article_keys.sort();
+    for article_key in article_keys {
+        let article = articles.get_article(article_key).unwrap().borrow();
+        let class = article.get_class();
+        let class = class.borrow();
+        let item = items.len();
+        let mut title = String::new();
+        for element in article.names.first().unwrap().iter() {
+            if let NameElement::Name(markup) = element {
+                title.push_str(&strip_markup(&markup.0));
+            }
+        }
+        items.push(JsonValue::Array(vec![
+            JsonValue::String(title.trim().to_string()),
+            JsonValue::String(format!("/classes/{}.html", class.key)),
+            JsonValue::String("article".into()),
+        ]));
+        let mut title_text = String::new();
+        for name in article.names.iter() {
+            for element in name.iter() {
+                match element {
+                    NameElement::Name(markup) | NameElement::Preposition(markup) => title_text.push_str(&strip_markup(&markup.0)),
+                    NameElement::Parameter { markup, .. } => title_text.push_str(&strip_markup(&markup.0)),
+                }
+                title_text.push(' ');
+            }
+        }
+        if let Some(style) = class.style.as_ref().and_then(|style| styles.get(style.as_ref())) {
+            if let Some(abbreviation) = &style.abbreviation {
+                title_text.push_str(abbreviation);
+            }
+        }
+        add_terms(&mut index, item, &title_text, TITLE_SCORE);
+        let mut content_text = String::new();
+        for markup in article_markups(&article) {
+            content_text.push_str(&strip_markup(&markup.0));
+            content_text.push(' ');
+        }
+        add_terms(&mut index, item, &content_text, CONTENT_SCORE);
+    }
+    // Index documents.
+    for document in documents.iter() {
+        let item = items.len();
+        items.push(JsonValue::Array(vec![
+            JsonValue::String(document.title.clone()),
+            JsonValue::String(document.web_path()),
+            JsonValue::String("document".into()),
+        ]));
+        add_terms(&mut index, item, &document.title, TITLE_SCORE);
+        if let Some(description) = &document.description {
+            add_terms(&mut index, item, description, CONTENT_SCORE);
+        }
+    }
+    // Write items and shards.
+    write_json_file(&search_dir_path.join("items.json"), &JsonValue::Array(items))?;
+    let mut shards: BTreeMap<String, JsonMap<String, JsonValue>> = BTreeMap::new();
+    for (term, postings) in index {
+        let mut entries = vec![];
+        for (item, score) in postings {
+            entries.push(JsonValue::from(item));
+            entries.push(JsonValue::from(score));
+        }
+        shards.entry(shard_name(&term)).or_default().insert(term, JsonValue::Array(entries));
+    }
+    for (shard, terms) in shards {
+        write_json_file(&search_dir_path.join(format!("{}.json", shard)), &JsonValue::Object(terms))?;
+    }
+    Ok(())
+}
+
+/// Name of the shard of a term. Must match `searchShardName` in script.js.
+fn shard_name(term: &str) -> String {
+    let first = term.chars().next().unwrap();
+    if first.is_ascii_alphanumeric() {
+        first.to_string()
+    } else {
+        String::from("other")
+    }
+}
+
+/// Split text into lowercase terms. Must match `searchTerms` in script.js.
+fn terms(text: &str) -> impl Iterator<Item = String> + '_ {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|term| term.chars().count() >= 2)
+        .map(|term| term.to_lowercase())
+}
+
+fn add_terms(index: &mut HashMap<String, BTreeMap<usize, u64>>, item: usize, text: &str, score: u64) {
+    for term in terms(text) {
+        *index.entry(term).or_default().entry(item).or_default() += score;
+    }
+}
+
+fn write_json_file(path: &Path, json: &JsonValue) -> Result<(), String> {
+    let json = serde_json::to_string(json).unwrap();
+    let mut file = File::create(path).unwrap();
+    if let Err(_) = file.write_all(json.as_bytes()) {
+        return Err(format!("Error writing to search index file {}.", path.to_str().unwrap()));
+    }
+    Ok(())
+}
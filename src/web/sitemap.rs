@@ -0,0 +1,61 @@
+use std::fs::File;
+use std::io::Write;
+use std::path::Path;
+use std::rc::Rc;
+use crate::article::Articles;
+use crate::dir::Dir;
+use crate::file::format_date;
+use crate::markup::escape_html;
+use crate::web::SiteSettings;
+
+/// Write sitemap.xml with the front page, directory indexes, documents, class
+/// pages and the bibliography page. Requires the base URL.
+pub fn write_sitemap(site: &SiteSettings, root_path: &Path, articles: &Articles, tree: &Rc<Dir>, bibliography: bool) -> Result<(), String> {
+    if site.base_url.is_none() {
+        return Ok(());
+    }
+    let mut xml = String::new();
+    xml.push_str(r#"<?xml version="1.0" encoding="UTF-8"?>"#);
+    xml.push('\n');
+    xml.push_str(r#"<urlset xmlns="http://www.sitemaps.org/schemas/sitemap/0.9">"#);
+    xml.push('\n');
+    push_url(&mut xml, site, "/", None);
+    push_dir_urls(&mut xml, site, tree, "");
+    let mut class_keys: Vec<_> = articles.get_classes().keys().collect();
+    class_keys.sort();
+    for class_key in class_keys {
+        push_url(&mut xml, site, &format!("/classes/{}.html", class_key), None);
+    }
+    if bibliography {
+        push_url(&mut xml, site, "/bibliography.html", None);
+    }
+    xml.push_str("</urlset>\n");
+    let path = root_path.join("sitemap.xml");
+    let mut file = File::create(&path).unwrap();
+    if let Err(_) = file.write_all(xml.as_bytes()) {
+        return Err(format!("Error writing to sitemap {}.", path.to_str().unwrap()));
+    }
+    Ok(())
+}
+
+fn push_dir_urls(xml: &mut String, site: &SiteSettings, dir: &Rc<Dir>, parent_path: &str) {
+    let dir_path = format!("{}/{}", parent_path, dir.file_name.to_str().unwrap());
+    push_url(xml, site, &format!("{}/", dir_path), None);
+    for document in dir.subdocs.iter() {
+        let modified = document.modified().map(format_date);
+        push_url(xml, site, &document.web_path(), modified.as_deref());
+    }
+    for subdir in dir.subdirs.iter() {
+        push_dir_urls(xml, site, subdir, &dir_path);
+    }
+}
+
+fn push_url(xml: &mut String, site: &SiteSettings, path: &str, modified: Option<&str>) {
+    xml.push_str("  <url><loc>");
+    xml.push_str(&escape_html(&site.url(path).unwrap()));
+    xml.push_str("</loc>");
+    if let Some(modified) = modified {
+        xml.push_str(&format!("<lastmod>{}</lastmod>", modified));
+    }
+    xml.push_str("</url>\n");
+}
@@ -0,0 +1,170 @@
+//! Structured data for search engines, written as schema.org JSON-LD.
+
+use std::collections::BTreeSet;
+use serde_json::{json, Map as JsonMap, Value as JsonValue};
+use crate::article::{Article, Articles, Class};
+use crate::document::{Document, DocumentElement, PanelElement};
+use crate::file::format_timestamp;
+use crate::markup::strip_markup;
+use crate::name::NameElement;
+use crate::relation::RelationClass;
+use crate::web::SiteSettings;
+
+/// Generate the JSON-LD script of a document page.
+///
+/// The document is a `LearningResource` and `Article` that is part of its
+/// directory and of the website, and is about the classes of the articles it
+/// links to, each described as a `DefinedTerm`.
+pub(crate) fn generate_document_json_ld(site: &SiteSettings, articles: &Articles, resolve_paths: &[String], document: &Document) -> String {
+    let mut data = JsonMap::new();
+    data.insert("@context".into(), json!("https://schema.org"));
+    data.insert("@type".into(), json!(["LearningResource", "Article"]));
+    data.insert("name".into(), json!(document.title));
+    data.insert("headline".into(), json!(document.title));
+    if let Some(description) = &document.description {
+        data.insert("description".into(), json!(description));
+    }
+    if !document.authors.is_empty() {
+        let authors: Vec<_> = document.authors.iter().map(|author| json!({ "@type": "Person", "name": author })).collect();
+        data.insert("author".into(), JsonValue::Array(authors));
+    }
+    if let Some(published) = document.published() {
+        data.insert("datePublished".into(), json!(published));
+    }
+    if let Some(modified) = document.modified() {
+        data.insert("dateModified".into(), json!(format_timestamp(modified)));
+    }
+    if let Some(url) = site.url(&document.web_path()) {
+        data.insert("url".into(), json!(url));
+    }
+    // The document is part of its directory, which is part of the website.
+    let mut website = JsonMap::new();
+    website.insert("@type".into(), json!("WebSite"));
+    website.insert("name".into(), json!(site.title));
+    if let Some(url) = site.url("/") {
+        website.insert("url".into(), json!(url));
+    }
+    let dir = document.parent_dir.upgrade().unwrap();
+    let mut dir_path = String::new();
+    for dir in document.dirtrail() {
+        dir_path.push('/');
+        dir_path.push_str(dir.file_name.to_str().unwrap());
+    }
+    let mut collection = JsonMap::new();
+    collection.insert("@type".into(), json!("CollectionPage"));
+    collection.insert("name".into(), json!(dir.name));
+    if let Some(url) = site.url(&format!("{}/", dir_path)) {
+        collection.insert("url".into(), json!(url));
+    }
+    collection.insert("isPartOf".into(), JsonValue::Object(website));
+    data.insert("isPartOf".into(), JsonValue::Object(collection));
+    // The document is about the classes it links to.
+    let mut class_keys = vec![];
+    for element in document.structure.iter() {
+        if let DocumentElement::Panel(elements) = element {
+            for element in elements {
+                let class = match element {
+                    PanelElement::ArticleLink { key, .. } => articles.get_article(key).map(|article| article.borrow().get_class()),
+                    PanelElement::ClassLink { key, .. } => articles.get_class(key).cloned(),
+                    PanelElement::Heading { .. } => None,
+                };
+                if let Some(class) = class {
+                    let key = class.borrow().key.clone();
+                    if !class_keys.contains(&key) {
+                        class_keys.push(key);
+                    }
+                }
+            }
+        }
+    }
+    let terms: Vec<_> = class_keys.iter().map(|key| {
+        let class = articles.get_class(key).unwrap().borrow();
+        JsonValue::Object(generate_defined_term(site, &class, &class.resolve(resolve_paths).borrow()))
+    }).collect();
+    if !terms.is_empty() {
+        data.insert("about".into(), JsonValue::Array(terms));
+    }
+    json_ld_script(&JsonValue::Object(data))
+}
+
+/// Generate the JSON-LD script of a class page, which describes the class as a `DefinedTerm`.
+pub(crate) fn generate_class_json_ld(site: &SiteSettings, resolve_paths: &[String], class: &Class) -> String {
+    let mut term = generate_defined_term(site, class, &class.resolve(resolve_paths).borrow());
+    term.insert("@context".into(), json!("https://schema.org"));
+    json_ld_script(&JsonValue::Object(term))
+}
+
+/// Describe a class as a `DefinedTerm` named by an article of it.
+///
+/// Classes that the class is related to, as in "a group is a monoid", are the
+/// terms it is part of, and the classes that the relations take as arguments, as
+/// in "a subgroup of a group is a group", are what it is about. Both are sorted.
+fn generate_defined_term(site: &SiteSettings, class: &Class, article: &Article) -> JsonMap<String, JsonValue> {
+    let mut term = JsonMap::new();
+    term.insert("@type".into(), json!("DefinedTerm"));
+    let mut name = String::new();
+    for element in article.names.first().unwrap().iter() {
+        if let NameElement::Name(markup) = element {
+            name.push_str(&strip_markup(&markup.0));
+        }
+    }
+    term.insert("name".into(), json!(name.trim()));
+    term.insert("termCode".into(), json!(class.key.as_ref()));
+    if let Some(url) = site.url(&format!("/classes/{}.html", class.key)) {
+        term.insert("url".into(), json!(url));
+    }
+    let mut part_of = BTreeSet::new();
+    let mut about = BTreeSet::new();
+    for relation in class.relations.iter() {
+        let left = relation_class_key(&relation.left);
+        let right = relation_class_key(&relation.right);
+        if left != class.key.as_ref() {
+            continue;
+        }
+        if right != left {
+            part_of.insert(right);
+        }
+        relation_arguments(&relation.left, &mut about);
+        relation_arguments(&relation.right, &mut about);
+    }
+    about.remove(class.key.as_ref());
+    about.retain(|key| !part_of.contains(key));
+    if !part_of.is_empty() {
+        term.insert("isPartOf".into(), JsonValue::Array(part_of.iter().map(|key| term_reference(site, key)).collect()));
+    }
+    if !about.is_empty() {
+        term.insert("about".into(), JsonValue::Array(about.iter().map(|key| term_reference(site, key)).collect()));
+    }
+    term
+}
+
+fn relation_class_key(class: &RelationClass) -> &str {
+    match class {
+        RelationClass::Name(name) => name,
+        RelationClass::Qual { name, .. } => name,
+    }
+}
+
+/// Add the keys of the classes that a relation class takes as arguments.
+fn relation_arguments<'a>(class: &'a RelationClass, keys: &mut BTreeSet<&'a str>) {
+    if let RelationClass::Qual { arguments, .. } = class {
+        for argument in arguments.iter() {
+            keys.insert(relation_class_key(argument));
+            relation_arguments(argument, keys);
+        }
+    }
+}
+
+/// A `DefinedTerm` that refers to the class with the key `key`.
+fn term_reference(site: &SiteSettings, key: &str) -> JsonValue {
+    match site.url(&format!("/classes/{}.html", key)) {
+        Some(url) => json!({ "@type": "DefinedTerm", "@id": url, "url": url, "termCode": key }),
+        None => json!({ "@type": "DefinedTerm", "termCode": key }),
+    }
+}
+
+fn json_ld_script(data: &JsonValue) -> String {
+    // Escape < so that the data cannot end the script element.
+    let json = serde_json::to_string(data).unwrap().replace('<', "\\u003c");
+    format!(r#"<script type="application/ld+json">{}</script>"#, json)
+}
@@ -0,0 +1,77 @@
+use crate::markup::Markup;
+use crate::table::{Alignment, Table};
+
+/// Generate the HTML of a table.
+pub(crate) fn generate_table(html: &mut Vec<u8>, table: &Table) {
+    html.extend_from_slice(b"<table>");
+    if let Some(caption) = &table.caption {
+        html.extend_from_slice(format!("<caption>{}</caption>", caption.to_html()).as_bytes());
+    }
+    if !table.header.is_empty() {
+        html.extend_from_slice(b"<thead>");
+        for row in &table.header {
+            generate_table_row(html, table, row, "th");
+        }
+        html.extend_from_slice(b"</thead>");
+    }
+    html.extend_from_slice(b"<tbody>");
+    for row in &table.rows {
+        generate_table_row(html, table, row, "td");
+    }
+    html.extend_from_slice(b"</tbody></table>");
+}
+
+fn generate_table_row(html: &mut Vec<u8>, table: &Table, row: &[Markup], cell_element: &str) {
+    html.extend_from_slice(b"<tr>");
+    for (column, cell) in row.iter().enumerate() {
+        match table.alignment(column) {
+            Alignment::Left => html.extend_from_slice(format!("<{cell_element}>").as_bytes()),
+            Alignment::Center => html.extend_from_slice(format!(r#"<{cell_element} class="center">"#).as_bytes()),
+            Alignment::Right => html.extend_from_slice(format!(r#"<{cell_element} class="right">"#).as_bytes()),
+        }
+        html.extend_from_slice(cell.to_html().as_bytes());
+        html.extend_from_slice(format!("</{cell_element}>").as_bytes());
+    }
+    html.extend_from_slice(b"</tr>");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cells(texts: &[&str]) -> Vec<Markup> {
+        texts.iter().map(|text| Markup::raw(text)).collect()
+    }
+
+    fn render(table: &Table) -> String {
+        let mut html = vec![];
+        generate_table(&mut html, table);
+        String::from_utf8(html).unwrap()
+    }
+
+    #[test]
+    fn header_rows_and_alignments() {
+        let table = Table {
+            header: vec![cells(&["a", "b", "c"])],
+            rows: vec![cells(&["1", "2", "3"]), cells(&["4"])],
+            alignments: vec![Alignment::Left, Alignment::Right],
+            caption: None,
+        };
+        assert_eq!(render(&table), concat!(
+            r#"<table><thead><tr><th>a</th><th class="right">b</th><th>c</th></tr></thead>"#,
+            r#"<tbody><tr><td>1</td><td class="right">2</td><td>3</td></tr><tr><td>4</td></tr></tbody></table>"#,
+        ));
+    }
+
+    #[test]
+    fn caption_and_inline_styles() {
+        let table = Table {
+            header: vec![],
+            rows: vec![cells(&["<inline-emphasis>x</inline-emphasis>"])],
+            alignments: vec![Alignment::Center],
+            caption: Some(Markup::raw("Values")),
+        };
+        assert_eq!(render(&table), r#"<table><caption>Values</caption><tbody><tr><td class="center"><em>x</em></td></tr></tbody></table>"#);
+    }
+
+}
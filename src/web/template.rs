@@ -0,0 +1,442 @@
+//! Page templates.
+//!
+//! Pages are generated from the HTML templates in `templates/`. A project can
+//! override any of them by placing a file with the same name in its own
+//! `templates` directory. Templates are HTML with the following tags:
+//!
+//! - `{NAME}` inserts a value. Text is escaped, HTML is inserted as is.
+//! - `{#if NAME}…{#else}…{/if}` inserts its content if the value is not empty.
+//! - `{#each NAME}…{/each}` inserts its content once for each item of a list.
+//! - `{L:key}` inserts a string of the locale, when the template is read. Keys
+//!   that the locale does not define are reported as errors.
+//! - `{BASE}` inserts the base path of the website, when the template is read.
+//!   Links to website paths are written as `{BASE}/path`.
+//!
+//! Each template has a fixed set of names. Other names are reported as errors
+//! when the template is read. Page templates must contain `{HEAD}`, which
+//! inserts the tags that script.js needs.
+
+use std::collections::HashMap;
+use std::fs::read_dir;
+use std::path::Path;
+use crate::file::read_file_to_string;
+use crate::locale::Locale;
+use crate::markup::escape_html;
+
+/// Built-in templates with the names they may use.
+const BUILT_IN_TEMPLATES: &[(&str, &str, &[&str])] = &[
+    ("template.html", include_str!("../../templates/template.html"), &["HEAD", "TITLE", "LIBRARIES", "NAV", "HREF", "NAME", "STRUCTURED-DATA", "BODY-CLASS", "RESOLUTION-PATHS", "OVERVIEW", "DETAILS"]),
+    ("dirpage.html", include_str!("../../templates/dirpage.html"), &["HEAD", "TITLE", "LIBRARIES", "NAV", "HREF", "NAME", "CONTENT"]),
+    ("index.html", include_str!("../../templates/index.html"), &["HEAD", "TITLE", "DESCRIPTION", "LIBRARIES", "CONTENT"]),
+    ("class.html", include_str!("../../templates/class.html"), &["HEAD", "TITLE", "LIBRARIES", "NAV", "HREF", "NAME", "STRUCTURED-DATA", "ARTICLES"]),
+    ("bibliography.html", include_str!("../../templates/bibliography.html"), &["HEAD", "LIBRARIES", "ENTRIES", "KEY", "REFERENCE"]),
+    ("graph.html", include_str!("../../templates/graph.html"), &["HEAD", "LIBRARIES", "GRAPH"]),
+    ("article-link.html", include_str!("../../templates/article-link.html"), &["STYLE", "ARTICLE", "SYMBOL", "ABBREVIATION", "NAME", "INDEX"]),
+    ("article-link-symbol.html", include_str!("../../templates/article-link-symbol.html"), &["SYMBOL"]),
+    ("article-link-abbreviation.html", include_str!("../../templates/article-link-abbreviation.html"), &["TEXT"]),
+    ("article-link-index.html", include_str!("../../templates/article-link-index.html"), &["TEXT"]),
+    ("article-preload.html", include_str!("../../templates/article-preload.html"), &["ARTICLE", "CLASS", "STYLE", "TYPE", "SYMBOL", "NAMES", "CONTENT", "BACKLINKS", "LINKS"]),
+];
+
+/// Parsed page templates by file name.
+pub struct PageTemplates {
+    templates: HashMap<&'static str, PageTemplate>,
+}
+
+struct PageTemplate {
+    nodes: Vec<Node>,
+}
+
+enum Node {
+    Text(String),
+    Value(String),
+    If { name: String, then: Vec<Node>, otherwise: Vec<Node> },
+    Each { name: String, body: Vec<Node> },
+}
+
+enum Token {
+    Text(String),
+    Value(String),
+    If(String),
+    Else,
+    EndIf,
+    Each(String),
+    EndEach,
+}
+
+/// A value inserted into a template.
+pub enum TemplateValue {
+    /// Text, which is escaped.
+    Text(String),
+    /// HTML, which is inserted as is.
+    Html(String),
+    /// A list of items for `{#each}`.
+    List(Vec<TemplateContext>),
+}
+
+/// The values of the names used by a template.
+///
+/// Within `{#each}`, names are looked up in the item first and then in the
+/// enclosing contexts.
+#[derive(Default)]
+pub struct TemplateContext {
+    values: HashMap<&'static str, TemplateValue>,
+}
+
+impl TemplateContext {
+
+    pub fn new() -> Self {
+        TemplateContext::default()
+    }
+
+    /// Set a text value.
+    pub fn text(&mut self, name: &'static str, text: impl Into<String>) {
+        self.values.insert(name, TemplateValue::Text(text.into()));
+    }
+
+    /// Set an HTML value.
+    pub fn html(&mut self, name: &'static str, html: impl Into<String>) {
+        self.values.insert(name, TemplateValue::Html(html.into()));
+    }
+
+    /// Set a list value.
+    pub fn list(&mut self, name: &'static str, items: Vec<TemplateContext>) {
+        self.values.insert(name, TemplateValue::List(items));
+    }
+
+}
+
+/// Read the built-in page templates and the overrides in `dir`, if it exists,
+/// with the strings of `locale` and the base path `base_path`.
+pub fn read_page_templates(dir: &Path, locale: &Locale, base_path: &str) -> Result<PageTemplates, String> {
+    let mut templates = HashMap::new();
+    for (file_name, source, names) in BUILT_IN_TEMPLATES {
+        templates.insert(*file_name, PageTemplate::parse(file_name, source, names, locale, base_path)?);
+    }
+    if !dir.is_dir() {
+        return Ok(PageTemplates { templates });
+    }
+    let dir_entries = match read_dir(dir) {
+        Ok(dir_entries) => dir_entries,
+        Err(e) => return Err(format!("Error reading template directory {}: {}", dir.display(), e)),
+    };
+    for dir_entry in dir_entries {
+        let dir_entry = match dir_entry {
+            Ok(dir_entry) => dir_entry,
+            Err(e) => return Err(format!("Error reading template directory {}: {}", dir.display(), e)),
+        };
+        let file_name = dir_entry.file_name();
+        let file_name = match file_name.to_str() {
+            Some(file_name) => file_name,
+            None => return Err(format!("Template file name {} in {} is not valid UTF-8.", file_name.to_string_lossy(), dir.display())),
+        };
+        let file_path = dir.join(file_name);
+        match BUILT_IN_TEMPLATES.iter().find(|(name, _, _)| *name == file_name) {
+            Some((name, _, names)) => {
+                let source = read_file_to_string(&file_path, "page template")?;
+                templates.insert(*name, PageTemplate::parse(&file_path.display().to_string(), &source, names, locale, base_path)?);
+                eprintln!("Read page template {}", file_path.display());
+            }
+            None => {
+                eprintln!("[Warning] {} does not override a page template and is ignored.", file_path.display());
+            }
+        }
+    }
+    Ok(PageTemplates { templates })
+}
+
+impl PageTemplates {
+
+    /// Render the template with the file name `name`.
+    pub fn render(&self, name: &str, context: &TemplateContext) -> String {
+        let template = self.templates.get(name).unwrap();
+        let mut html = String::new();
+        render_nodes(&mut html, &template.nodes, &mut vec![context]);
+        html
+    }
+
+}
+
+impl PageTemplate {
+
+    fn parse(file_name: &str, source: &str, names: &[&str], locale: &Locale, base_path: &str) -> Result<PageTemplate, String> {
+        let tokens = tokenize(file_name, source, names, locale, base_path)?;
+        let mut i = 0;
+        let nodes = parse_nodes(file_name, &tokens, &mut i)?;
+        if let Some((token, line)) = tokens.get(i) {
+            let tag = match token {
+                Token::Else => "{#else}",
+                Token::EndIf => "{/if}",
+                _ => "{/each}",
+            };
+            return Err(format!("Unexpected {} on line {} of template {}.", tag, line, file_name));
+        }
+        // Without {HEAD}, script.js would not find the tags it needs.
+        if names.contains(&"HEAD") && !uses_name(&nodes, "HEAD") {
+            return Err(format!("Template {} must contain {{HEAD}} in its head element.", file_name));
+        }
+        Ok(PageTemplate { nodes })
+    }
+
+}
+
+/// Split a template into text and tags, with the locale strings and the base
+/// path inserted into the text. Each token is paired with its line.
+fn tokenize(file_name: &str, source: &str, names: &[&str], locale: &Locale, base_path: &str) -> Result<Vec<(Token, usize)>, String> {
+    let mut tokens = vec![];
+    let mut text = String::new();
+    let mut line = 1;
+    let mut rest = source;
+    while let Some(c) = rest.chars().next() {
+        if c == '{' {
+            if let Some(end) = rest.find('}') {
+                let tag = &rest[1..end];
+                if let Some(key) = tag.strip_prefix("L:").filter(|key| is_locale_key(key)) {
+                    let string = match locale.strings.get(key) {
+                        Some(string) => string,
+                        None => return Err(format!("Unknown locale string {{L:{}}} on line {} of template {}. The locale {} does not define {}.", key, line, file_name, locale.language, key)),
+                    };
+                    text.push_str(&escape_html(string));
+                    rest = &rest[end + 1..];
+                    continue;
+                }
+                if tag == "BASE" {
+                    text.push_str(&escape_html(base_path));
+                    rest = &rest[end + 1..];
+                    continue;
+                }
+                if let Some(token) = parse_tag(file_name, line, tag, names)? {
+                    if !text.is_empty() {
+                        tokens.push((Token::Text(text), line));
+                        text = String::new();
+                    }
+                    tokens.push((token, line));
+                    rest = &rest[end + 1..];
+                    continue;
+                }
+            }
+        }
+        if c == '\n' {
+            line += 1;
+        }
+        text.push(c);
+        rest = &rest[c.len_utf8()..];
+    }
+    if !text.is_empty() {
+        tokens.push((Token::Text(text), line));
+    }
+    Ok(tokens)
+}
+
+/// Parse the inside of braces. Returns None if it is not a tag, as in `{x}` in a script.
+fn parse_tag(file_name: &str, line: usize, tag: &str, names: &[&str]) -> Result<Option<Token>, String> {
+    let check_name = |name: &str| {
+        if names.contains(&name) {
+            Ok(name.to_string())
+        } else {
+            Err(format!("Unknown placeholder {{{}}} on line {} of template {}. Known placeholders are: {}.", name, line, file_name, names.join(", ")))
+        }
+    };
+    if tag.contains(|c| c == '{' || c == '\n') {
+        Ok(None)
+    } else if let Some(name) = tag.strip_prefix("#if ") {
+        Ok(Some(Token::If(check_name(name.trim())?)))
+    } else if let Some(name) = tag.strip_prefix("#each ") {
+        Ok(Some(Token::Each(check_name(name.trim())?)))
+    } else if tag == "#else" {
+        Ok(Some(Token::Else))
+    } else if tag == "/if" {
+        Ok(Some(Token::EndIf))
+    } else if tag == "/each" {
+        Ok(Some(Token::EndEach))
+    } else if tag.starts_with('#') || tag.starts_with('/') {
+        Err(format!("Unknown tag {{{}}} on line {} of template {}.", tag, line, file_name))
+    } else if is_placeholder_name(tag) {
+        Ok(Some(Token::Value(check_name(tag)?)))
+    } else {
+        Ok(None)
+    }
+}
+
+fn is_locale_key(key: &str) -> bool {
+    !key.is_empty() && key.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+fn is_placeholder_name(name: &str) -> bool {
+    name.starts_with(|c: char| c.is_ascii_uppercase())
+        && name.chars().all(|c| c.is_ascii_uppercase() || c.is_ascii_digit() || c == '-' || c == '_')
+}
+
+/// Parse nodes until the end or until a token that closes a block.
+fn parse_nodes(file_name: &str, tokens: &[(Token, usize)], i: &mut usize) -> Result<Vec<Node>, String> {
+    let mut nodes = vec![];
+    while *i < tokens.len() {
+        let (token, line) = &tokens[*i];
+        match token {
+            Token::Text(text) => nodes.push(Node::Text(text.clone())),
+            Token::Value(name) => nodes.push(Node::Value(name.clone())),
+            Token::If(name) => {
+                *i += 1;
+                let then = parse_nodes(file_name, tokens, i)?;
+                let mut otherwise = vec![];
+                if let Some((Token::Else, _)) = tokens.get(*i) {
+                    *i += 1;
+                    otherwise = parse_nodes(file_name, tokens, i)?;
+                }
+                if !matches!(tokens.get(*i), Some((Token::EndIf, _))) {
+                    return Err(format!("{{#if {}}} on line {} of template {} is not closed by {{/if}}.", name, line, file_name));
+                }
+                nodes.push(Node::If { name: name.clone(), then, otherwise });
+            }
+            Token::Each(name) => {
+                *i += 1;
+                let body = parse_nodes(file_name, tokens, i)?;
+                if !matches!(tokens.get(*i), Some((Token::EndEach, _))) {
+                    return Err(format!("{{#each {}}} on line {} of template {} is not closed by {{/each}}.", name, line, file_name));
+                }
+                nodes.push(Node::Each { name: name.clone(), body });
+            }
+            Token::Else | Token::EndIf | Token::EndEach => return Ok(nodes),
+        }
+        *i += 1;
+    }
+    Ok(nodes)
+}
+
+/// Whether `name` is used anywhere in `nodes`.
+fn uses_name(nodes: &[Node], name: &str) -> bool {
+    nodes.iter().any(|node| match node {
+        Node::Text(_) => false,
+        Node::Value(value) => value == name,
+        Node::If { name: condition, then, otherwise } => condition == name || uses_name(then, name) || uses_name(otherwise, name),
+        Node::Each { name: list, body } => list == name || uses_name(body, name),
+    })
+}
+
+fn render_nodes<'a>(html: &mut String, nodes: &[Node], scopes: &mut Vec<&'a TemplateContext>) {
+    for node in nodes {
+        match node {
+            Node::Text(text) => html.push_str(text),
+            Node::Value(name) => match lookup(scopes, name) {
+                Some(TemplateValue::Text(text)) => html.push_str(&escape_html(text)),
+                Some(TemplateValue::Html(value)) => html.push_str(value),
+                Some(TemplateValue::List(_)) | None => {}
+            },
+            Node::If { name, then, otherwise } => {
+                let condition = match lookup(scopes, name) {
+                    Some(TemplateValue::Text(value)) | Some(TemplateValue::Html(value)) => !value.is_empty(),
+                    Some(TemplateValue::List(items)) => !items.is_empty(),
+                    None => false,
+                };
+                render_nodes(html, if condition { then } else { otherwise }, scopes);
+            }
+            Node::Each { name, body } => {
+                if let Some(TemplateValue::List(items)) = lookup(scopes, name) {
+                    for item in items {
+                        scopes.push(item);
+                        render_nodes(html, body, scopes);
+                        scopes.pop();
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn lookup<'a>(scopes: &[&'a TemplateContext], name: &str) -> Option<&'a TemplateValue> {
+    scopes.iter().rev().find_map(|scope| scope.values.get(name))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeMap;
+
+    const NAMES: &[&str] = &["A", "TEXT", "HTML", "ROWS", "CELLS", "CELL", "ROW"];
+
+    fn locale() -> Locale {
+        Locale { language: "en".to_string(), strings: BTreeMap::from([("Home".to_string(), "Home & <away>".to_string())]) }
+    }
+
+    fn parse(source: &str) -> Result<PageTemplate, String> {
+        PageTemplate::parse("test.html", source, NAMES, &locale(), "/notes")
+    }
+
+    fn render(source: &str, context: &TemplateContext) -> String {
+        let mut html = String::new();
+        render_nodes(&mut html, &parse(source).unwrap().nodes, &mut vec![context]);
+        html
+    }
+
+    #[test]
+    fn if_and_else() {
+        let source = "{#if A}yes {A}{#else}no{/if}";
+        let mut context = TemplateContext::new();
+        assert_eq!(render(source, &context), "no");
+        context.text("A", "");
+        assert_eq!(render(source, &context), "no");
+        context.text("A", "a");
+        assert_eq!(render(source, &context), "yes a");
+        context.list("A", vec![]);
+        assert_eq!(render(source, &context), "no");
+    }
+
+    #[test]
+    fn nested_each() {
+        let row = |name: &str, cells: &[&str]| {
+            let mut row = TemplateContext::new();
+            row.text("ROW", name);
+            row.list("CELLS", cells.iter().map(|cell| {
+                let mut context = TemplateContext::new();
+                context.text("CELL", *cell);
+                context
+            }).collect());
+            row
+        };
+        let mut context = TemplateContext::new();
+        context.text("A", "!");
+        context.list("ROWS", vec![row("x", &["1", "2"]), row("y", &[])]);
+        // Names missing from an item are looked up in the enclosing contexts.
+        assert_eq!(render("{#each ROWS}{ROW}:{#each CELLS}{CELL}{ROW}{A}{/each};{/each}", &context), "x:1x!2x!;y:;");
+    }
+
+    #[test]
+    fn text_is_escaped_and_html_is_not() {
+        let mut context = TemplateContext::new();
+        context.text("TEXT", "<b>a & \"b\"</b>");
+        context.html("HTML", "<b>a &amp; b</b>");
+        assert_eq!(render("<p title=\"{TEXT}\">{HTML}</p>", &context), "<p title=\"&lt;b&gt;a &amp; &quot;b&quot;&lt;/b&gt;\"><b>a &amp; b</b></p>");
+    }
+
+    #[test]
+    fn locale_strings_and_base_path() {
+        assert_eq!(render(r#"<a href="{BASE}/">{L:Home}</a>"#, &TemplateContext::new()), r#"<a href="/notes/">Home &amp; &lt;away&gt;</a>"#);
+    }
+
+    #[test]
+    fn braces_that_are_not_tags_are_left_alone() {
+        let source = "<script>if (a) { b(); } let c = {x};</script>{ A }{}";
+        assert_eq!(render(source, &TemplateContext::new()), source);
+    }
+
+    #[test]
+    fn errors() {
+        let error = |source: &str| parse(source).err().unwrap();
+        assert!(error("a\n{NAME}").starts_with("Unknown placeholder {NAME} on line 2 of template test.html."));
+        assert!(error("{#if NAME}{/if}").starts_with("Unknown placeholder {NAME}"));
+        assert!(error("\n{L:Away}").starts_with("Unknown locale string {L:Away} on line 2 of template test.html."));
+        assert_eq!(error("{#if A}a"), "{#if A} on line 1 of template test.html is not closed by {/if}.");
+        assert_eq!(error("{#each ROWS}a{/if}"), "{#each ROWS} on line 1 of template test.html is not closed by {/each}.");
+        assert_eq!(error("a\n{/if}"), "Unexpected {/if} on line 2 of template test.html.");
+        assert_eq!(error("{#else}"), "Unexpected {#else} on line 1 of template test.html.");
+        assert_eq!(error("{#unless A}"), "Unknown tag {#unless A} on line 1 of template test.html.");
+    }
+
+    #[test]
+    fn page_templates_must_contain_head() {
+        let error = PageTemplate::parse("page.html", "<head></head>", &["HEAD"], &locale(), "").err().unwrap();
+        assert_eq!(error, "Template page.html must contain {HEAD} in its head element.");
+        assert!(PageTemplate::parse("page.html", "<head>{HEAD}</head>", &["HEAD"], &locale(), "").is_ok());
+    }
+
+}